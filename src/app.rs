@@ -6,6 +6,7 @@ include!("app/10_impl_core.rs");
 include!("app/11_impl_terminal_helpers.rs");
 include!("app/12_impl_settings.rs");
 include!("app/13_impl_terminal_view.rs");
+include!("app/14_impl_replay_view.rs");
 include!("app/20_tiles.rs");
 include!("app/21_impl_file_manager_view.rs");
 include!("app/22_impl_downloads_window.rs");
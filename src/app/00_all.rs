@@ -17,8 +17,14 @@ use egui_tiles::{
 
 use crate::async_config::AsyncConfigSaver;
 use crate::config;
-use crate::model::ConnectionSettings;
+use crate::known_hosts;
+use crate::model::{
+    ConnectionProtocol, ConnectionSettings, DynamicForward, EnvVar, HostKeyPolicy, IdentityFile,
+    JumpHostSettings, KnownHostsTarget, LocalForward, ProxyKind, ProxySettings, RemoteForward,
+    DEFAULT_TERMINAL_TYPE,
+};
 use crate::ssh::{self, SftpUiMessage, SftpWorkerMessage, UiMessage, WorkerMessage};
+use crate::ssh_config;
 use crate::terminal_themes::ThemeRegistry;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Default)]
@@ -32,6 +38,12 @@ enum PersistedPaneKind {
         source_connection_group_id: u64,
         path: String,
     },
+    Replay {
+        path: PathBuf,
+    },
+    LocalShell {
+        command: String,
+    },
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -98,6 +110,66 @@ enum TerminalGroupReconnectPlan {
     },
 }
 
+const AUTO_RECONNECT_BASE_DELAY: Duration = Duration::from_secs(2);
+const AUTO_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Retry state for a terminal whose session dropped without the user asking.
+#[derive(Clone, Debug, Default)]
+struct AutoReconnect {
+    /// Attempts already started since the connection was lost.
+    attempt: u32,
+    /// When the next attempt starts; `None` while one is in flight.
+    due: Option<Instant>,
+    /// Why the connection (or the last attempt) ended, shown ahead of the countdown.
+    reason: String,
+}
+
+impl AutoReconnect {
+    /// Exponential backoff capped at a minute. `jitter` in `[0, 1)` picks a point in the upper
+    /// half of the window so tabs that dropped together do not all redial at once.
+    fn delay(attempt: u32, jitter: f64) -> Duration {
+        AUTO_RECONNECT_BASE_DELAY
+            .saturating_mul(1u32 << attempt.min(16))
+            .min(AUTO_RECONNECT_MAX_DELAY)
+            .mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+    }
+
+    fn random_jitter() -> f64 {
+        use std::hash::BuildHasher;
+        let bits = std::collections::hash_map::RandomState::new().hash_one(Instant::now());
+        (bits >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Only failures a later attempt could plausibly fix are retried.
+    fn retries_after(kind: ssh::IssueKind) -> bool {
+        matches!(kind, ssh::IssueKind::Transport | ssh::IssueKind::Unknown)
+    }
+
+    fn schedule(&mut self, reason: String) {
+        self.due = Some(Instant::now() + Self::delay(self.attempt, Self::random_jitter()));
+        self.reason = reason;
+    }
+
+    fn seconds_left(&self, now: Instant) -> Option<u64> {
+        let due = self.due?;
+        Some(due.saturating_duration_since(now).as_secs_f32().ceil() as u64)
+    }
+
+    fn countdown_status(&self, now: Instant) -> String {
+        let secs = self.seconds_left(now).unwrap_or(0);
+        let reason = self.reason.trim();
+        let reason = if reason.is_empty() {
+            "Connection lost."
+        } else {
+            reason
+        };
+        format!(
+            "{reason} Reconnecting in {secs}s (attempt {}).",
+            self.attempt + 1
+        )
+    }
+}
+
 #[derive(Clone, Copy)]
 struct UiTheme {
     bg: Color32,
@@ -472,6 +544,7 @@ struct AuthDialog {
     profile_name: Option<String>,
     instructions: String,
     prompts: Vec<ssh::AuthPromptItem>,
+    key_path: Option<String>,
     responses: Vec<String>,
     just_opened: bool,
     remember_key_passphrase: bool,
@@ -491,6 +564,14 @@ struct UploadConflictDialog {
     apply_to_all: bool,
 }
 
+struct SshConfigImportDialog {
+    source: PathBuf,
+    hosts: Vec<(ssh_config::ImportedHost, bool)>,
+    warnings: Vec<String>,
+    overwrite_existing: bool,
+    summary: Option<ssh_config::MergeSummary>,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum SettingsPage {
     Autostart,
@@ -500,6 +581,8 @@ enum SettingsPage {
     UiTheme,
     Updates,
     TerminalColors,
+    Network,
+    KnownHosts,
     ProfilesAndAccount,
 }
 
@@ -513,6 +596,8 @@ impl SettingsPage {
             Self::UiTheme => "UI Theme",
             Self::Updates => "Updates",
             Self::TerminalColors => "Terminal Colors",
+            Self::Network => "Network",
+            Self::KnownHosts => "Known Hosts",
             Self::ProfilesAndAccount => "Profiles and Account",
         }
     }
@@ -528,6 +613,61 @@ struct SettingsDialog {
     remember_key_passphrase: bool,
     draft: ConnectionSettings,
     just_opened: bool,
+    ssh_config_export_status: Option<String>,
+    layout_name: String,
+    layout_status: Option<String>,
+    /// Parsed certificate for the draft, keyed by path and modification time.
+    certificate_preview: Option<CertificatePreview>,
+    known_hosts: KnownHostsManager,
+}
+
+struct CertificatePreview {
+    path: PathBuf,
+    modified: Option<std::time::SystemTime>,
+    summary: Result<ssh::CertificateSummary, String>,
+}
+
+/// State of the Known Hosts settings page; `rows` are reloaded from disk after every edit.
+#[derive(Default)]
+struct KnownHostsManager {
+    loaded: bool,
+    rows: Vec<KnownHostRow>,
+    search: String,
+    selected: BTreeSet<(PathBuf, usize)>,
+    confirm_bulk_remove: bool,
+    probe: Option<KnownHostProbe>,
+    status: Option<(ssh::IssueKind, String)>,
+}
+
+struct KnownHostRow {
+    path: PathBuf,
+    entry: known_hosts::KnownHostEntry,
+    comparison: Option<KeyComparison>,
+}
+
+impl KnownHostRow {
+    fn id(&self) -> (PathBuf, usize) {
+        (self.path.clone(), self.entry.line)
+    }
+}
+
+/// A running "fetch current key" request for one known_hosts line.
+struct KnownHostProbe {
+    path: PathBuf,
+    line: usize,
+    host: String,
+    port: u16,
+    rx: Receiver<Result<russh::keys::PublicKey, String>>,
+}
+
+enum KeyComparison {
+    Matches,
+    Differs {
+        host: String,
+        port: u16,
+        key: russh::keys::PublicKey,
+    },
+    Failed(String),
 }
 
 impl SettingsDialog {
@@ -542,6 +682,11 @@ impl SettingsDialog {
             remember_key_passphrase: false,
             draft: ConnectionSettings::default(),
             just_opened: false,
+            ssh_config_export_status: None,
+            layout_name: String::new(),
+            layout_status: None,
+            certificate_preview: None,
+            known_hosts: KnownHostsManager::default(),
         }
     }
 }
@@ -584,10 +729,41 @@ struct DownloadJob {
     message: String,
 }
 
-#[derive(Clone, Debug)]
 enum PaneKind {
     Terminal,
     FileManager(Box<FileBrowserState>),
+    Replay(Box<ReplayState>),
+    LocalShell(LocalShellState),
+}
+
+/// A shell running on this machine behind a pseudo console.
+struct LocalShellState {
+    /// As configured when the pane opened; empty runs the default shell.
+    command: String,
+}
+
+/// Playback of a `.cast` recording in a read-only terminal pane.
+struct ReplayState {
+    path: PathBuf,
+    /// `None` when the file could not be loaded; the pane then shows the error.
+    player: Option<crate::asciicast::Player>,
+    playing: bool,
+    speed: f64,
+    last_tick: Option<Instant>,
+}
+
+impl ReplayState {
+    const SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 1.5, 2.0, 4.0];
+
+    fn set_playing(&mut self, playing: bool) {
+        if playing {
+            if let Some(player) = self.player.as_mut().filter(|player| player.at_end()) {
+                player.seek(0.0);
+            }
+        }
+        self.playing = playing && self.player.is_some();
+        self.last_tick = None;
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -919,6 +1095,11 @@ struct SshTab {
     pending_scrollback: Option<usize>,
     last_selection_autoscroll: Instant,
     pending_sftp_events: Vec<ssh::SftpEvent>,
+    forwards: Vec<ssh::ForwardStatus>,
+    negotiated: Option<ssh::NegotiatedAlgorithms>,
+    auto_reconnect: Option<AutoReconnect>,
+    /// `.cast` file this session is being recorded to.
+    recording: Option<PathBuf>,
     kind: PaneKind,
 }
 
@@ -981,6 +1162,10 @@ impl SshTab {
             pending_scrollback: None,
             last_selection_autoscroll: Instant::now(),
             pending_sftp_events: Vec::new(),
+            forwards: Vec::new(),
+            negotiated: None,
+            auto_reconnect: None,
+            recording: None,
             kind: PaneKind::Terminal,
         }
     }
@@ -1021,21 +1206,133 @@ impl SshTab {
         tab
     }
 
+    fn new_replay(id: u64, path: PathBuf, scrollback_len: usize) -> Self {
+        let mut tab = Self::new(
+            id,
+            ConnectionSettings::default(),
+            None,
+            scrollback_len,
+            format!("logs\\replay-tab-{id}.log"),
+        );
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        tab.title = format!("Replay: {name}");
+        let player = match crate::asciicast::read(&path) {
+            Ok(cast) => {
+                if let Some(title) = cast.title.as_deref().filter(|t| !t.trim().is_empty()) {
+                    tab.title = format!("Replay: {}", title.trim());
+                }
+                let mut player = crate::asciicast::Player::new(cast, tab.scrollback_len);
+                tab.screen = player.screen().clone();
+                Some(player)
+            }
+            Err(err) => {
+                tab.last_status_kind = ssh::IssueKind::Configuration;
+                tab.last_status = format!("Failed to open {}: {err}", path.display());
+                None
+            }
+        };
+        let playing = player.is_some();
+        tab.kind = PaneKind::Replay(Box::new(ReplayState {
+            path,
+            player,
+            playing,
+            speed: 1.0,
+            last_tick: None,
+        }));
+        tab
+    }
+
+    fn new_local_shell(id: u64, command: String, scrollback_len: usize) -> Self {
+        let mut tab = Self::new(
+            id,
+            ConnectionSettings::default(),
+            None,
+            scrollback_len,
+            format!("logs\\local-tab-{id}.log"),
+        );
+        let shown = if command.trim().is_empty() {
+            crate::local_shell::default_shell_command()
+        } else {
+            command.clone()
+        };
+        tab.title = format!("Local: {}", crate::local_shell::shell_display_name(&shown));
+        tab.kind = PaneKind::LocalShell(LocalShellState { command });
+        tab
+    }
+
     fn is_terminal(&self) -> bool {
         matches!(self.kind, PaneKind::Terminal)
     }
 
+    fn is_local_shell(&self) -> bool {
+        matches!(self.kind, PaneKind::LocalShell(_))
+    }
+
+    /// An interactive terminal pane, remote or local.
+    fn is_shell_pane(&self) -> bool {
+        self.is_terminal() || self.is_local_shell()
+    }
+
+    /// A terminal whose worker other tabs and file managers can share. Telnet and raw TCP
+    /// connections carry one stream, so they have no channels or SFTP to hand out.
+    fn shares_session(&self) -> bool {
+        self.is_terminal() && self.settings.protocol == ConnectionProtocol::Ssh
+    }
+
+    /// A terminal tab nobody has used yet: no host entered and never connected.
+    fn is_placeholder(&self) -> bool {
+        self.is_terminal()
+            && !self.connected
+            && !self.connecting
+            && self.worker_tx.is_none()
+            && self.user_title.is_none()
+            && self.settings.host.trim().is_empty()
+    }
+
+    /// Whether the pane has terminal content to draw: a live session or a loaded recording.
+    fn has_screen(&self) -> bool {
+        self.connected || self.replay().is_some_and(|replay| replay.player.is_some())
+    }
+
     fn file_browser(&self) -> Option<&FileBrowserState> {
         match &self.kind {
             PaneKind::FileManager(f) => Some(f.as_ref()),
-            PaneKind::Terminal => None,
+            _ => None,
         }
     }
 
     fn file_browser_mut(&mut self) -> Option<&mut FileBrowserState> {
         match &mut self.kind {
             PaneKind::FileManager(f) => Some(f.as_mut()),
-            PaneKind::Terminal => None,
+            _ => None,
+        }
+    }
+
+    fn replay(&self) -> Option<&ReplayState> {
+        match &self.kind {
+            PaneKind::Replay(replay) => Some(replay.as_ref()),
+            _ => None,
+        }
+    }
+
+    fn seek_replay(&mut self, position: f64) {
+        let PaneKind::Replay(replay) = &mut self.kind else {
+            return;
+        };
+        let Some(player) = replay.player.as_mut() else {
+            return;
+        };
+        replay.last_tick = None;
+        if player.seek(position) {
+            self.screen = player.screen().clone();
+            self.scrollback_max = self.screen.scrollback_max();
+            // The selection pointed at the frame we just left.
+            self.selection = None;
+            self.abs_selection = None;
+            self.invalidate_terminal_render_cache();
         }
     }
 
@@ -1044,7 +1341,7 @@ impl SshTab {
     }
 
     fn start_connect(&mut self) {
-        if !self.is_terminal() {
+        if !self.is_shell_pane() {
             return;
         }
         if self.connected || self.connecting {
@@ -1076,6 +1373,31 @@ impl SshTab {
         let settings = self.settings.clone();
         let scrollback_len = self.scrollback_len;
         let log_path = self.log_path.clone();
+        if let PaneKind::LocalShell(shell) = &self.kind {
+            self.host_key_tx = None;
+            self.last_status = "Starting shell...".to_string();
+            let _handle = crate::local_shell::start_local_shell(
+                self.id,
+                shell.command.clone(),
+                scrollback_len,
+                ui_tx,
+                worker_rx,
+                log_path,
+            );
+            return;
+        }
+        if settings.protocol != ConnectionProtocol::Ssh {
+            self.host_key_tx = None;
+            let _handle = crate::telnet::start_telnet(
+                self.id,
+                settings,
+                scrollback_len,
+                ui_tx,
+                worker_rx,
+                log_path,
+            );
+            return;
+        }
         let _handle = ssh::start_shell(
             self.id,
             settings,
@@ -1091,6 +1413,9 @@ impl SshTab {
         match &mut self.kind {
             PaneKind::Terminal => {
                 if let Some(tx) = self.worker_tx.take() {
+                    if self.recording.take().is_some() {
+                        let _ = tx.send(WorkerMessage::StopRecording { client_id: self.id });
+                    }
                     if self.connecting && self.host_key_tx.is_some() {
                         let _ = tx.send(WorkerMessage::Disconnect);
                     } else {
@@ -1101,6 +1426,15 @@ impl SshTab {
                 }
                 self.ui_rx = None;
                 self.host_key_tx = None;
+                self.forwards.clear();
+                self.negotiated = None;
+                self.auto_reconnect = None;
+            }
+            PaneKind::LocalShell(_) => {
+                if let Some(tx) = self.worker_tx.take() {
+                    let _ = tx.send(WorkerMessage::Disconnect);
+                }
+                self.ui_rx = None;
             }
             PaneKind::FileManager(file) => {
                 if let Some(tx) = file.worker_tx.take() {
@@ -1111,6 +1445,10 @@ impl SshTab {
                 file.busy = false;
                 file.status_kind = ssh::IssueKind::Info;
             }
+            PaneKind::Replay(replay) => {
+                replay.set_playing(false);
+                return;
+            }
         }
         self.connected = false;
         self.connecting = false;
@@ -1161,7 +1499,7 @@ impl SshTab {
         let mut processed = 0usize;
         let mut saw_message = false;
         match &mut self.kind {
-            PaneKind::Terminal => {
+            PaneKind::Terminal | PaneKind::LocalShell(_) => {
                 let Some(rx) = self.ui_rx.as_ref() else {
                     return false;
                 };
@@ -1197,7 +1535,24 @@ impl SshTab {
                             self.pending_host_key = None;
                             self.host_key_tx = None;
                             self.pending_restore_attach_group = None;
+                            if !ok {
+                                self.forwards.clear();
+                                self.negotiated = None;
+                                // A lost session or failed retry schedules the next attempt.
+                                let retry = AutoReconnect::retries_after(self.last_status_kind);
+                                let last_status = self.last_status.clone();
+                                if let Some(state) =
+                                    self.auto_reconnect.as_mut().filter(|s| s.due.is_none())
+                                {
+                                    if retry {
+                                        state.schedule(last_status);
+                                    } else {
+                                        self.auto_reconnect = None;
+                                    }
+                                }
+                            }
                             if ok {
+                                self.auto_reconnect = None;
                                 // A new shell starts at server-default PTY size (often 80x24).
                                 // Reset cached size so the next frame always sends current viewport size.
                                 self.last_sent_size = None;
@@ -1205,6 +1560,12 @@ impl SshTab {
                                 self.focus_terminal_next_frame = true;
                             }
                         }
+                        Ok(UiMessage::ConnectionLost) => {
+                            saw_message = true;
+                            if self.settings.auto_reconnect && self.auto_reconnect.is_none() {
+                                self.auto_reconnect = Some(AutoReconnect::default());
+                            }
+                        }
                         Ok(UiMessage::AuthPrompt(p)) => {
                             saw_message = true;
                             self.pending_auth = Some(p);
@@ -1213,11 +1574,26 @@ impl SshTab {
                             saw_message = true;
                             self.pending_host_key = Some(p);
                         }
+                        Ok(UiMessage::Forwards(forwards)) => {
+                            saw_message = true;
+                            self.forwards = forwards;
+                        }
+                        Ok(UiMessage::Negotiated(negotiated)) => {
+                            saw_message = true;
+                            self.negotiated = Some(negotiated);
+                        }
+                        Ok(UiMessage::Recording(path)) => {
+                            saw_message = true;
+                            self.recording = path;
+                        }
                         Err(TryRecvError::Empty) => break,
                         Err(TryRecvError::Disconnected) => {
                             saw_message = true;
                             self.connected = false;
                             self.connecting = false;
+                            self.forwards.clear();
+                            self.negotiated = None;
+                            self.recording = None;
                             if self.last_status.trim().is_empty() {
                                 self.last_status_kind = ssh::IssueKind::Transport;
                                 self.last_status = "Transport disconnected".to_string();
@@ -1260,6 +1636,40 @@ impl SshTab {
                     }
                 }
             }
+            PaneKind::Replay(replay) => {
+                let Some(player) = replay.player.as_mut() else {
+                    return false;
+                };
+                let mut changed = false;
+                if replay.playing {
+                    let now = Instant::now();
+                    let elapsed = replay
+                        .last_tick
+                        .map(|last| now.duration_since(last).as_secs_f64())
+                        .unwrap_or(0.0);
+                    replay.last_tick = Some(now);
+                    changed = player.seek(player.position() + elapsed * replay.speed);
+                    if player.at_end() {
+                        replay.playing = false;
+                        replay.last_tick = None;
+                    } else {
+                        // Keep the timeline moving between events, but wake up exactly for
+                        // the next one when it is close.
+                        let wait = player.next_event_in().unwrap_or(0.0) / replay.speed;
+                        ctx.request_repaint_after(Duration::from_secs_f64(wait.min(0.1)));
+                    }
+                }
+                if let Some(rows) = self.pending_scrollback.take() {
+                    player.set_scrollback(rows);
+                    changed = true;
+                }
+                if changed {
+                    self.screen = player.screen().clone();
+                    self.scrollback_max = self.screen.scrollback_max();
+                    self.invalidate_terminal_render_cache();
+                    saw_message = true;
+                }
+            }
             PaneKind::FileManager(file) => {
                 let Some(rx) = file.ui_rx.as_ref() else {
                     return false;
@@ -1339,6 +1749,9 @@ pub struct AppState {
 
     tray: Option<crate::tray::TrayState>,
     tray_events: crossbeam_channel::Receiver<crate::tray::TrayAppEvent>,
+    launch_requests: crossbeam_channel::Receiver<crate::single_instance::Launch>,
+    /// A handed-over `--layout` request waiting for the user to agree to close live sessions.
+    pending_layout_launch: Option<crate::cli::LaunchRequest>,
     hidden_to_tray: bool,
     minimize_to_tray_requested: bool,
 
@@ -1348,6 +1761,7 @@ pub struct AppState {
     host_key_dialog: Option<HostKeyDialog>,
     transfer_delete_dialog: Option<TransferDeleteDialog>,
     upload_conflict_dialog: Option<UploadConflictDialog>,
+    ssh_config_import_dialog: Option<SshConfigImportDialog>,
     pending_upload_conflict_prompts: VecDeque<ssh::UploadConflictPrompt>,
 
     style_initialized: bool,
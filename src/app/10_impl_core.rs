@@ -36,6 +36,8 @@ impl AppState {
         }
         let load_outcome = config::load();
         let mut config = load_outcome.config;
+        ssh::set_default_proxy(config.proxy.clone());
+        ssh::set_known_hosts_target(config.known_hosts_target);
         let startup_notice = load_outcome.notice;
         let config_saver = AsyncConfigSaver::new();
         let (download_event_tx, download_event_rx) = mpsc::channel::<ssh::DownloadManagerEvent>();
@@ -191,8 +193,7 @@ impl AppState {
 
             let autostart_ok = config.autostart
                 && default_profile_idx.is_some()
-                && !settings_dialog.draft.host.trim().is_empty()
-                && !settings_dialog.draft.username.trim().is_empty();
+                && settings_dialog.draft.can_connect();
             if autostart_ok {
                 first.start_connect();
                 settings_dialog.open = false;
@@ -248,6 +249,8 @@ impl AppState {
             snap_fractional_dpi,
             tray: None,
             tray_events: crate::tray::install_handlers(),
+            launch_requests: crate::single_instance::requests(),
+            pending_layout_launch: None,
             hidden_to_tray: false,
             minimize_to_tray_requested: false,
             clipboard: Clipboard::new().ok(),
@@ -256,6 +259,7 @@ impl AppState {
             host_key_dialog: None,
             transfer_delete_dialog: None,
             upload_conflict_dialog: None,
+            ssh_config_import_dialog: None,
             pending_upload_conflict_prompts: VecDeque::new(),
             style_initialized: false,
             style_scale_key: 0,
@@ -293,6 +297,7 @@ impl AppState {
         let mut settings_dialog = SettingsDialog::closed();
         let (download_event_tx, download_event_rx) = mpsc::channel::<ssh::DownloadManagerEvent>();
         let (_tray_tx, tray_events) = crossbeam_channel::unbounded();
+        let (_launch_tx, launch_requests) = crossbeam_channel::unbounded();
 
         let mut tiles = egui_tiles::Tiles::default();
         let first = SshTab::new(
@@ -331,6 +336,8 @@ impl AppState {
             snap_fractional_dpi: false,
             tray: None,
             tray_events,
+            launch_requests,
+            pending_layout_launch: None,
             hidden_to_tray: false,
             minimize_to_tray_requested: false,
             clipboard: None,
@@ -339,6 +346,7 @@ impl AppState {
             host_key_dialog: None,
             transfer_delete_dialog: None,
             upload_conflict_dialog: None,
+            ssh_config_import_dialog: None,
             pending_upload_conflict_prompts: VecDeque::new(),
             style_initialized: false,
             style_scale_key: 0,
@@ -436,8 +444,8 @@ impl AppState {
     }
 
     fn invalidate_terminal_font_caches(&mut self) {
-        for pane_id in self.terminal_pane_ids() {
-            if let Some(tab) = self.terminal_pane_mut(pane_id) {
+        for pane_id in self.pane_ids() {
+            if let Some(tab) = self.pane_mut(pane_id) {
                 tab.cell_metrics_cache = None;
                 tab.render_cache = None;
             }
@@ -1080,7 +1088,7 @@ impl AppState {
             .into_iter()
             .enumerate()
             .map(|(index, tile_id)| {
-                let rect = self.terminal_pane(tile_id).and_then(|pane| pane.last_view_rect);
+                let rect = self.pane(tile_id).and_then(|pane| pane.last_view_rect);
                 (index, tile_id, rect)
             })
             .collect();
@@ -1114,7 +1122,7 @@ impl AppState {
 
         match tile {
             Tile::Pane(tab) => {
-                if tab.is_terminal() {
+                if tab.is_shell_pane() {
                     out.push(tile_id);
                 }
             }
@@ -1356,6 +1364,22 @@ impl AppState {
                             },
                             path.clone(),
                         ),
+                        PersistedPaneKind::Replay { path } => {
+                            let mut tab = SshTab::new_replay(p.id, path.clone(), scrollback_len);
+                            // Restored replays wait at the start instead of playing on launch.
+                            if let PaneKind::Replay(replay) = &mut tab.kind {
+                                replay.set_playing(false);
+                            }
+                            tab
+                        }
+                        PersistedPaneKind::LocalShell { command } => {
+                            let mut tab =
+                                SshTab::new_local_shell(p.id, command.clone(), scrollback_len);
+                            if p.autoconnect {
+                                tab.start_connect();
+                            }
+                            tab
+                        }
                     };
                     tab.user_title = p.user_title.clone();
                     tab.color = p.color;
@@ -1372,10 +1396,7 @@ impl AppState {
 
         for id in to_autoconnect_terminals.into_values() {
             if let Some(Tile::Pane(tab)) = tree.tiles.get_mut(id) {
-                if tab.is_terminal()
-                    && !tab.settings.host.trim().is_empty()
-                    && !tab.settings.username.trim().is_empty()
-                {
+                if tab.is_terminal() && tab.settings.can_connect() {
                     tab.start_connect();
                 }
             }
@@ -1409,6 +1430,12 @@ impl AppState {
                             source_connection_group_id: file.source_connection_group_id,
                             path: file.cwd.clone(),
                         },
+                        PaneKind::Replay(replay) => PersistedPaneKind::Replay {
+                            path: replay.path.clone(),
+                        },
+                        PaneKind::LocalShell(shell) => PersistedPaneKind::LocalShell {
+                            command: shell.command.clone(),
+                        },
                     };
                     let p = PersistedTab {
                         id: tab.id,
@@ -1419,7 +1446,7 @@ impl AppState {
                         scrollback_len: tab.scrollback_len,
                         title_index: tab.title_index,
                         connection_group_id: tab.connection_group_id,
-                        autoconnect: tab.is_terminal() && (tab.connected || tab.connecting),
+                        autoconnect: tab.is_shell_pane() && (tab.connected || tab.connecting),
                         pane_kind,
                     };
                     tiles.insert(*tile_id, Tile::Pane(p));
@@ -1556,10 +1583,7 @@ impl AppState {
         tab.title = SshTab::title_for(tab.title_index, &tab.settings);
         tab.color = color;
         tab.focus_terminal_next_frame = true;
-        if auto_connect
-            && !tab.settings.host.trim().is_empty()
-            && !tab.settings.username.trim().is_empty()
-        {
+        if auto_connect && tab.settings.can_connect() {
             tab.start_connect();
         }
 
@@ -1686,7 +1710,7 @@ impl AppState {
         pane_id: TileId,
     ) -> Option<(Sender<WorkerMessage>, u64)> {
         let pane = self.pane(pane_id)?;
-        if pane.is_terminal() && pane.connected {
+        if pane.shares_session() && pane.connected {
             return pane
                 .worker_tx
                 .as_ref()
@@ -1706,7 +1730,9 @@ impl AppState {
     fn sender_for_connection_group(&self, connection_group_id: u64) -> Option<Sender<WorkerMessage>> {
         self.pane_ids().into_iter().find_map(|tile_id| {
             let pane = self.terminal_pane(tile_id)?;
-            (pane.connection_group_id == connection_group_id && pane.connected)
+            (pane.connection_group_id == connection_group_id
+                && pane.connected
+                && pane.shares_session())
                 .then(|| pane.worker_tx.as_ref().cloned())
                 .flatten()
         })
@@ -1949,6 +1975,122 @@ impl AppState {
         }
     }
 
+    /// Ticks auto-reconnect countdowns and starts the attempts that are due. Returns how long
+    /// until a countdown label changes so the caller can schedule a repaint.
+    fn drive_auto_reconnects(&mut self) -> Option<Duration> {
+        self.settle_queued_auto_reconnects();
+
+        let now = Instant::now();
+        let mut due_panes: Vec<TileId> = Vec::new();
+        let mut next_tick: Option<Duration> = None;
+        for pane_id in self.pane_ids() {
+            let Some(tab) = self.terminal_pane_mut(pane_id) else {
+                continue;
+            };
+            if tab.connected || tab.connecting {
+                continue;
+            }
+            let Some(due) = tab.auto_reconnect.as_ref().and_then(|state| state.due) else {
+                continue;
+            };
+            if now >= due {
+                due_panes.push(pane_id);
+                continue;
+            }
+            if let Some(state) = tab.auto_reconnect.as_ref() {
+                tab.last_status_kind = ssh::IssueKind::Transport;
+                tab.last_status = state.countdown_status(now);
+            }
+            let remaining = due.saturating_duration_since(now);
+            let tick = Duration::from_nanos(u64::from(remaining.subsec_nanos()))
+                .max(Duration::from_millis(50));
+            next_tick = Some(next_tick.map(|current| current.min(tick)).unwrap_or(tick));
+        }
+
+        for pane_id in due_panes {
+            self.start_auto_reconnect(pane_id);
+        }
+        next_tick
+    }
+
+    fn start_auto_reconnect(&mut self, pane_id: TileId) {
+        let Some(tab) = self.terminal_pane(pane_id) else {
+            return;
+        };
+        // A sibling's attempt earlier this frame may already have picked this pane up.
+        if tab.connected || tab.connecting {
+            return;
+        }
+        let Some(state) = tab.auto_reconnect.clone().filter(|state| state.due.is_some()) else {
+            return;
+        };
+        let connection_group_id = tab.connection_group_id;
+
+        // Every pane counting down in the group rides along on this one attempt.
+        let in_flight = AutoReconnect {
+            attempt: state.attempt + 1,
+            due: None,
+            reason: state.reason,
+        };
+        for group_tile in self.terminal_tiles_for_connection_group(connection_group_id) {
+            if let Some(group_tab) = self.terminal_pane_mut(group_tile) {
+                if group_tile == pane_id || group_tab.auto_reconnect.is_some() {
+                    group_tab.auto_reconnect = Some(in_flight.clone());
+                }
+            }
+        }
+        self.reconnect_terminal_group(pane_id);
+    }
+
+    /// Panes queued behind a failed auto-reconnect attempt take the group's next countdown (or
+    /// give up with it) instead of waiting on a session that will never arrive.
+    fn settle_queued_auto_reconnects(&mut self) {
+        let mut settled: Vec<(TileId, Option<AutoReconnect>, ssh::IssueKind, String)> = Vec::new();
+        for pane_id in self.pane_ids() {
+            let Some(tab) = self.terminal_pane(pane_id) else {
+                continue;
+            };
+            let Some(connection_group_id) = tab.pending_restore_attach_group else {
+                continue;
+            };
+            if tab.auto_reconnect.is_none() || tab.worker_tx.is_some() || tab.ui_rx.is_some() {
+                continue;
+            }
+            let group_tiles = self.terminal_tiles_for_connection_group(connection_group_id);
+            let attempt_alive = group_tiles.iter().any(|group_tile| {
+                self.terminal_pane(*group_tile)
+                    .map(|group_tab| {
+                        group_tab.connected || (group_tab.connecting && group_tab.ui_rx.is_some())
+                    })
+                    .unwrap_or(false)
+            });
+            if attempt_alive {
+                continue;
+            }
+            let lead = group_tiles
+                .iter()
+                .filter_map(|group_tile| self.terminal_pane(*group_tile))
+                .find(|group_tab| group_tab.pending_restore_attach_group.is_none());
+            let next = lead
+                .and_then(|lead| lead.auto_reconnect.clone())
+                .filter(|state| state.due.is_some());
+            let (kind, status) = lead
+                .map(|lead| (lead.last_status_kind, lead.last_status.clone()))
+                .unwrap_or((ssh::IssueKind::Transport, "Transport disconnected".to_string()));
+            settled.push((pane_id, next, kind, status));
+        }
+
+        for (pane_id, next, kind, status) in settled {
+            if let Some(tab) = self.terminal_pane_mut(pane_id) {
+                tab.connecting = false;
+                tab.pending_restore_attach_group = None;
+                tab.auto_reconnect = next;
+                tab.last_status_kind = kind;
+                tab.last_status = status;
+            }
+        }
+    }
+
     fn source_worker_sender_for_file_tile(&self, file_tile: TileId) -> Option<Sender<WorkerMessage>> {
         let file = self.file_pane(file_tile)?;
         file.source_worker_tx
@@ -2102,8 +2244,121 @@ impl AppState {
         }
     }
 
+    fn toggle_recording(&mut self, tile_id: TileId) {
+        let record_input = self.config.record_input;
+        let redact_hidden_input = self.config.redact_hidden_input;
+        let Some(tab) = self.terminal_pane_mut(tile_id) else {
+            return;
+        };
+        let Some(tx) = tab.worker_tx.as_ref().cloned() else {
+            return;
+        };
+        if tab.recording.is_some() {
+            let _ = tx.send(WorkerMessage::StopRecording { client_id: tab.id });
+            return;
+        }
+        if !tab.connected {
+            return;
+        }
+
+        let title = tab.user_title.clone().unwrap_or_else(|| tab.title.clone());
+        let stem: String = title
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+            .collect();
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let mut dlg = rfd::FileDialog::new()
+            .add_filter("asciicast", &["cast"])
+            .set_file_name(format!("rusty-{stem}-{stamp}.cast"));
+        if let Some(profile_dir) = user_profile_dir() {
+            dlg = dlg.set_directory(profile_dir);
+        }
+        let Some(path) = dlg.save_file() else {
+            return;
+        };
+        let request = ssh::RecordingRequest {
+            path,
+            title,
+            include_input: record_input,
+            redact_hidden_input,
+        };
+        if tx
+            .send(WorkerMessage::StartRecording {
+                client_id: tab.id,
+                request,
+            })
+            .is_err()
+        {
+            tab.last_status_kind = ssh::IssueKind::Transport;
+            tab.last_status = "Failed to start recording: session is gone.".to_string();
+        }
+    }
+
+    fn open_recording(&mut self, tabs_container_id: TileId) -> Option<TileId> {
+        let mut dlg = rfd::FileDialog::new().add_filter("asciicast", &["cast"]);
+        if let Some(profile_dir) = user_profile_dir() {
+            dlg = dlg.set_directory(profile_dir);
+        }
+        let path = dlg.pick_file()?;
+
+        let id = self.next_session_id;
+        self.next_session_id += 1;
+        let pane = SshTab::new_replay(id, path, self.config.terminal_scrollback_lines);
+        let pane_id = self.tree.tiles.insert_pane(pane);
+        if let Some(Tile::Container(Container::Tabs(tabs))) =
+            self.tree.tiles.get_mut(tabs_container_id)
+        {
+            tabs.children.push(pane_id);
+            tabs.set_active(pane_id);
+        } else {
+            let root = self.tree.tiles.insert_tab_tile(vec![pane_id]);
+            self.tree.root = Some(root);
+        }
+        self.set_active_tile(Some(pane_id));
+        self.set_focus_next_frame(pane_id);
+        Some(pane_id)
+    }
+
+    fn create_local_shell_pane(&mut self, command: String, scrollback_len: usize) -> TileId {
+        let id = self.next_session_id;
+        self.next_session_id += 1;
+        let mut tab = SshTab::new_local_shell(id, command, scrollback_len);
+        tab.focus_terminal_next_frame = true;
+        tab.start_connect();
+        self.tree.tiles.insert_pane(tab)
+    }
+
+    fn open_local_shell(&mut self, tabs_container_id: TileId) -> TileId {
+        let pane_id = self.create_local_shell_pane(
+            self.config.local_shell_command.clone(),
+            self.config.terminal_scrollback_lines,
+        );
+        if let Some(Tile::Container(Container::Tabs(tabs))) =
+            self.tree.tiles.get_mut(tabs_container_id)
+        {
+            tabs.children.push(pane_id);
+            tabs.set_active(pane_id);
+        } else {
+            let root = self.tree.tiles.insert_tab_tile(vec![pane_id]);
+            self.tree.root = Some(root);
+        }
+        self.set_active_tile(Some(pane_id));
+        pane_id
+    }
+
     fn open_file_manager_for_terminal(&mut self, source_tile: TileId) -> Option<TileId> {
         let source = self.terminal_pane(source_tile)?;
+        if !source.shares_session() {
+            let message = format!(
+                "File manager needs SFTP, which {} sessions do not have.",
+                source.settings.protocol.label()
+            );
+            self.set_tab_status_for_tile(source_tile, ssh::IssueKind::Configuration, message);
+            return None;
+        }
         let settings = source.settings.clone();
         let profile_name = source.profile_name.clone();
         let color = source.color;
@@ -3713,6 +3968,7 @@ impl AppState {
             || self.rename_popup.is_some()
             || self.transfer_delete_dialog.is_some()
             || self.upload_conflict_dialog.is_some()
+            || self.pending_layout_launch.is_some()
         {
             return;
         }
@@ -3882,6 +4138,196 @@ impl AppState {
         }
     }
 
+    fn handle_launch_requests(&mut self, ctx: &egui::Context) {
+        let mut handed_over = false;
+        while let Ok(launch) = self.launch_requests.try_recv() {
+            handed_over = true;
+            let result = launch.and_then(|request| self.open_launch_request(&request));
+            if let Err(err) = result {
+                crate::logger::log_line("logs\\single-instance.log", &err);
+                self.startup_notice = Some(format!("Command line: {err}"));
+            }
+        }
+        if handed_over {
+            if self.hidden_to_tray {
+                self.show_from_tray(ctx);
+            } else {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+        }
+    }
+
+    /// Opens what the command line asked for. A layout replaces the whole tree, so while
+    /// sessions are live the request waits for [`Self::draw_layout_launch_confirm`].
+    fn open_launch_request(&mut self, request: &crate::cli::LaunchRequest) -> Result<(), String> {
+        if request.layout.is_some() && self.live_session_count() > 0 {
+            self.pending_layout_launch = Some(request.clone());
+            return Ok(());
+        }
+        self.apply_launch_request(request)
+    }
+
+    fn apply_launch_request(&mut self, request: &crate::cli::LaunchRequest) -> Result<(), String> {
+        if let Some(name) = &request.layout {
+            self.open_saved_layout(name)?;
+        }
+        if !request.opens_tab() {
+            return Ok(());
+        }
+        let (settings, profile_name) = request.connection_settings(&self.config)?;
+        // A fresh window's empty first tab would only sit next to the requested one.
+        let pane_ids = self.pane_ids();
+        let placeholder = match pane_ids.as_slice() {
+            [only] if self.pane(*only).is_some_and(SshTab::is_placeholder) => Some(*only),
+            _ => None,
+        };
+
+        let tabs_container_id = self.launch_tabs_container();
+        self.add_new_pane_to_tabs_with_settings(tabs_container_id, settings, None, profile_name);
+        if let Some(placeholder) = placeholder {
+            self.close_pane(placeholder);
+        }
+        self.layout_dirty = true;
+        Ok(())
+    }
+
+    fn live_session_count(&self) -> usize {
+        self.pane_ids()
+            .into_iter()
+            .filter_map(|id| self.pane(id))
+            .filter(|pane| pane.is_shell_pane() && (pane.connected || pane.connecting))
+            .count()
+    }
+
+    fn draw_layout_launch_confirm(&mut self, ctx: &egui::Context) {
+        let Some(request) = self.pending_layout_launch.clone() else {
+            return;
+        };
+        let name = request.layout.clone().unwrap_or_default();
+        let live = self.live_session_count();
+
+        let mut open = true;
+        let mut confirm = false;
+        let mut cancel = false;
+        egui::Window::new("Open Saved Layout")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "The command line asked to open layout \"{name}\", which replaces this window's tabs."
+                ));
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{live} open session{} will be disconnected.",
+                        if live == 1 { "" } else { "s" }
+                    ))
+                    .color(issue_kind_color(self.theme, ssh::IssueKind::Transport)),
+                );
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui.button("Close Sessions and Open").clicked() {
+                        confirm = true;
+                    }
+                });
+            });
+
+        if confirm {
+            self.pending_layout_launch = None;
+            if let Err(err) = self.apply_launch_request(&request) {
+                crate::logger::log_line("logs\\single-instance.log", &err);
+                self.startup_notice = Some(format!("Command line: {err}"));
+            }
+        } else if cancel || !open {
+            self.pending_layout_launch = None;
+        }
+    }
+
+    /// The tabs container holding the active pane, or any tabs container in the tree.
+    fn launch_tabs_container(&mut self) -> TileId {
+        let is_tabs = |tiles: &Tiles<SshTab>, id: TileId| {
+            matches!(tiles.get(id), Some(Tile::Container(Container::Tabs(_))))
+        };
+        if let Some(parent) = self
+            .active_tile
+            .and_then(|id| self.tree.tiles.parent_of(id))
+            .filter(|id| is_tabs(&self.tree.tiles, *id))
+        {
+            return parent;
+        }
+        let any_tabs = self
+            .tree
+            .tiles
+            .iter()
+            .find_map(|(id, _)| is_tabs(&self.tree.tiles, *id).then_some(*id));
+        if let Some(id) = any_tabs {
+            return id;
+        }
+        let children = self.tree.root.into_iter().collect();
+        let root = self.tree.tiles.insert_tab_tile(children);
+        self.tree.root = Some(root);
+        root
+    }
+
+    fn save_current_layout(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Enter a name for the layout.".to_string());
+        }
+        let layout_json = self
+            .persist_session_tree()
+            .ok_or_else(|| "There is no layout to save.".to_string())?;
+        match self
+            .config
+            .saved_layouts
+            .iter_mut()
+            .find(|layout| layout.name.eq_ignore_ascii_case(name))
+        {
+            Some(existing) => existing.layout_json = layout_json,
+            None => self.config.saved_layouts.push(config::SavedLayout {
+                name: name.to_string(),
+                layout_json,
+            }),
+        }
+        self.config_saver.request_save(self.config.clone());
+        Ok(())
+    }
+
+    /// Replaces the current tabs and splits with a saved layout; open sessions are closed.
+    fn open_saved_layout(&mut self, name: &str) -> Result<(), String> {
+        let json = self
+            .config
+            .saved_layouts
+            .iter()
+            .find(|layout| layout.name.eq_ignore_ascii_case(name.trim()))
+            .map(|layout| layout.layout_json.clone())
+            .ok_or_else(|| format!("No saved layout named \"{}\".", name.trim()))?;
+        let (next_session_id, tree, active_tile) =
+            Self::restore_session_tree(&json, self.config.terminal_scrollback_lines, &self.config)
+                .map_err(|err| format!("Saved layout \"{}\" could not be opened: {err}", name.trim()))?;
+
+        for pane_id in self.pane_ids() {
+            if let Some(pane) = self.pane_mut(pane_id) {
+                pane.disconnect();
+            }
+        }
+        self.pending_sftp_requests.clear();
+        self.tree = tree;
+        self.next_session_id = self.next_session_id.max(next_session_id);
+        let active = active_tile
+            .filter(|id| matches!(self.tree.tiles.get(*id), Some(Tile::Pane(_))))
+            .or_else(|| self.first_pane_id());
+        self.set_active_tile(active);
+        self.settings_dialog.target_tile = active;
+        self.layout_dirty = true;
+        Ok(())
+    }
+
     fn handle_tray_events(&mut self, ctx: &egui::Context) {
         while let Ok(ev) = self.tray_events.try_recv() {
             match ev {
@@ -3983,7 +4429,7 @@ impl AppState {
         // leaving the user at "Not connected" with no next step.
         let needs_settings = self
             .pane(pane_id)
-            .map(|t| t.settings.host.trim().is_empty() || t.settings.username.trim().is_empty())
+            .map(|t| !t.settings.can_connect())
             .unwrap_or(true);
         if needs_settings {
             self.open_settings_dialog_for_tile(pane_id);
@@ -4068,16 +4514,31 @@ impl AppState {
                 self.config.terminal_scrollback_lines,
             ));
 
+        let local_shell_command = match self.pane(pane_id).map(|p| &p.kind) {
+            Some(PaneKind::LocalShell(shell)) => Some(shell.command.clone()),
+            _ => None,
+        };
+
         let tabs_container_id = self.tree.tiles.parent_of(pane_id)?;
         let parent_of_tabs = self.tree.tiles.parent_of(tabs_container_id);
 
-        let new_pane_id = self.create_pane(
-            settings,
-            color,
-            profile_name,
-            scrollback_len,
-            shared_session.is_none(),
-        );
+        // Splitting a local shell opens another local shell rather than an SSH tab.
+        let new_pane_id = match local_shell_command {
+            Some(command) => {
+                let new_pane_id = self.create_local_shell_pane(command, scrollback_len);
+                if let Some(tab) = self.pane_mut(new_pane_id) {
+                    tab.color = color;
+                }
+                new_pane_id
+            }
+            None => self.create_pane(
+                settings,
+                color,
+                profile_name,
+                scrollback_len,
+                shared_session.is_none(),
+            ),
+        };
         let new_tabs_id = self.tree.tiles.insert_tab_tile(vec![new_pane_id]);
         let new_linear_id = match dir {
             LinearDir::Horizontal => self
@@ -4119,12 +4580,12 @@ impl AppState {
 
 #[cfg(test)]
 mod title_index_tests {
-    use super::{AppState, TerminalGroupReconnectPlan};
-    use crate::ssh::WorkerMessage;
+    use super::{AppState, AutoReconnect, TerminalGroupReconnectPlan};
+    use crate::ssh::{IssueKind, WorkerMessage};
     use egui_tiles::{Container, Tile};
     use std::fs;
     use std::sync::mpsc;
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
     #[test]
     fn lowest_unused_terminal_title_index_reuses_gaps() {
@@ -4155,6 +4616,31 @@ mod title_index_tests {
         let _ = fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn handed_over_layout_waits_for_confirmation_while_sessions_are_live() {
+        let mut app = AppState::new_for_tests();
+        let first_id = app.first_pane_id().expect("first pane");
+        app.config.saved_layouts.push(crate::config::SavedLayout {
+            name: "work".to_string(),
+            layout_json: app.persist_session_tree().expect("layout"),
+        });
+        app.terminal_pane_mut(first_id).expect("terminal").connected = true;
+
+        let request = crate::cli::LaunchRequest {
+            layout: Some("work".to_string()),
+            ..Default::default()
+        };
+        app.open_launch_request(&request).expect("queued");
+        assert_eq!(app.pending_layout_launch.as_ref(), Some(&request));
+        assert!(app.terminal_pane(first_id).is_some_and(|tab| tab.connected));
+
+        app.pending_layout_launch = None;
+        app.terminal_pane_mut(first_id).expect("terminal").connected = false;
+        app.open_launch_request(&request).expect("opened");
+        assert!(app.pending_layout_launch.is_none());
+        assert!(app.layout_dirty);
+    }
+
     #[test]
     fn plan_terminal_group_reconnect_starts_one_session_and_queues_siblings() {
         let mut app = AppState::new_for_tests();
@@ -4276,4 +4762,70 @@ mod title_index_tests {
             Some(Tile::Container(Container::Tabs(_)))
         ));
     }
+
+    #[test]
+    fn auto_reconnect_delay_backs_off_with_jitter_and_caps() {
+        assert_eq!(AutoReconnect::delay(0, 0.0), Duration::from_secs(1));
+        assert_eq!(AutoReconnect::delay(0, 1.0), Duration::from_secs(2));
+        assert_eq!(AutoReconnect::delay(3, 0.0), Duration::from_secs(8));
+        assert_eq!(AutoReconnect::delay(3, 0.5), Duration::from_secs(12));
+        assert_eq!(AutoReconnect::delay(12, 1.0), Duration::from_secs(60));
+        assert_eq!(AutoReconnect::delay(u32::MAX, 0.0), Duration::from_secs(30));
+        let jitter = AutoReconnect::random_jitter();
+        assert!((0.0..1.0).contains(&jitter));
+        assert!(AutoReconnect::retries_after(IssueKind::Transport));
+        assert!(!AutoReconnect::retries_after(IssueKind::Authentication));
+        assert!(!AutoReconnect::retries_after(IssueKind::HostKey));
+    }
+
+    #[test]
+    fn queued_siblings_follow_failed_auto_reconnect_attempt() {
+        let mut app = AppState::new_for_tests();
+        let first_id = app.first_pane_id().expect("first pane");
+        let tabs_id = app.tree.root.expect("root tabs");
+        let second_id = app
+            .add_new_pane_to_tabs_with_settings(tabs_id, Default::default(), None, None)
+            .expect("second pane");
+
+        let group_id = 55;
+        let due = Instant::now() + Duration::from_secs(10);
+        {
+            let tab = app.terminal_pane_mut(first_id).expect("first terminal");
+            tab.connection_group_id = group_id;
+            tab.connected = false;
+            tab.connecting = false;
+            tab.last_status_kind = IssueKind::Transport;
+            tab.last_status = "Connection refused.".to_string();
+            tab.auto_reconnect = Some(AutoReconnect {
+                attempt: 1,
+                due: Some(due),
+                reason: "Connection refused.".to_string(),
+            });
+        }
+        {
+            let tab = app.terminal_pane_mut(second_id).expect("second terminal");
+            tab.connection_group_id = group_id;
+            tab.connected = false;
+            tab.connecting = true;
+            tab.worker_tx = None;
+            tab.ui_rx = None;
+            tab.pending_restore_attach_group = Some(group_id);
+            tab.auto_reconnect = Some(AutoReconnect {
+                attempt: 1,
+                ..Default::default()
+            });
+        }
+
+        assert!(app.drive_auto_reconnects().is_some());
+
+        let second = app.terminal_pane(second_id).expect("second terminal");
+        assert!(!second.connecting);
+        assert!(second.pending_restore_attach_group.is_none());
+        let state = second.auto_reconnect.as_ref().expect("sibling keeps retrying");
+        assert_eq!(state.due, Some(due));
+        assert!(second
+            .last_status
+            .starts_with("Connection refused. Reconnecting in "));
+        assert!(second.last_status.ends_with("(attempt 2)."));
+    }
 }
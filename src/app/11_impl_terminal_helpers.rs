@@ -897,7 +897,7 @@ impl AppState {
             .unwrap_or(false)
             || scrollbar_response.hovered();
         if (scrollbar_response.drag_started() || (primary_pressed && hovering_scrollbar))
-            && tab.connected
+            && tab.has_screen()
             && tab.scrollback_max > 0
         {
             tab.scrollbar_dragging = true;
@@ -907,7 +907,7 @@ impl AppState {
         if primary_released {
             tab.scrollbar_dragging = false;
         }
-        if tab.scrollbar_dragging && primary_down && tab.connected && tab.scrollback_max > 0 {
+        if tab.scrollbar_dragging && primary_down && tab.has_screen() && tab.scrollback_max > 0 {
             if let Some(pos) = pointer_pos {
                 // Map pointer Y to scrollback offset.
                 let visible_rows = tab.screen.size().0 as f32;
@@ -941,7 +941,7 @@ impl AppState {
         );
 
         // Local scrollback or remote wheel reporting.
-        if hovering_term && tab.connected {
+        if hovering_term && tab.has_screen() {
             if allow_remote_mouse {
                 tab.remote_scroll_accum += wheel_delta;
 
@@ -1081,7 +1081,7 @@ impl AppState {
                 }
 
                 // Auto-scroll while selecting beyond the viewport edge.
-                if tab.connected && tab.scrollback_max > 0 {
+                if tab.has_screen() && tab.scrollback_max > 0 {
                     let selection_dragging = tab.selection.map(|s| s.dragging).unwrap_or(false);
                     if selection_dragging {
                         let dist_top = (term_rect.top() - raw_pos.y).max(0.0);
@@ -1381,6 +1381,22 @@ impl AppState {
                     _ => {}
                 }
             }
+        } else if response.has_focus() && tab.has_screen() {
+            // Read-only screens (recording replays) still copy what is selected.
+            let copy_requested = events.iter().any(|ev| match ev {
+                egui::Event::Copy => true,
+                egui::Event::Key {
+                    key: egui::Key::C,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => modifiers.ctrl && !has_copy_event,
+                _ => false,
+            });
+            if copy_requested {
+                let text = Self::selected_text(tab);
+                Self::copy_selection_with_flash(ctx, clipboard, tab, text);
+            }
         }
 
     }
@@ -1817,6 +1833,111 @@ impl AppState {
         painter.rect_filled(thumb, 3.0, with_alpha(theme.accent, 150));
     }
 
+    fn session_badges(tab: &SshTab, theme: UiTheme) -> Vec<(String, Color32, String)> {
+        let mut badges = Vec::new();
+        if tab.is_terminal() && tab.connected && !tab.settings.protocol.is_encrypted() {
+            badges.push((
+                "Unencrypted".to_string(),
+                issue_kind_color(theme, ssh::IssueKind::Transport),
+                format!(
+                    "{} sends everything, including passwords, in plain text.",
+                    tab.settings.protocol.label()
+                ),
+            ));
+        }
+        if let Some(path) = &tab.recording {
+            badges.push((
+                "REC".to_string(),
+                issue_kind_color(theme, ssh::IssueKind::Transport),
+                format!("Recording to {}", path.display()),
+            ));
+        }
+        if !tab.forwards.is_empty() {
+            let active = tab
+                .forwards
+                .iter()
+                .filter(|f| f.state == ssh::ForwardState::Active)
+                .count();
+            let failing = tab
+                .forwards
+                .iter()
+                .any(|f| f.state == ssh::ForwardState::Failed || f.last_error.is_some());
+            let color = if failing {
+                issue_kind_color(theme, ssh::IssueKind::Transport)
+            } else {
+                theme.muted
+            };
+            let details = tab
+                .forwards
+                .iter()
+                .map(|f| {
+                    let state = match f.state {
+                        ssh::ForwardState::Starting => "starting".to_string(),
+                        ssh::ForwardState::Active => format!(
+                            "active, {} open, {} sent, {} received",
+                            f.open_connections,
+                            Self::format_transfer_size(f.bytes_sent),
+                            Self::format_transfer_size(f.bytes_received)
+                        ),
+                        ssh::ForwardState::Failed => "failed".to_string(),
+                        ssh::ForwardState::Stopped => "stopped".to_string(),
+                    };
+                    match &f.last_error {
+                        Some(err) => format!("{} ({state})\n    {err}", f.description),
+                        None => format!("{} ({state})", f.description),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            badges.push((
+                format!("Forwards {active}/{}", tab.forwards.len()),
+                color,
+                details,
+            ));
+        }
+        if let Some(negotiated) = &tab.negotiated {
+            let mac = if negotiated.client_mac == negotiated.server_mac {
+                negotiated.client_mac.clone()
+            } else {
+                format!("{} (out) / {} (in)", negotiated.client_mac, negotiated.server_mac)
+            };
+            badges.push((
+                negotiated.cipher.clone(),
+                theme.muted,
+                format!(
+                    "Key exchange: {}\nHost key: {}\nCipher: {}\nMAC: {mac}",
+                    negotiated.kex, negotiated.host_key, negotiated.cipher
+                ),
+            ));
+        }
+        badges
+    }
+
+    /// Small hoverable chips in the bottom-right corner summarising session extras.
+    fn draw_session_badges(
+        ui: &mut egui::Ui,
+        painter: &egui::Painter,
+        rect: Rect,
+        tab: &SshTab,
+        theme: UiTheme,
+    ) {
+        let pad = Vec2::new(6.0, 3.0);
+        let mut right = rect.right() - 24.0;
+        for (index, (label, color, details)) in Self::session_badges(tab, theme).into_iter().enumerate() {
+            let galley = painter.layout_no_wrap(label, FontId::proportional(11.0), color);
+            let size = galley.size() + pad * 2.0;
+            let badge = Rect::from_min_size(
+                Pos2::new(right - size.x, rect.bottom() - size.y - 6.0),
+                size,
+            );
+            right = badge.left() - 4.0;
+            painter.rect_filled(badge, 4.0, theme.top_bg);
+            painter.rect_stroke(badge, 4.0, Stroke::new(1.0, theme.top_border));
+            painter.galley(badge.min + pad, galley, color);
+            ui.interact(badge, Id::new(("terminal_session_badge", tab.id, index)), Sense::hover())
+                .on_hover_text(details);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -51,7 +51,7 @@ impl AppState {
         self.settings_dialog.draft = settings.clone();
 
         // If we don't have enough info to connect, open directly on the connection page.
-        if settings.host.trim().is_empty() || settings.username.trim().is_empty() {
+        if !settings.can_connect() {
             self.settings_dialog.page = SettingsPage::ProfilesAndAccount;
         }
 
@@ -252,6 +252,111 @@ impl AppState {
             egui::RichText::new("Remembers splits/tabs and window position/size between launches.")
                 .color(theme.muted),
         );
+
+        ui.add_space(12.0);
+        ui.separator();
+        ui.add_space(10.0);
+
+        ui.label(egui::RichText::new("Saved layouts").strong());
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.settings_dialog.layout_name)
+                    .hint_text("Layout name")
+                    .desired_width(180.0),
+            );
+            if ui.button("Save current layout").clicked() {
+                let name = self.settings_dialog.layout_name.trim().to_string();
+                self.settings_dialog.layout_status = Some(match self.save_current_layout(&name) {
+                    Ok(()) => format!("Saved layout \"{name}\"."),
+                    Err(err) => err,
+                });
+            }
+        });
+        let mut open: Option<String> = None;
+        let mut delete: Option<usize> = None;
+        for (index, layout) in self.config.saved_layouts.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(&layout.name);
+                if ui.small_button("Open").clicked() {
+                    open = Some(layout.name.clone());
+                }
+                if ui.small_button("Delete").clicked() {
+                    delete = Some(index);
+                }
+            });
+        }
+        if let Some(name) = open {
+            self.settings_dialog.layout_status = Some(match self.open_saved_layout(&name) {
+                Ok(()) => format!("Opened layout \"{name}\"."),
+                Err(err) => err,
+            });
+        }
+        if let Some(index) = delete {
+            let removed = self.config.saved_layouts.remove(index);
+            self.config_saver.request_save(self.config.clone());
+            self.settings_dialog.layout_status = Some(format!("Deleted layout \"{}\".", removed.name));
+        }
+        if let Some(status) = &self.settings_dialog.layout_status {
+            ui.label(egui::RichText::new(status).color(theme.muted));
+        }
+        ui.add_space(4.0);
+        ui.label(
+            egui::RichText::new(
+                "Opening a layout replaces the current tabs and closes their sessions. Start Rusty with --layout <name> to open one directly.",
+            )
+            .color(theme.muted),
+        );
+
+        ui.add_space(12.0);
+        ui.separator();
+        ui.add_space(10.0);
+
+        ui.label(egui::RichText::new("Session recording").strong());
+        ui.add_space(4.0);
+        let before = (self.config.record_input, self.config.redact_hidden_input);
+        ui.checkbox(&mut self.config.record_input, "Record typed input");
+        ui.add_enabled_ui(self.config.record_input, |ui| {
+            ui.checkbox(
+                &mut self.config.redact_hidden_input,
+                "Mask input typed at password prompts",
+            )
+            .on_hover_text(
+                "SSH doesn't tell Rusty when the remote terminal stops echoing, so prompts are recognised by their text: a line ending in \":\" or \"?\" that mentions a password, passphrase, passcode, PIN, secret, token, code, OTP or 2FA, or a line that ends with one of those words. Other hidden prompts are recorded as typed.",
+            );
+        });
+        if (self.config.record_input, self.config.redact_hidden_input) != before {
+            self.config_saver.request_save(self.config.clone());
+        }
+        ui.add_space(4.0);
+        ui.label(
+            egui::RichText::new(
+                "Recordings are asciinema v2 .cast files, started from a terminal tab's context menu. Settings apply to the next recording.",
+            )
+            .color(theme.muted),
+        );
+
+        ui.add_space(12.0);
+        ui.separator();
+        ui.add_space(10.0);
+
+        ui.label(egui::RichText::new("Local shell").strong());
+        ui.add_space(4.0);
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.config.local_shell_command)
+                .hint_text(crate::local_shell::default_shell_command())
+                .desired_width(360.0),
+        );
+        if response.changed() {
+            self.config_saver.request_save(self.config.clone());
+        }
+        ui.add_space(4.0);
+        ui.label(
+            egui::RichText::new(
+                "Command run by new local shell tabs (right-click the + button). Leave empty for the default shell.",
+            )
+            .color(theme.muted),
+        );
     }
 
     fn draw_settings_page_appearance(&mut self, ui: &mut egui::Ui) {
@@ -774,6 +879,440 @@ impl AppState {
         }
     }
 
+    fn draw_settings_page_network(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        ui.label(egui::RichText::new("Proxy").strong());
+        ui.label(
+            egui::RichText::new(
+                "Outbound SSH connections (shells, file managers and transfers) tunnel through this proxy unless a profile overrides it.",
+            )
+            .color(theme.muted),
+        );
+        ui.add_space(4.0);
+        if draw_proxy_settings_grid(ui, "settings_network_proxy_grid", &mut self.config.proxy) {
+            ssh::set_default_proxy(self.config.proxy.clone());
+            self.config_saver.request_save(self.config.clone());
+        }
+    }
+
+    fn draw_settings_page_known_hosts(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        ui.label(egui::RichText::new("Host keys").strong());
+        ui.label(
+            egui::RichText::new(
+                "Server keys are checked against every file below, including hashed entries and @revoked markers. Keys you trust are appended to the file chosen here.",
+            )
+            .color(theme.muted),
+        );
+        ui.add_space(4.0);
+        egui::Grid::new("settings_known_hosts_grid")
+            .num_columns(2)
+            .spacing(Vec2::new(10.0, 6.0))
+            .show(ui, |ui| {
+                ui.label("Save new keys to");
+                let mut changed = false;
+                egui::ComboBox::from_id_source("settings_known_hosts_target")
+                    .selected_text(self.config.known_hosts_target.label())
+                    .show_ui(ui, |ui| {
+                        for target in [KnownHostsTarget::OpenSsh, KnownHostsTarget::Rusty] {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut self.config.known_hosts_target,
+                                    target,
+                                    target.label(),
+                                )
+                                .changed();
+                        }
+                    });
+                ui.end_row();
+                if changed {
+                    ssh::set_known_hosts_target(self.config.known_hosts_target);
+                    self.config_saver.request_save(self.config.clone());
+                }
+
+                ui.label("Files checked");
+                ui.vertical(|ui| {
+                    for path in ssh::known_hosts_read_paths() {
+                        ui.label(
+                            egui::RichText::new(path.display().to_string())
+                                .color(theme.muted)
+                                .monospace(),
+                        );
+                    }
+                });
+                ui.end_row();
+            });
+
+        ui.separator();
+        self.draw_known_hosts_manager(ui);
+    }
+
+    fn reload_known_hosts(&mut self) {
+        let manager = &mut self.settings_dialog.known_hosts;
+        manager.rows.clear();
+        manager.loaded = true;
+        for path in ssh::known_hosts_read_paths() {
+            match known_hosts::read_file(&path) {
+                Ok(entries) => manager.rows.extend(entries.into_iter().map(|entry| KnownHostRow {
+                    path: path.clone(),
+                    entry,
+                    comparison: None,
+                })),
+                Err(err) => {
+                    manager.status = Some((
+                        ssh::IssueKind::Permission,
+                        format!("Failed to read {}: {err}", path.display()),
+                    ))
+                }
+            }
+        }
+        let ids: BTreeSet<(PathBuf, usize)> = manager.rows.iter().map(KnownHostRow::id).collect();
+        manager.selected.retain(|id| ids.contains(id));
+    }
+
+    fn start_known_host_probe(&mut self, path: PathBuf, line: usize, host: String, port: u16) {
+        let (tx, rx) = mpsc::channel();
+        let probe_host = host.clone();
+        // Ask for the pinned key type so a server that prefers another one isn't a "change".
+        let algorithm = self
+            .settings_dialog
+            .known_hosts
+            .rows
+            .iter()
+            .find(|row| row.path == path && row.entry.line == line)
+            .map(|row| row.entry.algorithm.clone())
+            .unwrap_or_default();
+        std::thread::spawn(move || {
+            let result =
+                ssh::fetch_host_key(&probe_host, port, &algorithm, "logs\\known-hosts.log")
+                    .map_err(|err| format!("{err:#}"));
+            let _ = tx.send(result);
+            crate::tray::request_app_repaint();
+        });
+        self.settings_dialog.known_hosts.probe = Some(KnownHostProbe {
+            path,
+            line,
+            host,
+            port,
+            rx,
+        });
+    }
+
+    fn poll_known_host_probe(&mut self) {
+        let manager = &mut self.settings_dialog.known_hosts;
+        let Some(probe) = manager.probe.as_ref() else {
+            return;
+        };
+        let result = match probe.rx.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("The key fetch stopped unexpectedly.".into()),
+        };
+        let Some(probe) = manager.probe.take() else {
+            return;
+        };
+        let Some(row) = manager
+            .rows
+            .iter_mut()
+            .find(|row| row.path == probe.path && row.entry.line == probe.line)
+        else {
+            return;
+        };
+        row.comparison = Some(match result {
+            Ok(key) if row.entry.key.as_ref() == Some(&key) => KeyComparison::Matches,
+            Ok(key) => KeyComparison::Differs {
+                host: probe.host,
+                port: probe.port,
+                key,
+            },
+            Err(err) => KeyComparison::Failed(err),
+        });
+    }
+
+    fn remove_known_host_lines(&mut self, mut ids: Vec<(PathBuf, usize)>) {
+        // Bottom-up, so earlier removals don't shift the lines still to go.
+        ids.sort_by(|a, b| b.cmp(a));
+        let mut removed = 0;
+        let mut failure = None;
+        for (path, line) in &ids {
+            match ssh::remove_known_hosts_line(path, *line) {
+                Ok(()) => removed += 1,
+                Err(err) => {
+                    failure = Some(format!(
+                        "Failed to remove line {line} of {}: {err}",
+                        path.display()
+                    ));
+                    break;
+                }
+            }
+        }
+        self.settings_dialog.known_hosts.selected.clear();
+        self.reload_known_hosts();
+        self.settings_dialog.known_hosts.status = Some(match failure {
+            Some(message) => (ssh::IssueKind::Permission, message),
+            None => (
+                ssh::IssueKind::Info,
+                format!(
+                    "Removed {removed} entr{}.",
+                    if removed == 1 { "y" } else { "ies" }
+                ),
+            ),
+        });
+    }
+
+    fn draw_known_hosts_manager(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        if !self.settings_dialog.known_hosts.loaded {
+            self.reload_known_hosts();
+        }
+        self.poll_known_host_probe();
+        if self.settings_dialog.known_hosts.probe.is_some() {
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        }
+
+        enum Action {
+            Reload,
+            Remove(Vec<(PathBuf, usize)>),
+            Probe(PathBuf, usize, String, u16),
+            Repin(PathBuf, usize),
+        }
+        let mut action: Option<Action> = None;
+
+        let manager = &mut self.settings_dialog.known_hosts;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Pinned keys").strong());
+            ui.label(
+                egui::RichText::new(format!("{} entries", manager.rows.len())).color(theme.muted),
+            );
+        });
+        if manager
+            .rows
+            .iter()
+            .any(|row| row.entry.marker == Some(known_hosts::Marker::CertAuthority))
+        {
+            ui.label(
+                egui::RichText::new(
+                    "@cert-authority entries are listed but not used: Rusty does not validate host certificates, so those hosts are prompted for like unknown ones.",
+                )
+                .color(theme.muted),
+            );
+        }
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut manager.search)
+                    .hint_text("Search host, algorithm or fingerprint")
+                    .desired_width(260.0),
+            )
+            .on_hover_text("Hashed entries match when you type the exact host name, or [host]:port.");
+            if ui.button("Reload").clicked() {
+                action = Some(Action::Reload);
+            }
+            let selected = manager.selected.len();
+            if selected > 0 {
+                let label = if manager.confirm_bulk_remove {
+                    format!("Confirm removing {selected}")
+                } else {
+                    format!("Remove selected ({selected})")
+                };
+                if ui.button(label).clicked() {
+                    if manager.confirm_bulk_remove {
+                        action = Some(Action::Remove(manager.selected.iter().cloned().collect()));
+                    }
+                    manager.confirm_bulk_remove = !manager.confirm_bulk_remove;
+                }
+            } else {
+                manager.confirm_bulk_remove = false;
+            }
+        });
+
+        let query = manager.search.trim().to_lowercase();
+        let (query_host, query_port) = known_hosts::split_host_pattern(manager.search.trim());
+        let probing = manager.probe.is_some();
+        egui::Grid::new("settings_known_hosts_entries_grid")
+            .num_columns(7)
+            .striped(true)
+            .spacing(Vec2::new(8.0, 4.0))
+            .show(ui, |ui| {
+                for label in ["", "Host", "Port", "Algorithm", "SHA-256 fingerprint", "File", ""] {
+                    ui.label(egui::RichText::new(label).color(theme.muted));
+                }
+                ui.end_row();
+
+                for row in &manager.rows {
+                    let entry = &row.entry;
+                    let host_label = entry.host_label();
+                    let fingerprint = entry.fingerprint();
+                    let file_name = row
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    let visible = query.is_empty()
+                        || host_label.to_lowercase().contains(&query)
+                        || entry.algorithm.to_lowercase().contains(&query)
+                        || fingerprint
+                            .as_deref()
+                            .is_some_and(|fp| fp.to_lowercase().contains(&query))
+                        || entry.matches_host(query_host, query_port);
+                    if !visible {
+                        continue;
+                    }
+
+                    let id = row.id();
+                    let mut checked = manager.selected.contains(&id);
+                    if ui.checkbox(&mut checked, "").changed() {
+                        if checked {
+                            manager.selected.insert(id.clone());
+                        } else {
+                            manager.selected.remove(&id);
+                        }
+                    }
+                    let host_text = match entry.marker {
+                        Some(known_hosts::Marker::Revoked) => format!("@revoked {host_label}"),
+                        Some(known_hosts::Marker::CertAuthority) => {
+                            format!("@cert-authority {host_label}")
+                        }
+                        None => host_label,
+                    };
+                    let host_hover = if entry.marker == Some(known_hosts::Marker::CertAuthority) {
+                        format!("{}\nNot used: host certificates are not validated.", entry.hosts)
+                    } else {
+                        entry.hosts.clone()
+                    };
+                    ui.label(host_text).on_hover_text(host_hover);
+                    ui.label(entry.port().to_string());
+                    ui.label(&entry.algorithm);
+                    ui.label(
+                        egui::RichText::new(fingerprint.as_deref().unwrap_or("(unsupported key)"))
+                            .monospace()
+                            .size(11.0),
+                    );
+                    ui.label(egui::RichText::new(&file_name).color(theme.muted))
+                        .on_hover_text(row.path.display().to_string());
+                    ui.horizontal(|ui| {
+                        let target = entry
+                            .marker
+                            .is_none()
+                            .then(|| entry.probe_target(manager.search.trim()))
+                            .flatten();
+                        let compare = ui.add_enabled(
+                            target.is_some() && !probing,
+                            egui::Button::new("Compare"),
+                        );
+                        let compare = if target.is_none() {
+                            compare.on_disabled_hover_text(
+                                "Search for the exact host name to compare a hashed entry.",
+                            )
+                        } else {
+                            compare.on_hover_text("Fetch the server's current key and compare.")
+                        };
+                        if compare.clicked() {
+                            if let Some((host, port)) = target {
+                                action = Some(Action::Probe(id.0.clone(), id.1, host, port));
+                            }
+                        }
+                        if ui.button("Remove").clicked() {
+                            action = Some(Action::Remove(vec![id.clone()]));
+                        }
+                    });
+                    ui.end_row();
+
+                    let pending = manager
+                        .probe
+                        .as_ref()
+                        .is_some_and(|probe| probe.path == row.path && probe.line == entry.line);
+                    if pending {
+                        ui.label("");
+                        ui.label(egui::RichText::new("Fetching current key...").color(theme.muted));
+                        ui.end_row();
+                    }
+                    match &row.comparison {
+                        None => {}
+                        Some(KeyComparison::Matches) => {
+                            ui.label("");
+                            ui.label(
+                                egui::RichText::new("Server key matches.")
+                                    .color(issue_kind_color(theme, ssh::IssueKind::Info)),
+                            );
+                            ui.end_row();
+                        }
+                        Some(KeyComparison::Differs { key, .. }) => {
+                            ui.label("");
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "Server now offers {} {}",
+                                    key.algorithm(),
+                                    key.fingerprint(russh::keys::HashAlg::Sha256)
+                                ))
+                                .color(issue_kind_color(theme, ssh::IssueKind::HostKey)),
+                            );
+                            if ui
+                                .button("Re-pin")
+                                .on_hover_text("Replace this line with the key just fetched.")
+                                .clicked()
+                            {
+                                action = Some(Action::Repin(id.0.clone(), id.1));
+                            }
+                            ui.end_row();
+                        }
+                        Some(KeyComparison::Failed(err)) => {
+                            ui.label("");
+                            ui.label(
+                                egui::RichText::new(err)
+                                    .color(issue_kind_color(theme, ssh::IssueKind::Transport)),
+                            );
+                            ui.end_row();
+                        }
+                    }
+                }
+            });
+
+        if let Some((kind, message)) = &manager.status {
+            ui.label(egui::RichText::new(message).color(issue_kind_color(theme, *kind)));
+        }
+
+        match action {
+            None => {}
+            Some(Action::Reload) => {
+                self.reload_known_hosts();
+                self.settings_dialog.known_hosts.status = None;
+            }
+            Some(Action::Remove(ids)) => self.remove_known_host_lines(ids),
+            Some(Action::Probe(path, line, host, port)) => {
+                self.start_known_host_probe(path, line, host, port)
+            }
+            Some(Action::Repin(path, line)) => self.repin_known_host_row(&path, line),
+        }
+    }
+
+    /// Replaces a row's pinned key with the one its last comparison fetched.
+    fn repin_known_host_row(&mut self, path: &Path, line: usize) {
+        let manager = &mut self.settings_dialog.known_hosts;
+        let Some(row) = manager
+            .rows
+            .iter()
+            .find(|row| row.path == path && row.entry.line == line)
+        else {
+            return;
+        };
+        let Some(KeyComparison::Differs { host, port, key }) = &row.comparison else {
+            return;
+        };
+        let status = match ssh::repin_known_host(path, line, host, *port, key) {
+            Ok(()) => (
+                ssh::IssueKind::Info,
+                format!(
+                    "Re-pinned {} in {}.",
+                    known_hosts::host_port_name(host, *port),
+                    path.display()
+                ),
+            ),
+            Err(err) => (ssh::IssueKind::Permission, format!("{err:#}")),
+        };
+        self.reload_known_hosts();
+        self.settings_dialog.known_hosts.status = Some(status);
+    }
+
     fn draw_ui_theme_preview_card(
         ui: &mut egui::Ui,
         app_theme: UiTheme,
@@ -1003,6 +1542,187 @@ impl AppState {
         inner.response.interact(Sense::click())
     }
 
+    fn open_ssh_config_import(&mut self, source: Option<PathBuf>) {
+        let home = user_profile_dir();
+        let Some(source) =
+            source.or_else(|| home.as_deref().map(ssh_config::default_config_path))
+        else {
+            return;
+        };
+        let report = ssh_config::import_from_path(&source, home.as_deref());
+        self.ssh_config_import_dialog = Some(SshConfigImportDialog {
+            source,
+            hosts: report.hosts.into_iter().map(|host| (host, true)).collect(),
+            warnings: report.warnings,
+            overwrite_existing: false,
+            summary: None,
+        });
+    }
+
+    fn export_ssh_config(&mut self) {
+        let mut dlg = rfd::FileDialog::new().set_file_name("config");
+        if let Some(profile_dir) = user_profile_dir() {
+            dlg = dlg.set_directory(profile_dir.join(".ssh"));
+        }
+        let Some(path) = dlg.save_file() else {
+            return;
+        };
+        let (text, exported) = ssh_config::export_profiles(&self.config.profiles);
+        let skipped = self.config.profiles.len() - exported;
+        self.settings_dialog.ssh_config_export_status = Some(match fs::write(&path, text) {
+            Ok(()) if skipped > 0 => format!(
+                "Exported {exported} profile(s) to {} ({skipped} Telnet, raw TCP or host-less profile(s) skipped)",
+                path.display()
+            ),
+            Ok(()) => format!("Exported {exported} profile(s) to {}", path.display()),
+            Err(err) => format!("Export failed: {err}"),
+        });
+    }
+
+    fn draw_ssh_config_import_dialog(&mut self, ctx: &egui::Context) {
+        let theme = self.theme;
+        let config = &self.config;
+        let Some(dialog) = self.ssh_config_import_dialog.as_mut() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut browse = false;
+        let mut import = false;
+        let mut close = false;
+        egui::Window::new("Import SSH Config")
+            .collapsible(false)
+            .resizable(true)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .default_width(560.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Source: {}", dialog.source.display()));
+                    if ui.button("Browse...").clicked() {
+                        browse = true;
+                    }
+                });
+                ui.separator();
+
+                if dialog.hosts.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No concrete Host entries found.").color(theme.muted),
+                    );
+                }
+                egui::ScrollArea::vertical()
+                    .id_source("ssh_config_import_hosts_scroll")
+                    .max_height(260.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("ssh_config_import_hosts_grid")
+                            .num_columns(3)
+                            .spacing(Vec2::new(10.0, 4.0))
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (host, selected) in &mut dialog.hosts {
+                                    ui.checkbox(selected, &host.alias);
+                                    let settings = &host.settings;
+                                    let target = if settings.username.trim().is_empty() {
+                                        format!("{}:{}", settings.host, settings.port)
+                                    } else {
+                                        format!(
+                                            "{}@{}:{}",
+                                            settings.username, settings.host, settings.port
+                                        )
+                                    };
+                                    let mut extras = Vec::new();
+                                    if !settings.jump_hosts.is_empty() {
+                                        extras.push(format!("{} jump", settings.jump_hosts.len()));
+                                    }
+                                    let forwards = settings.local_forwards.len()
+                                        + settings.remote_forwards.len()
+                                        + settings.dynamic_forwards.len();
+                                    if forwards > 0 {
+                                        extras.push(format!("{forwards} fwd"));
+                                    }
+                                    if extras.is_empty() {
+                                        ui.label(target);
+                                    } else {
+                                        ui.label(format!("{target}  ({})", extras.join(", ")));
+                                    }
+                                    let name = config::sanitized_profile_name(&host.alias);
+                                    if config::find_profile_index(config, &name).is_some() {
+                                        ui.label(
+                                            egui::RichText::new("exists")
+                                                .color(Color32::from_rgb(220, 170, 90)),
+                                        );
+                                    } else {
+                                        ui.label(egui::RichText::new("new").color(theme.muted));
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                if !dialog.warnings.is_empty() {
+                    ui.separator();
+                    for warning in &dialog.warnings {
+                        ui.label(
+                            egui::RichText::new(warning).color(Color32::from_rgb(220, 170, 90)),
+                        );
+                    }
+                }
+
+                ui.separator();
+                ui.checkbox(
+                    &mut dialog.overwrite_existing,
+                    "Overwrite existing profiles with the same name",
+                );
+                if let Some(summary) = dialog.summary {
+                    ui.label(format!(
+                        "Added {}, updated {}, skipped {}.",
+                        summary.added, summary.updated, summary.skipped
+                    ));
+                }
+                ui.horizontal(|ui| {
+                    let any_selected = dialog.hosts.iter().any(|(_, selected)| *selected);
+                    if ui
+                        .add_enabled(any_selected, egui::Button::new("Import"))
+                        .clicked()
+                    {
+                        import = true;
+                    }
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if import {
+            let selected: Vec<ssh_config::ImportedHost> = dialog
+                .hosts
+                .iter()
+                .filter(|(_, selected)| *selected)
+                .map(|(host, _)| host.clone())
+                .collect();
+            let overwrite = dialog.overwrite_existing;
+            let summary = ssh_config::merge_into_config(&mut self.config, &selected, overwrite);
+            if let Some(dialog) = self.ssh_config_import_dialog.as_mut() {
+                dialog.summary = Some(summary);
+            }
+            self.persist_config_now();
+        }
+
+        if browse {
+            let mut dlg = rfd::FileDialog::new();
+            if let Some(profile_dir) = user_profile_dir() {
+                dlg = dlg.set_directory(profile_dir.join(".ssh"));
+            }
+            if let Some(path) = dlg.pick_file() {
+                self.open_ssh_config_import(Some(path));
+            }
+        }
+
+        if !open || close {
+            self.ssh_config_import_dialog = None;
+        }
+    }
+
     fn draw_settings_page_profiles_and_account(&mut self, ui: &mut egui::Ui) {
         let theme = self.theme;
         ui.spacing_mut().item_spacing = Vec2::new(8.0, 6.0);
@@ -1116,7 +1836,29 @@ impl AppState {
                 self.config.default_profile = selected_profile_name;
                 self.persist_config_now();
             }
+
+            if ui
+                .button("Import SSH Config...")
+                .on_hover_text("Create profiles from the Host entries in ~/.ssh/config.")
+                .clicked()
+            {
+                self.open_ssh_config_import(None);
+            }
+
+            if ui
+                .add_enabled(
+                    !self.config.profiles.is_empty(),
+                    egui::Button::new("Export SSH Config..."),
+                )
+                .on_hover_text("Write all profiles as an ssh_config file (without secrets).")
+                .clicked()
+            {
+                self.export_ssh_config();
+            }
         });
+        if let Some(status) = self.settings_dialog.ssh_config_export_status.as_deref() {
+            ui.label(egui::RichText::new(status).color(theme.muted));
+        }
 
         ui.horizontal(|ui| {
             ui.label("Profile name");
@@ -1140,6 +1882,43 @@ impl AppState {
             .num_columns(2)
             .spacing(Vec2::new(10.0, 6.0))
             .show(ui, |ui| {
+                ui.label("Protocol");
+                ui.horizontal(|ui| {
+                    let draft = &mut self.settings_dialog.draft;
+                    let previous = draft.protocol;
+                    egui::ComboBox::from_id_source("settings_profile_protocol")
+                        .selected_text(draft.protocol.label())
+                        .show_ui(ui, |ui| {
+                            for option in [
+                                ConnectionProtocol::Ssh,
+                                ConnectionProtocol::Telnet,
+                                ConnectionProtocol::Raw,
+                            ] {
+                                ui.selectable_value(&mut draft.protocol, option, option.label());
+                            }
+                        });
+                    // Follow the protocol's well-known port unless the user picked another one.
+                    if draft.protocol != previous && draft.port == previous.default_port() {
+                        draft.port = draft.protocol.default_port();
+                    }
+                    ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                        "Telnet and raw TCP reach devices without an SSH server. They use the host, port, proxy and terminal type; keys, jump hosts, forwards and SFTP need SSH.",
+                    );
+                });
+                ui.end_row();
+
+                if !self.settings_dialog.draft.protocol.is_encrypted() {
+                    ui.label("");
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} is not encrypted. Anyone on the network path can read the session, including passwords you type.",
+                            self.settings_dialog.draft.protocol.label()
+                        ))
+                        .color(issue_kind_color(theme, ssh::IssueKind::Transport)),
+                    );
+                    ui.end_row();
+                }
+
                 ui.label("Host");
                 ui.add(
                     egui::TextEdit::singleline(&mut self.settings_dialog.draft.host)
@@ -1148,21 +1927,178 @@ impl AppState {
                 );
                 ui.end_row();
 
-                ui.label("Port");
-                ui.add(
-                    egui::DragValue::new(&mut self.settings_dialog.draft.port)
-                        .speed(1.0)
-                        .clamp_range(1..=65535),
-                );
+                ui.label("Port");
+                ui.add(
+                    egui::DragValue::new(&mut self.settings_dialog.draft.port)
+                        .speed(1.0)
+                        .clamp_range(1..=65535),
+                );
+                ui.end_row();
+
+                ui.label("User");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.settings_dialog.draft.username)
+                        .desired_width(ui.available_width()),
+                );
+                ui.end_row();
+
+                ui.label("Proxy command");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(
+                            &mut self.settings_dialog.draft.proxy_command,
+                        )
+                        .hint_text("optional, e.g. ncat --proxy proxy:8080 %h %p")
+                        .desired_width((ui.available_width() - 28.0).max(120.0)),
+                    );
+                    ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                        "Runs a local helper and speaks SSH over its stdin/stdout instead of a TCP socket. %h, %p and %r expand to host, port and user.",
+                    );
+                });
+                ui.end_row();
+
+                ui.label("Connect timeout (s)");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.settings_dialog.draft.connect_timeout_secs)
+                            .speed(1.0)
+                            .clamp_range(0..=600),
+                    );
+                    ui.label(egui::RichText::new("(i)").color(theme.muted))
+                        .on_hover_text("Applies to each TCP connect and SSH handshake. 0 waits indefinitely.");
+                });
+                ui.end_row();
+
+                ui.label("Keepalive interval (s)");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(
+                            &mut self.settings_dialog.draft.keepalive_interval_secs,
+                        )
+                        .speed(1.0)
+                        .clamp_range(0..=3600),
+                    );
+                    ui.label(egui::RichText::new("(i)").color(theme.muted))
+                        .on_hover_text("Probe the server after this much silence. 0 disables keepalives.");
+                });
+                ui.end_row();
+
+                ui.label("Max missed keepalives");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.settings_dialog.draft.keepalive_max_missed)
+                            .speed(1.0)
+                            .clamp_range(0..=100),
+                    );
+                    ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                        "The tab reports \"connection lost\" after this many unanswered keepalives. 0 never gives up.",
+                    );
+                });
+                ui.end_row();
+
+                ui.label("Auto-reconnect");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings_dialog.draft.auto_reconnect, "Enable");
+                    ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                        "Retries with increasing delays when the connection drops unexpectedly, then reopens every pane that shared it.",
+                    );
+                });
+                ui.end_row();
+
+                ui.label("Host key policy");
+                ui.horizontal(|ui| {
+                    let policy = &mut self.settings_dialog.draft.host_key_policy;
+                    egui::ComboBox::from_id_source("settings_profile_host_key_policy")
+                        .selected_text(policy.label())
+                        .show_ui(ui, |ui| {
+                            for option in [
+                                HostKeyPolicy::Ask,
+                                HostKeyPolicy::AcceptNew,
+                                HostKeyPolicy::Strict,
+                            ] {
+                                ui.selectable_value(policy, option, option.label());
+                            }
+                        });
+                    ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                        "Ask: prompt for unknown or changed keys (background transfers reject them).\nAccept new: pin unknown keys automatically, refuse changed ones.\nStrict: only connect to hosts already in known_hosts.\nApplies to jump hosts too.",
+                    );
+                });
+                ui.end_row();
+            });
+
+        ui.label(egui::RichText::new("Startup").strong());
+        egui::Grid::new("settings_profile_startup_grid")
+            .num_columns(2)
+            .spacing(Vec2::new(10.0, 6.0))
+            .show(ui, |ui| {
+                ui.label("Terminal type");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.settings_dialog.draft.terminal_type)
+                            .hint_text(DEFAULT_TERMINAL_TYPE)
+                            .desired_width(160.0),
+                    );
+                    ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                        "Sent as TERM when the PTY is requested. Use a value the server's terminfo knows, e.g. xterm or screen-256color for old hosts.",
+                    );
+                });
+                ui.end_row();
+
+                ui.label("Run command");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.settings_dialog.draft.remote_command)
+                            .hint_text("login shell")
+                            .desired_width((ui.available_width() - 24.0).max(120.0)),
+                    );
+                    ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                        "Runs this command instead of a login shell (e.g. htop or journalctl -f). The tab shows its exit status when it finishes.",
+                    );
+                });
                 ui.end_row();
 
-                ui.label("User");
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.settings_dialog.draft.username)
+                let exec_mode = !self.settings_dialog.draft.remote_command.trim().is_empty();
+                ui.label("Startup commands");
+                ui.add_enabled(
+                    !exec_mode,
+                    egui::TextEdit::multiline(&mut self.settings_dialog.draft.startup_commands)
+                        .hint_text("cd /srv/app\nsource .venv/bin/activate")
+                        .desired_rows(3)
                         .desired_width(ui.available_width()),
-                );
+                )
+                .on_hover_text("Typed into the shell after login, one command per line.");
+                ui.end_row();
+
+                ui.label("Wait for prompt");
+                ui.horizontal(|ui| {
+                    ui.add_enabled(
+                        !exec_mode,
+                        egui::TextEdit::singleline(
+                            &mut self.settings_dialog.draft.startup_prompt_pattern,
+                        )
+                        .hint_text("[$#] $")
+                        .desired_width((ui.available_width() - 24.0).max(120.0)),
+                    );
+                    ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                        "Regular expression matched against the text left of the cursor. Each startup command waits for it, so commands after sudo -i land in the new shell. Leave empty to send them at once.",
+                    );
+                });
                 ui.end_row();
+
+                let pattern = self.settings_dialog.draft.startup_prompt_pattern.trim();
+                if !pattern.is_empty() {
+                    if let Err(err) = fancy_regex::Regex::new(pattern) {
+                        ui.label("");
+                        ui.label(
+                            egui::RichText::new(format!("Invalid pattern: {err}")).color(
+                                issue_kind_color(theme, ssh::IssueKind::Configuration),
+                            ),
+                        );
+                        ui.end_row();
+                    }
+                }
             });
+        self.draw_profile_environment_editor(ui);
 
         ui.label(egui::RichText::new("Advanced authentication").strong());
         egui::Grid::new("settings_profile_advanced_grid")
@@ -1206,7 +2142,85 @@ impl AppState {
                     }
                 });
                 ui.end_row();
+
+                ui.label("Certificate (optional)");
+                ui.horizontal(|ui| {
+                    let key_path = self.settings_dialog.draft.private_key_path.trim();
+                    let hint = if key_path.is_empty() {
+                        "id_ed25519-cert.pub".to_string()
+                    } else {
+                        format!("{key_path}-cert.pub")
+                    };
+                    ui.add(
+                        egui::TextEdit::singleline(
+                            &mut self.settings_dialog.draft.certificate_path,
+                        )
+                        .hint_text(hint)
+                        .desired_width((ui.available_width() - 92.0).max(120.0)),
+                    )
+                    .on_hover_text(
+                        "OpenSSH user certificate signed by your CA. Leave empty to use <key>-cert.pub when it exists.",
+                    );
+                    if ui.button("Browse...").clicked() {
+                        let mut dlg = rfd::FileDialog::new().add_filter("Certificate", &["pub"]);
+                        if let Some(profile_dir) = user_profile_dir() {
+                            dlg = dlg.set_directory(profile_dir);
+                        }
+                        if let Some(path) = dlg.pick_file() {
+                            self.settings_dialog.draft.certificate_path =
+                                path.display().to_string();
+                        }
+                    }
+                });
+                ui.end_row();
+
+                if let Some((text, kind)) = self.certificate_preview_label() {
+                    ui.label("");
+                    ui.label(egui::RichText::new(text).color(issue_kind_color(theme, kind)));
+                    ui.end_row();
+                }
+
+                ui.label("Forward agent");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings_dialog.draft.forward_agent, "Enable");
+                    ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                        "Lets the remote host sign with keys held by your local ssh-agent. Only enable for hosts you trust.",
+                    );
+                });
+                ui.end_row();
             });
+        self.draw_profile_identities_editor(ui);
+
+        ui.separator();
+        let mut override_proxy = self.settings_dialog.draft.proxy_override.is_some();
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Proxy").strong());
+            if ui
+                .checkbox(&mut override_proxy, "Override global proxy")
+                .changed()
+            {
+                self.settings_dialog.draft.proxy_override =
+                    override_proxy.then(|| self.config.proxy.clone());
+            }
+        });
+        if let Some(proxy) = self.settings_dialog.draft.proxy_override.as_mut() {
+            draw_proxy_settings_grid(ui, "settings_profile_proxy_grid", proxy);
+        }
+
+        ui.separator();
+        self.draw_profile_jump_hosts_editor(ui);
+
+        ui.separator();
+        self.draw_profile_local_forwards_editor(ui);
+
+        ui.separator();
+        self.draw_profile_remote_forwards_editor(ui);
+
+        ui.separator();
+        self.draw_profile_dynamic_forwards_editor(ui);
+
+        ui.separator();
+        self.draw_profile_algorithms_editor(ui);
 
         // Status (only show failures to keep noise down).
         let status_tile = self
@@ -1234,8 +2248,7 @@ impl AppState {
                 self.settings_dialog.open = false;
             }
 
-            let connect_enabled = !self.settings_dialog.draft.host.trim().is_empty()
-                && !self.settings_dialog.draft.username.trim().is_empty();
+            let connect_enabled = self.settings_dialog.draft.can_connect();
             if ui
                 .add_enabled(connect_enabled, egui::Button::new("Connect"))
                 .clicked()
@@ -1273,6 +2286,497 @@ impl AppState {
         });
     }
 
+    fn draw_profile_identities_editor(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Additional keys").strong());
+            ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                "Tried in order after the private key above, before ssh-agent, keyboard-interactive and password. Passphrases are asked for on first use and can be remembered per key.",
+            );
+        });
+
+        let identity_count = self.settings_dialog.draft.identities.len();
+        let mut remove_idx: Option<usize> = None;
+        let mut swap_idx: Option<(usize, usize)> = None;
+        for (i, identity) in self.settings_dialog.draft.identities.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("Key {}", i + 2)).color(theme.muted));
+                ui.add(
+                    egui::TextEdit::singleline(&mut identity.private_key_path)
+                        .hint_text("C:\\Users\\you\\.ssh\\id_deploy")
+                        .desired_width((ui.available_width() - 300.0).max(120.0)),
+                );
+                if ui.button("Browse...").clicked() {
+                    let mut dlg = rfd::FileDialog::new();
+                    if let Some(profile_dir) = user_profile_dir() {
+                        dlg = dlg.set_directory(profile_dir);
+                    }
+                    if let Some(path) = dlg.pick_file() {
+                        identity.private_key_path = path.display().to_string();
+                    }
+                }
+                if !identity.key_passphrase.is_empty()
+                    && ui
+                        .button("Forget passphrase")
+                        .on_hover_text("Ask for this key's passphrase again on next connect.")
+                        .clicked()
+                {
+                    identity.key_passphrase.clear();
+                }
+                if ui
+                    .add_enabled(i > 0, egui::Button::new("Up"))
+                    .clicked()
+                {
+                    swap_idx = Some((i - 1, i));
+                }
+                if ui
+                    .add_enabled(i + 1 < identity_count, egui::Button::new("Down"))
+                    .clicked()
+                {
+                    swap_idx = Some((i, i + 1));
+                }
+                if ui.button("Remove").clicked() {
+                    remove_idx = Some(i);
+                }
+            });
+        }
+
+        if let Some((a, b)) = swap_idx {
+            self.settings_dialog.draft.identities.swap(a, b);
+        }
+        if let Some(i) = remove_idx {
+            self.settings_dialog.draft.identities.remove(i);
+        }
+        if ui.button("Add key").clicked() {
+            self.settings_dialog
+                .draft
+                .identities
+                .push(IdentityFile::default());
+        }
+    }
+
+    fn draw_profile_jump_hosts_editor(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Jump hosts").strong());
+            ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                "Bastion hosts are connected in order before the target host (like OpenSSH ProxyJump). Empty user falls back to the profile user.",
+            );
+        });
+
+        let hop_count = self.settings_dialog.draft.jump_hosts.len();
+        let mut remove_idx: Option<usize> = None;
+        let mut swap_idx: Option<(usize, usize)> = None;
+        let mut passphrase_entered = false;
+        for (i, hop) in self.settings_dialog.draft.jump_hosts.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("Hop {}", i + 1)).color(theme.muted));
+                if ui
+                    .add_enabled(i > 0, egui::Button::new("Up"))
+                    .clicked()
+                {
+                    swap_idx = Some((i - 1, i));
+                }
+                if ui
+                    .add_enabled(i + 1 < hop_count, egui::Button::new("Down"))
+                    .clicked()
+                {
+                    swap_idx = Some((i, i + 1));
+                }
+                if ui.button("Remove").clicked() {
+                    remove_idx = Some(i);
+                }
+            });
+            egui::Grid::new(("settings_profile_jump_host_grid", i))
+                .num_columns(2)
+                .spacing(Vec2::new(10.0, 6.0))
+                .show(ui, |ui| {
+                    ui.label("Host");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut hop.host)
+                            .hint_text("bastion.example.com")
+                            .desired_width(ui.available_width()),
+                    );
+                    ui.end_row();
+
+                    ui.label("Port");
+                    ui.add(
+                        egui::DragValue::new(&mut hop.port)
+                            .speed(1.0)
+                            .clamp_range(1..=65535),
+                    );
+                    ui.end_row();
+
+                    ui.label("User");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut hop.username)
+                            .hint_text("same as profile")
+                            .desired_width(ui.available_width()),
+                    );
+                    ui.end_row();
+
+                    ui.label("Password (optional)");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut hop.password)
+                            .password(true)
+                            .desired_width(ui.available_width()),
+                    );
+                    ui.end_row();
+
+                    ui.label("Private key (optional)");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut hop.private_key_path)
+                                .desired_width((ui.available_width() - 92.0).max(120.0)),
+                        );
+                        if ui.button("Browse...").clicked() {
+                            let mut dlg = rfd::FileDialog::new();
+                            if let Some(profile_dir) = user_profile_dir() {
+                                dlg = dlg.set_directory(profile_dir);
+                            }
+                            if let Some(path) = dlg.pick_file() {
+                                hop.private_key_path = path.display().to_string();
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Key passphrase (optional)");
+                    let passphrase = ui
+                        .add(
+                            egui::TextEdit::singleline(&mut hop.key_passphrase)
+                                .password(true)
+                                .desired_width(ui.available_width()),
+                        )
+                        .on_hover_text(
+                            "Stored encrypted in local config using Windows DPAPI. Leave empty to be asked on connect.",
+                        );
+                    if passphrase.changed() && !hop.key_passphrase.is_empty() {
+                        passphrase_entered = true;
+                    }
+                    ui.end_row();
+                });
+        }
+        // Typing a passphrase here is the opt-in; without it saving would drop the passphrase.
+        if passphrase_entered {
+            self.settings_dialog.remember_key_passphrase = true;
+        }
+
+        if let Some((a, b)) = swap_idx {
+            self.settings_dialog.draft.jump_hosts.swap(a, b);
+        }
+        if let Some(i) = remove_idx {
+            self.settings_dialog.draft.jump_hosts.remove(i);
+        }
+        if ui.button("Add jump host").clicked() {
+            self.settings_dialog
+                .draft
+                .jump_hosts
+                .push(JumpHostSettings::default());
+        }
+    }
+
+    /// Principals and expiry of the draft's certificate, re-read only when the file changes.
+    fn certificate_preview_label(&mut self) -> Option<(String, ssh::IssueKind)> {
+        let Some(path) = ssh::user_certificate_path(&self.settings_dialog.draft) else {
+            self.settings_dialog.certificate_preview = None;
+            return None;
+        };
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let stale = self
+            .settings_dialog
+            .certificate_preview
+            .as_ref()
+            .map(|preview| preview.path != path || preview.modified != modified)
+            .unwrap_or(true);
+        if stale {
+            let summary = ssh::CertificateSummary::read(&path).map_err(|err| format!("{err:#}"));
+            self.settings_dialog.certificate_preview = Some(CertificatePreview {
+                path,
+                modified,
+                summary,
+            });
+        }
+
+        let preview = self.settings_dialog.certificate_preview.as_ref()?;
+        let summary = match &preview.summary {
+            Ok(summary) => summary,
+            Err(err) => return Some((err.clone(), ssh::IssueKind::Configuration)),
+        };
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let expiry = if summary.valid_before == u64::MAX {
+            "never expires".to_string()
+        } else if summary.is_not_yet_valid(now) {
+            format!(
+                "valid from {}",
+                Self::file_modified_label(Some(summary.valid_after))
+            )
+        } else if summary.is_expired(now) {
+            format!("expired {}", Self::file_modified_label(Some(summary.valid_before)))
+        } else {
+            format!("expires {}", Self::file_modified_label(Some(summary.valid_before)))
+        };
+        let kind = if summary.is_expired(now) || summary.is_not_yet_valid(now) {
+            ssh::IssueKind::Authentication
+        } else {
+            ssh::IssueKind::Info
+        };
+        Some((
+            format!("Principals: {} - {expiry}", summary.principals_label()),
+            kind,
+        ))
+    }
+
+    fn draw_profile_algorithms_editor(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Algorithms").strong());
+            ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                "Comma-separated names in order of preference, e.g. to reach legacy devices (diffie-hellman-group14-sha1, ssh-rsa) or to restrict a host to a hardened set. Leave empty for the defaults.",
+            );
+        });
+
+        let prefs = &mut self.settings_dialog.draft.algorithms;
+        let fields = [
+            &mut prefs.kex,
+            &mut prefs.ciphers,
+            &mut prefs.macs,
+            &mut prefs.host_keys,
+        ];
+        egui::Grid::new("settings_profile_algorithms_grid")
+            .num_columns(2)
+            .spacing(Vec2::new(10.0, 6.0))
+            .show(ui, |ui| {
+                for ((label, supported), value) in
+                    ssh::supported_algorithm_names().into_iter().zip(fields)
+                {
+                    ui.label(label);
+                    ui.add(
+                        egui::TextEdit::singleline(value)
+                            .hint_text("default")
+                            .desired_width(ui.available_width()),
+                    )
+                    .on_hover_text(format!("Supported: {}", supported.join(", ")));
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn draw_profile_local_forwards_editor(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Local forwards").strong());
+            ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                "Listens on a local port and tunnels each connection to the remote host/port through the SSH session (like ssh -L). Forwards run while the tab's session is connected.",
+            );
+        });
+
+        let forwards = &mut self.settings_dialog.draft.local_forwards;
+        let mut remove_idx: Option<usize> = None;
+        if !forwards.is_empty() {
+            egui::Grid::new("settings_profile_local_forwards_grid")
+                .num_columns(5)
+                .spacing(Vec2::new(8.0, 6.0))
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new("Bind address").color(theme.muted));
+                    ui.label(egui::RichText::new("Local port").color(theme.muted));
+                    ui.label(egui::RichText::new("Remote host").color(theme.muted));
+                    ui.label(egui::RichText::new("Remote port").color(theme.muted));
+                    ui.label("");
+                    ui.end_row();
+
+                    for (i, forward) in forwards.iter_mut().enumerate() {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut forward.bind_address)
+                                .hint_text("127.0.0.1")
+                                .desired_width(110.0),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut forward.local_port)
+                                .speed(1.0)
+                                .clamp_range(0..=65535),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut forward.remote_host)
+                                .hint_text("localhost")
+                                .desired_width(140.0),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut forward.remote_port)
+                                .speed(1.0)
+                                .clamp_range(0..=65535),
+                        );
+                        if ui.button("Remove").clicked() {
+                            remove_idx = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+        }
+        if let Some(i) = remove_idx {
+            forwards.remove(i);
+        }
+        if ui.button("Add local forward").clicked() {
+            forwards.push(LocalForward::default());
+        }
+    }
+
+    fn draw_profile_remote_forwards_editor(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Remote forwards").strong());
+            ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                "Asks the server to listen on a port and tunnels each connection back to a local host/port (like ssh -R). Remote port 0 lets the server choose; an empty bind address means the server's loopback.",
+            );
+        });
+
+        let forwards = &mut self.settings_dialog.draft.remote_forwards;
+        let mut remove_idx: Option<usize> = None;
+        if !forwards.is_empty() {
+            egui::Grid::new("settings_profile_remote_forwards_grid")
+                .num_columns(5)
+                .spacing(Vec2::new(8.0, 6.0))
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new("Remote bind").color(theme.muted));
+                    ui.label(egui::RichText::new("Remote port").color(theme.muted));
+                    ui.label(egui::RichText::new("Local host").color(theme.muted));
+                    ui.label(egui::RichText::new("Local port").color(theme.muted));
+                    ui.label("");
+                    ui.end_row();
+
+                    for (i, forward) in forwards.iter_mut().enumerate() {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut forward.bind_address)
+                                .hint_text("localhost")
+                                .desired_width(110.0),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut forward.remote_port)
+                                .speed(1.0)
+                                .clamp_range(0..=65535),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut forward.local_host)
+                                .hint_text("127.0.0.1")
+                                .desired_width(140.0),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut forward.local_port)
+                                .speed(1.0)
+                                .clamp_range(0..=65535),
+                        );
+                        if ui.button("Remove").clicked() {
+                            remove_idx = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+        }
+        if let Some(i) = remove_idx {
+            forwards.remove(i);
+        }
+        if ui.button("Add remote forward").clicked() {
+            forwards.push(RemoteForward::default());
+        }
+    }
+
+    fn draw_profile_environment_editor(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Environment").strong());
+            ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                "Sent before the shell starts (like SetEnv). The server only accepts names listed in its AcceptEnv; refused variables are reported in the status bar.",
+            );
+        });
+
+        let vars = &mut self.settings_dialog.draft.environment;
+        let mut remove_idx: Option<usize> = None;
+        if !vars.is_empty() {
+            egui::Grid::new("settings_profile_environment_grid")
+                .num_columns(3)
+                .spacing(Vec2::new(8.0, 6.0))
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new("Name").color(theme.muted));
+                    ui.label(egui::RichText::new("Value").color(theme.muted));
+                    ui.label("");
+                    ui.end_row();
+
+                    for (i, var) in vars.iter_mut().enumerate() {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut var.name)
+                                .hint_text("LANG")
+                                .desired_width(120.0),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut var.value)
+                                .hint_text("en_US.UTF-8")
+                                .desired_width(180.0),
+                        );
+                        if ui.button("Remove").clicked() {
+                            remove_idx = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+        }
+        if let Some(i) = remove_idx {
+            vars.remove(i);
+        }
+        if ui.button("Add variable").clicked() {
+            vars.push(EnvVar::default());
+        }
+    }
+
+    fn draw_profile_dynamic_forwards_editor(&mut self, ui: &mut egui::Ui) {
+        let theme = self.theme;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Dynamic (SOCKS) forwards").strong());
+            ui.label(egui::RichText::new("(i)").color(theme.muted)).on_hover_text(
+                "Runs a local SOCKS4/5 proxy; every CONNECT is tunnelled through the tab's existing SSH session (like ssh -D).",
+            );
+        });
+
+        let forwards = &mut self.settings_dialog.draft.dynamic_forwards;
+        let mut remove_idx: Option<usize> = None;
+        if !forwards.is_empty() {
+            egui::Grid::new("settings_profile_dynamic_forwards_grid")
+                .num_columns(3)
+                .spacing(Vec2::new(8.0, 6.0))
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new("Bind address").color(theme.muted));
+                    ui.label(egui::RichText::new("Local port").color(theme.muted));
+                    ui.label("");
+                    ui.end_row();
+
+                    for (i, forward) in forwards.iter_mut().enumerate() {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut forward.bind_address)
+                                .hint_text("127.0.0.1")
+                                .desired_width(110.0),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut forward.local_port)
+                                .speed(1.0)
+                                .clamp_range(0..=65535),
+                        );
+                        if ui.button("Remove").clicked() {
+                            remove_idx = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+        }
+        if let Some(i) = remove_idx {
+            forwards.remove(i);
+        }
+        if ui.button("Add SOCKS forward").clicked() {
+            forwards.push(DynamicForward::default());
+        }
+    }
+
     fn draw_settings_contents(
         &mut self,
         ui: &mut egui::Ui,
@@ -1486,6 +2990,10 @@ impl AppState {
                                     self.apply_font_preferences(ui.ctx());
                                 }
                             }
+                            if page == SettingsPage::KnownHosts {
+                                // Pick up edits made outside Rusty since the page was last shown.
+                                self.settings_dialog.known_hosts.loaded = false;
+                            }
                         }
                     };
 
@@ -1496,6 +3004,8 @@ impl AppState {
                     item(ui, SettingsPage::Updates);
                     item(ui, SettingsPage::UiTheme);
                     item(ui, SettingsPage::TerminalColors);
+                    item(ui, SettingsPage::Network);
+                    item(ui, SettingsPage::KnownHosts);
                     item(ui, SettingsPage::ProfilesAndAccount);
                 });
 
@@ -1530,6 +3040,8 @@ impl AppState {
                     SettingsPage::Updates => self.draw_settings_page_updates(ui),
                     SettingsPage::UiTheme => self.draw_settings_page_ui_theme(ui),
                     SettingsPage::TerminalColors => self.draw_settings_page_terminal_colors(ui),
+                    SettingsPage::Network => self.draw_settings_page_network(ui),
+                    SettingsPage::KnownHosts => self.draw_settings_page_known_hosts(ui),
                     SettingsPage::ProfilesAndAccount => {
                         self.draw_settings_page_profiles_and_account(ui)
                     }
@@ -1711,6 +3223,14 @@ impl AppState {
                         .color(Color32::from_rgb(220, 170, 90)),
                     );
                 }
+                if dialog.prompt.cert_authorities > 0 {
+                    ui.label(
+                        egui::RichText::new(
+                            "known_hosts has an @cert-authority entry for this host, but Rusty does not validate host certificates. Check the fingerprint as you would for any new host.",
+                        )
+                        .color(Color32::from_rgb(220, 170, 90)),
+                    );
+                }
                 ui.add_space(4.0);
 
                 ui.label(egui::RichText::new("SHA256 fingerprint").strong());
@@ -1816,7 +3336,9 @@ impl AppState {
                 .prompts
                 .iter()
                 .any(|p| p.text.to_ascii_lowercase().contains("key passphrase"));
-            (prompt_is_key || (instr.contains("private key") && prompt_has_passphrase))
+            (auth.key_path.is_some()
+                || prompt_is_key
+                || (instr.contains("private key") && prompt_has_passphrase))
                 && auth.prompts.len() == 1
         };
 
@@ -1971,12 +3493,16 @@ impl AppState {
                     {
                         if let Some(i) = config::find_profile_index(&self.config, profile_name) {
                             self.config.profiles[i].remember_key_passphrase = true;
-                            self.config.profiles[i].settings.key_passphrase = pw.clone();
+                            self.config.profiles[i]
+                                .settings
+                                .set_key_passphrase(auth.key_path.as_deref(), pw.clone());
                             self.config_saver.request_save(self.config.clone());
 
                             if self.settings_dialog.selected_profile == Some(i) {
                                 self.settings_dialog.remember_key_passphrase = true;
-                                self.settings_dialog.draft.key_passphrase = pw.clone();
+                                self.settings_dialog
+                                    .draft
+                                    .set_key_passphrase(auth.key_path.as_deref(), pw.clone());
                             }
                         }
                     }
@@ -1985,7 +3511,7 @@ impl AppState {
 
             if let Some(tab) = self.pane_mut(tab_id) {
                 if let Some(pw) = key_pw {
-                    tab.settings.key_passphrase = pw;
+                    tab.settings.set_key_passphrase(auth.key_path.as_deref(), pw);
                 }
                 if let Some(tx) = tab.worker_tx.as_ref() {
                     let _ = tx.send(WorkerMessage::AuthResponse(responses));
@@ -2003,3 +3529,90 @@ impl AppState {
         }
     }
 }
+
+#[cfg(test)]
+mod known_hosts_manager_tests {
+    use super::*;
+    use russh::keys::parse_public_key_base64;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const KEY_A: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIJdD7y3aLq454yWBdwLWbieU1ebz9/cu7/QEXn9OIeZJ";
+    const KEY_B: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIA6rWI3G1sz07DnfFlrouTcysQlj2P+jpNSOEWD9OJ3X";
+
+    fn probe_answer(app: &mut AppState, path: &Path, key: &str) {
+        let (tx, rx) = mpsc::channel();
+        tx.send(Ok(parse_public_key_base64(key).unwrap())).unwrap();
+        app.settings_dialog.known_hosts.probe = Some(KnownHostProbe {
+            path: path.to_path_buf(),
+            line: 1,
+            host: "db.example".to_string(),
+            port: 22,
+            rx,
+        });
+        app.poll_known_host_probe();
+    }
+
+    #[test]
+    fn probe_result_is_compared_and_repinned() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("rusty-repin-{nanos}"));
+        std::fs::write(
+            &path,
+            format!("db.example ssh-ed25519 {KEY_A}\nother.example ssh-ed25519 {KEY_B}\n"),
+        )
+        .unwrap();
+
+        let mut app = AppState::new_for_tests();
+        app.settings_dialog.known_hosts.rows = known_hosts::read_file(&path)
+            .unwrap()
+            .into_iter()
+            .map(|entry| KnownHostRow {
+                path: path.clone(),
+                entry,
+                comparison: None,
+            })
+            .collect();
+
+        probe_answer(&mut app, &path, KEY_A);
+        assert!(matches!(
+            app.settings_dialog.known_hosts.rows[0].comparison,
+            Some(KeyComparison::Matches)
+        ));
+
+        // A matching key offers nothing to re-pin.
+        app.repin_known_host_row(&path, 1);
+        assert_eq!(known_hosts::read_file(&path).unwrap().len(), 2);
+
+        probe_answer(&mut app, &path, KEY_B);
+        assert!(matches!(
+            app.settings_dialog.known_hosts.rows[0].comparison,
+            Some(KeyComparison::Differs { ref host, port: 22, .. }) if host == "db.example"
+        ));
+
+        app.repin_known_host_row(&path, 1);
+        let key_a = parse_public_key_base64(KEY_A).unwrap();
+        let key_b = parse_public_key_base64(KEY_B).unwrap();
+        let paths = [path.clone()];
+        assert!(matches!(
+            known_hosts::check(&paths, "db.example", 22, &key_b).unwrap(),
+            known_hosts::HostKeyVerdict::Trusted { .. }
+        ));
+        assert!(!matches!(
+            known_hosts::check(&paths, "db.example", 22, &key_a).unwrap(),
+            known_hosts::HostKeyVerdict::Trusted { .. }
+        ));
+        assert!(matches!(
+            known_hosts::check(&paths, "other.example", 22, &key_b).unwrap(),
+            known_hosts::HostKeyVerdict::Trusted { .. }
+        ));
+        assert_eq!(known_hosts::read_file(&path).unwrap().len(), 2);
+        assert!(matches!(
+            app.settings_dialog.known_hosts.status,
+            Some((ssh::IssueKind::Info, _))
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -113,7 +113,7 @@ impl AppState {
         let ppp = ctx.pixels_per_point();
         let origin = Pos2::new((origin.x * ppp).round() / ppp, (origin.y * ppp).round() / ppp);
 
-        if tab.connected {
+        if tab.has_screen() {
             let galley = Self::terminal_galley(ui, tab, &font_id, &options.term_theme);
             painter.galley(origin, galley.clone(), Color32::WHITE);
             let draw_sel = if let Some(sel) = tab.abs_selection {
@@ -212,6 +212,10 @@ impl AppState {
             );
         }
 
+        if tab.connected {
+            Self::draw_session_badges(ui, &painter, rect, tab, options.theme);
+        }
+
         if options.focus_shade && !options.is_active && !response.has_focus() {
             painter.rect_filled(
                 rect,
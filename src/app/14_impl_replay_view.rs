@@ -0,0 +1,87 @@
+impl AppState {
+    fn replay_view(
+        ui: &mut egui::Ui,
+        ctx: &egui::Context,
+        clipboard: &mut Option<Clipboard>,
+        tab: &mut SshTab,
+        options: TerminalViewOptions,
+    ) {
+        let theme = options.theme;
+        // Bottom-up so the controls claim their row first and the terminal fills the rest.
+        ui.with_layout(egui::Layout::bottom_up(egui::Align::Min), |ui| {
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                ui.add_space(8.0);
+                Self::replay_controls(ui, tab, theme);
+            });
+            ui.add_space(4.0);
+            Self::terminal_view(ui, ctx, clipboard, tab, options);
+        });
+    }
+
+    fn replay_controls(ui: &mut egui::Ui, tab: &mut SshTab, theme: UiTheme) {
+        let term_has_focus = ui.memory(|mem| mem.has_focus(Id::new(("terminal_view", tab.id))));
+        let space_pressed = term_has_focus && ui.input(|i| i.key_pressed(egui::Key::Space));
+        let tab_id = tab.id;
+        let PaneKind::Replay(replay) = &mut tab.kind else {
+            return;
+        };
+        let Some(player) = replay.player.as_ref() else {
+            ui.label(
+                egui::RichText::new(replay.path.display().to_string())
+                    .color(theme.muted)
+                    .size(12.0),
+            );
+            return;
+        };
+        let duration = player.cast().duration();
+        let mut position = player.position();
+
+        let toggle_label = if replay.playing { "Pause" } else { "Play" };
+        if ui.button(toggle_label).clicked() || space_pressed {
+            replay.set_playing(!replay.playing);
+        }
+
+        let mut speed = replay.speed;
+        egui::ComboBox::from_id_source(("replay_speed", tab_id))
+            .width(60.0)
+            .selected_text(format!("{speed}x"))
+            .show_ui(ui, |ui| {
+                for option in ReplayState::SPEEDS {
+                    ui.selectable_value(&mut speed, option, format!("{option}x"));
+                }
+            });
+        replay.speed = speed;
+
+        let time_label = format!(
+            "{} / {}",
+            Self::replay_time_label(position),
+            Self::replay_time_label(duration)
+        );
+        let label_width = 96.0;
+        ui.spacing_mut().slider_width = (ui.available_width() - label_width - 16.0).max(40.0);
+        let scrubbed = ui
+            .add(egui::Slider::new(&mut position, 0.0..=duration.max(0.001)).show_value(false))
+            .on_hover_text(replay.path.display().to_string())
+            .changed();
+        ui.label(
+            egui::RichText::new(time_label)
+                .color(theme.muted)
+                .monospace(),
+        );
+
+        if scrubbed {
+            tab.seek_replay(position);
+        }
+    }
+
+    fn replay_time_label(seconds: f64) -> String {
+        let total = seconds.max(0.0).floor() as u64;
+        let (hours, minutes, secs) = (total / 3600, (total / 60) % 60, total % 60);
+        if hours > 0 {
+            format!("{hours}:{minutes:02}:{secs:02}")
+        } else {
+            format!("{minutes}:{secs:02}")
+        }
+    }
+}
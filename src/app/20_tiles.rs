@@ -14,7 +14,7 @@ enum TilesAction {
     },
     NewTabWithSettings {
         tabs_container_id: TileId,
-        settings: ConnectionSettings,
+        settings: Box<ConnectionSettings>,
         color: Option<Color32>,
         profile_name: Option<String>,
     },
@@ -22,6 +22,14 @@ enum TilesAction {
     Connect(TileId),
     ToggleConnect(TileId),
     OpenFileManager(TileId),
+    ToggleRecording(TileId),
+    OpenRecording {
+        tabs_container_id: TileId,
+    },
+    OpenLocalShell {
+        tabs_container_id: TileId,
+    },
+    ToggleLocalShell(TileId),
     OpenSettings(TileId),
     Rename(TileId),
     SetColor {
@@ -156,23 +164,20 @@ impl<'a> TilesBehavior<SshTab> for SshTilesBehavior<'a> {
         }
 
         let ctx = ui.ctx().clone();
-        if pane.is_terminal() {
-            AppState::terminal_view(
-                ui,
-                &ctx,
-                self.clipboard,
-                pane,
-                TerminalViewOptions {
-                    is_active: self.active_tile == Some(tile_id),
-                    theme: self.theme,
-                    term_theme: self.term_theme,
-                    cursor_visible: self.cursor_visible,
-                    term_font_size: self.term_font_size,
-                    allow_resize: self.allow_resize,
-                    focus_shade: self.focus_shade,
-                    show_active_border: self.show_active_border,
-                },
-            );
+        let view_options = TerminalViewOptions {
+            is_active: self.active_tile == Some(tile_id),
+            theme: self.theme,
+            term_theme: self.term_theme,
+            cursor_visible: self.cursor_visible,
+            term_font_size: self.term_font_size,
+            allow_resize: self.allow_resize,
+            focus_shade: self.focus_shade,
+            show_active_border: self.show_active_border,
+        };
+        if pane.is_shell_pane() {
+            AppState::terminal_view(ui, &ctx, self.clipboard, pane, view_options);
+        } else if pane.replay().is_some() {
+            AppState::replay_view(ui, &ctx, self.clipboard, pane, view_options);
         } else {
             let actions = AppState::file_manager_view(ui, pane, self.theme, tile_id);
             self.actions.extend(actions);
@@ -181,7 +186,12 @@ impl<'a> TilesBehavior<SshTab> for SshTilesBehavior<'a> {
         if crate::logger::ui_profile_enabled() && pane_dt >= Duration::from_millis(4) {
             crate::logger::log_ui_profile(&format!(
                 "pane_ui tile={tile_id:?} kind={} connected={} connecting={} elapsed_ms={:.2}",
-                if pane.is_terminal() { "terminal" } else { "file_manager" },
+                match pane.kind {
+                    PaneKind::Terminal => "terminal",
+                    PaneKind::FileManager(_) => "file_manager",
+                    PaneKind::Replay(_) => "replay",
+                    PaneKind::LocalShell(_) => "local_shell",
+                },
                 pane.connected,
                 pane.connecting,
                 pane_dt.as_secs_f64() * 1000.0,
@@ -198,6 +208,12 @@ impl<'a> TilesBehavior<SshTab> for SshTilesBehavior<'a> {
             .to_string();
         if pane.connecting {
             label.push_str(" ...");
+        } else if let Some(secs) = pane
+            .auto_reconnect
+            .as_ref()
+            .and_then(|state| state.seconds_left(Instant::now()))
+        {
+            label.push_str(&format!(" ({secs}s)"));
         }
         label.into()
     }
@@ -356,13 +372,40 @@ impl<'a> TilesBehavior<SshTab> for SshTilesBehavior<'a> {
         };
 
         let pane_state = match tiles.get(tile_id) {
-            Some(Tile::Pane(pane)) => Some((pane.is_terminal(), pane.connecting, pane.connected)),
+            Some(Tile::Pane(pane)) => Some((
+                pane.is_terminal(),
+                pane.is_local_shell(),
+                pane.shares_session(),
+                pane.connecting,
+                pane.connected,
+                pane.recording.is_some(),
+            )),
             _ => None,
         };
 
         if pane_state.is_some() {
             response.context_menu(|ui: &mut egui::Ui| {
-                if let Some((is_terminal, connecting, connected)) = pane_state {
+                if let Some((
+                    is_terminal,
+                    is_local_shell,
+                    has_sftp,
+                    connecting,
+                    connected,
+                    recording,
+                )) = pane_state
+                {
+                    if is_local_shell {
+                        let label = if connecting || connected {
+                            "Stop Shell"
+                        } else {
+                            "Restart Shell"
+                        };
+                        if ui.button(label).clicked() {
+                            self.actions.push(TilesAction::ToggleLocalShell(tile_id));
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                    }
                     if is_terminal {
                         if connecting || connected {
                             if ui.button("Disconnect").clicked() {
@@ -373,10 +416,25 @@ impl<'a> TilesBehavior<SshTab> for SshTilesBehavior<'a> {
                             self.actions.push(TilesAction::Connect(tile_id));
                             ui.close_menu();
                         }
-                        if ui.button("Open File Manager").clicked() {
+                        if ui
+                            .add_enabled(has_sftp, egui::Button::new("Open File Manager"))
+                            .clicked()
+                        {
                             self.actions.push(TilesAction::OpenFileManager(tile_id));
                             ui.close_menu();
                         }
+                        if recording {
+                            if ui.button("Stop Recording").clicked() {
+                                self.actions.push(TilesAction::ToggleRecording(tile_id));
+                                ui.close_menu();
+                            }
+                        } else if ui
+                            .add_enabled(connected, egui::Button::new("Start Recording..."))
+                            .clicked()
+                        {
+                            self.actions.push(TilesAction::ToggleRecording(tile_id));
+                            ui.close_menu();
+                        }
                         ui.separator();
                     }
                 }
@@ -586,6 +644,22 @@ impl<'a> TilesBehavior<SshTab> for SshTilesBehavior<'a> {
 
             // Right-click the + button to create a new tab from a saved profile.
             plus_resp.context_menu(|ui: &mut egui::Ui| {
+                if ui.button("Open Local Shell").clicked() {
+                    self.actions.push(TilesAction::OpenLocalShell {
+                        tabs_container_id: tile_id,
+                    });
+                    *scroll_offset += 10_000.0;
+                    ui.close_menu();
+                }
+                if ui.button("Open Recording...").clicked() {
+                    self.actions.push(TilesAction::OpenRecording {
+                        tabs_container_id: tile_id,
+                    });
+                    *scroll_offset += 10_000.0;
+                    ui.close_menu();
+                }
+                ui.separator();
+
                 ui.label(egui::RichText::new("New Tab From Profile").strong());
                 ui.separator();
 
@@ -598,7 +672,7 @@ impl<'a> TilesBehavior<SshTab> for SshTilesBehavior<'a> {
                     if ui.button(&name).clicked() {
                         self.actions.push(TilesAction::NewTabWithSettings {
                             tabs_container_id: tile_id,
-                            settings,
+                            settings: Box::new(settings),
                             // Default tab color; user can change via tab right-click menu.
                             color: None,
                             profile_name: Some(name),
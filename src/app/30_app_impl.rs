@@ -36,6 +36,7 @@ impl eframe::App for AppState {
         }
         self.ensure_tray_icon();
         self.handle_tray_events(ctx);
+        self.handle_launch_requests(ctx);
 
         self.update_cursor_blink();
 
@@ -67,6 +68,7 @@ impl eframe::App for AppState {
             self.last_terminal_activity = Instant::now();
         }
         self.sync_shared_terminal_groups();
+        let auto_reconnect_tick = self.drive_auto_reconnects();
         self.route_sftp_events();
         self.poll_download_manager_events();
         self.sync_file_panes_with_sources();
@@ -119,6 +121,14 @@ impl eframe::App for AppState {
                     .unwrap_or(fallback_ms),
             );
         }
+        if let Some(tick) = auto_reconnect_tick {
+            let tick_ms = tick.as_millis().clamp(1, u64::MAX as u128) as u64;
+            repaint_ms = Some(
+                repaint_ms
+                    .map(|current| current.min(tick_ms))
+                    .unwrap_or(tick_ms),
+            );
+        }
         if let Some(repaint_ms) = repaint_ms {
             ctx.request_repaint_after(Duration::from_millis(repaint_ms));
         }
@@ -187,6 +197,7 @@ impl eframe::App for AppState {
                         profile_name,
                         instructions: p.instructions,
                         prompts: p.prompts,
+                        key_path: p.key_path,
                         responses: vec![String::new(); n],
                         just_opened: true,
                         remember_key_passphrase,
@@ -442,6 +453,10 @@ impl eframe::App for AppState {
                     TilesAction::Connect(_) => "connect",
                     TilesAction::ToggleConnect(_) => "toggle_connect",
                     TilesAction::OpenFileManager(_) => "open_file_manager",
+                    TilesAction::ToggleRecording(_) => "toggle_recording",
+                    TilesAction::OpenRecording { .. } => "open_recording",
+                    TilesAction::OpenLocalShell { .. } => "open_local_shell",
+                    TilesAction::ToggleLocalShell(_) => "toggle_local_shell",
                     TilesAction::OpenSettings(_) => "open_settings",
                     TilesAction::Rename(_) => "rename",
                     TilesAction::SetColor { .. } => "set_color",
@@ -480,7 +495,7 @@ impl eframe::App for AppState {
                 } => {
                     let _ = self.add_new_pane_to_tabs_with_settings(
                         tabs_container_id,
-                        settings,
+                        *settings,
                         color,
                         profile_name,
                     );
@@ -506,13 +521,7 @@ impl eframe::App for AppState {
                     }
                     let (missing_settings, connected_or_connecting) = self
                         .terminal_pane(tile_id)
-                        .map(|t| {
-                            (
-                                t.settings.host.trim().is_empty()
-                                    || t.settings.username.trim().is_empty(),
-                                t.connecting || t.connected,
-                            )
-                        })
+                        .map(|t| (!t.settings.can_connect(), t.connecting || t.connected))
                         .unwrap_or((true, false));
 
                     if missing_settings || connected_or_connecting {
@@ -535,10 +544,7 @@ impl eframe::App for AppState {
                     }
                     let needs_settings = self
                         .terminal_pane(tile_id)
-                        .map(|t| {
-                            t.settings.host.trim().is_empty()
-                                || t.settings.username.trim().is_empty()
-                        })
+                        .map(|t| !t.settings.can_connect())
                         .unwrap_or(true);
                     if needs_settings {
                         self.open_settings_dialog_for_tile(tile_id);
@@ -566,6 +572,30 @@ impl eframe::App for AppState {
                     let _ = self.open_file_manager_for_terminal(tile_id);
                     self.layout_dirty = true;
                 }
+                TilesAction::ToggleRecording(tile_id) => {
+                    self.toggle_recording(tile_id);
+                }
+                TilesAction::OpenRecording { tabs_container_id } => {
+                    if self.open_recording(tabs_container_id).is_some() {
+                        self.layout_dirty = true;
+                    }
+                }
+                TilesAction::OpenLocalShell { tabs_container_id } => {
+                    let _ = self.open_local_shell(tabs_container_id);
+                    self.layout_dirty = true;
+                }
+                TilesAction::ToggleLocalShell(tile_id) => {
+                    if let Some(tab) = self.pane_mut(tile_id).filter(|tab| tab.is_local_shell()) {
+                        if tab.connected || tab.connecting {
+                            tab.disconnect();
+                        } else {
+                            tab.start_connect();
+                        }
+                        tab.focus_terminal_next_frame = true;
+                    }
+                    self.set_active_tile(Some(tile_id));
+                    self.layout_dirty = true;
+                }
                 TilesAction::Rename(tile_id) => {
                     if let Some(tab) = self.pane(tile_id) {
                         let initial = tab.user_title.clone().unwrap_or_else(|| tab.title.clone());
@@ -672,6 +702,8 @@ impl eframe::App for AppState {
             }
         }
 
+        self.draw_layout_launch_confirm(ctx);
+
         let rename_popup_started = Instant::now();
         // Rename popup (global).
         let mut rename_action: Option<(TileId, String)> = None;
@@ -737,6 +769,7 @@ impl eframe::App for AppState {
 
         let dialogs_started = Instant::now();
         self.draw_settings_dialog(ctx);
+        self.draw_ssh_config_import_dialog(ctx);
         self.draw_host_key_dialog(ctx);
         self.draw_auth_dialog(ctx);
         self.draw_downloads_manager_window(ctx);
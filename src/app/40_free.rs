@@ -220,3 +220,70 @@ fn title_bar_image_button(
     }
     response
 }
+
+/// Proxy type/host/port/credential rows shared by the global and per-profile proxy editors.
+/// Returns true when any field changed.
+fn draw_proxy_settings_grid(ui: &mut egui::Ui, id_source: &str, proxy: &mut ProxySettings) -> bool {
+    let mut changed = false;
+    egui::Grid::new(id_source)
+        .num_columns(2)
+        .spacing(Vec2::new(10.0, 6.0))
+        .show(ui, |ui| {
+            ui.label("Proxy type");
+            egui::ComboBox::from_id_source((id_source, "kind"))
+                .selected_text(proxy.kind.label())
+                .show_ui(ui, |ui| {
+                    for kind in [ProxyKind::Direct, ProxyKind::Socks5, ProxyKind::HttpConnect] {
+                        changed |= ui
+                            .selectable_value(&mut proxy.kind, kind, kind.label())
+                            .changed();
+                    }
+                });
+            ui.end_row();
+
+            let enabled = proxy.kind != ProxyKind::Direct;
+            ui.label("Proxy host");
+            changed |= ui
+                .add_enabled(
+                    enabled,
+                    egui::TextEdit::singleline(&mut proxy.host)
+                        .hint_text("proxy.example.com")
+                        .desired_width(ui.available_width()),
+                )
+                .changed();
+            ui.end_row();
+
+            ui.label("Proxy port");
+            changed |= ui
+                .add_enabled(
+                    enabled,
+                    egui::DragValue::new(&mut proxy.port)
+                        .speed(1.0)
+                        .clamp_range(1..=65535),
+                )
+                .changed();
+            ui.end_row();
+
+            ui.label("Proxy user (optional)");
+            changed |= ui
+                .add_enabled(
+                    enabled,
+                    egui::TextEdit::singleline(&mut proxy.username)
+                        .desired_width(ui.available_width()),
+                )
+                .changed();
+            ui.end_row();
+
+            ui.label("Proxy password");
+            changed |= ui
+                .add_enabled(
+                    enabled,
+                    egui::TextEdit::singleline(&mut proxy.password)
+                        .password(true)
+                        .desired_width(ui.available_width()),
+                )
+                .changed();
+            ui.end_row();
+        });
+    changed
+}
@@ -0,0 +1,389 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use crate::terminal_emulator::{Parser, Screen};
+
+/// Writes an asciinema v2 recording: a JSON header line, then one `[time, code, data]`
+/// event per line with times in seconds since the recording started.
+pub struct CastWriter {
+    out: BufWriter<File>,
+    started: Instant,
+    /// Trailing bytes of an output chunk that end mid UTF-8 sequence.
+    pending_output: Vec<u8>,
+    events: u64,
+}
+
+impl CastWriter {
+    pub fn create(
+        path: &Path,
+        cols: u16,
+        rows: u16,
+        term: &str,
+        title: &str,
+    ) -> std::io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let header = json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": timestamp,
+            "title": title,
+            "env": { "TERM": term },
+        });
+        writeln!(out, "{header}")?;
+        Ok(Self {
+            out,
+            started: Instant::now(),
+            pending_output: Vec::new(),
+            events: 0,
+        })
+    }
+
+    /// Raw terminal output; multi-byte characters split across chunks are held back until
+    /// complete so every event stays valid UTF-8.
+    pub fn output(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.pending_output.extend_from_slice(bytes);
+        let text = take_utf8(&mut self.pending_output);
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.event("o", &text)
+    }
+
+    pub fn input(&mut self, text: &str) -> std::io::Result<()> {
+        self.event("i", text)
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) -> std::io::Result<()> {
+        self.event("r", &format!("{cols}x{rows}"))?;
+        self.out.flush()
+    }
+
+    pub fn finish(mut self) -> std::io::Result<u64> {
+        if !self.pending_output.is_empty() {
+            let text = String::from_utf8_lossy(&self.pending_output).into_owned();
+            self.pending_output.clear();
+            self.event("o", &text)?;
+        }
+        self.out.flush()?;
+        Ok(self.events)
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> std::io::Result<()> {
+        // Microsecond precision, as asciinema itself writes.
+        let time = (self.started.elapsed().as_secs_f64() * 1e6).round() / 1e6;
+        writeln!(self.out, "{}", json!([time, code, data]))?;
+        self.events += 1;
+        Ok(())
+    }
+}
+
+/// A recording loaded from a `.cast` file; input and marker events are dropped.
+pub struct Cast {
+    pub width: u16,
+    pub height: u16,
+    pub title: Option<String>,
+    pub events: Vec<CastEvent>,
+}
+
+pub struct CastEvent {
+    pub time: f64,
+    pub kind: CastEventKind,
+}
+
+pub enum CastEventKind {
+    Output(String),
+    Resize { cols: u16, rows: u16 },
+}
+
+impl Cast {
+    pub fn duration(&self) -> f64 {
+        self.events.last().map(|event| event.time).unwrap_or(0.0)
+    }
+}
+
+pub fn read(path: &Path) -> std::io::Result<Cast> {
+    parse(&std::fs::read_to_string(path)?)
+}
+
+fn parse(text: &str) -> std::io::Result<Cast> {
+    let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| invalid("empty recording".to_string()))?;
+    let header: Value =
+        serde_json::from_str(header).map_err(|err| invalid(format!("bad header: {err}")))?;
+    if header["version"].as_u64() != Some(2) {
+        return Err(invalid(
+            "unsupported recording: only asciicast v2 files can be played".to_string(),
+        ));
+    }
+    let dimension = |key: &str| {
+        header[key]
+            .as_u64()
+            .and_then(|value| u16::try_from(value).ok())
+            .filter(|value| *value > 0)
+            .ok_or_else(|| invalid(format!("header has no valid {key}")))
+    };
+    let width = dimension("width")?;
+    let height = dimension("height")?;
+    let title = header["title"].as_str().map(str::to_string);
+
+    let lines: Vec<(usize, &str)> = lines.collect();
+    let mut events = Vec::new();
+    for (index, (number, line)) in lines.iter().enumerate() {
+        let parsed = serde_json::from_str::<Value>(line).ok().and_then(|value| {
+            let time = value[0].as_f64()?;
+            let code = value[1].as_str()?.to_string();
+            let data = value[2].as_str()?.to_string();
+            Some((time, code, data))
+        });
+        let Some((time, code, data)) = parsed else {
+            // A recording cut short by a crash usually ends in a half-written line.
+            if index + 1 == lines.len() {
+                break;
+            }
+            return Err(invalid(format!("malformed event on line {}", number + 1)));
+        };
+        // Out-of-order timestamps would make seeking skip output.
+        let time = time.max(events.last().map(|e: &CastEvent| e.time).unwrap_or(0.0));
+        let kind = match code.as_str() {
+            "o" => CastEventKind::Output(data),
+            "r" => {
+                let Some((cols, rows)) = data
+                    .split_once('x')
+                    .and_then(|(cols, rows)| Some((cols.parse().ok()?, rows.parse().ok()?)))
+                else {
+                    continue;
+                };
+                CastEventKind::Resize { cols, rows }
+            }
+            _ => continue,
+        };
+        events.push(CastEvent { time, kind });
+    }
+    Ok(Cast {
+        width,
+        height,
+        title,
+        events,
+    })
+}
+
+/// Feeds a [`Cast`] through the terminal emulator up to a playback position.
+pub struct Player {
+    cast: Cast,
+    parser: Parser,
+    scrollback_len: usize,
+    /// Events already fed to `parser`.
+    applied: usize,
+    position: f64,
+}
+
+impl Player {
+    pub fn new(cast: Cast, scrollback_len: usize) -> Self {
+        let parser = Parser::new(cast.height, cast.width, scrollback_len);
+        Self {
+            cast,
+            parser,
+            scrollback_len,
+            applied: 0,
+            position: 0.0,
+        }
+    }
+
+    pub fn cast(&self) -> &Cast {
+        &self.cast
+    }
+
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.applied >= self.cast.events.len()
+    }
+
+    /// Seconds from `position` until the next event, if any is left.
+    pub fn next_event_in(&self) -> Option<f64> {
+        self.cast
+            .events
+            .get(self.applied)
+            .map(|event| (event.time - self.position).max(0.0))
+    }
+
+    /// Moves to `position` seconds; going backwards replays from the start, since the emulator
+    /// cannot be rewound. Returns whether the screen changed.
+    pub fn seek(&mut self, position: f64) -> bool {
+        let position = position.clamp(0.0, self.cast.duration());
+        let mut changed = false;
+        if position < self.position {
+            self.parser = Parser::new(self.cast.height, self.cast.width, self.scrollback_len);
+            self.applied = 0;
+            changed = true;
+        }
+        self.position = position;
+        while let Some(event) = self.cast.events.get(self.applied) {
+            if event.time > position {
+                break;
+            }
+            match &event.kind {
+                CastEventKind::Output(text) => self.parser.process(text.as_bytes()),
+                CastEventKind::Resize { cols, rows } => self.parser.set_size(*rows, *cols),
+            }
+            self.applied += 1;
+            changed = true;
+        }
+        if changed {
+            // Nothing reads OSC 52 writes from a recording; drop them so they don't pile up.
+            let _ = self.parser.take_clipboard_writes();
+        }
+        changed
+    }
+
+    pub fn set_scrollback(&mut self, rows: usize) {
+        self.parser.set_scrollback(rows);
+    }
+
+    pub fn screen(&mut self) -> &Screen {
+        self.parser.screen()
+    }
+}
+
+/// Decodes the complete UTF-8 prefix of `pending`, replacing invalid sequences, and leaves
+/// an incomplete trailing sequence in place.
+fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let mut text = String::new();
+    let mut rest: &[u8] = pending;
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                text.push_str(valid);
+                rest = &[];
+                break;
+            }
+            Err(err) => {
+                let (valid, after) = rest.split_at(err.valid_up_to());
+                text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                match err.error_len() {
+                    Some(len) => {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        rest = &after[len..];
+                    }
+                    None => {
+                        rest = after;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    let tail = rest.to_vec();
+    *pending = tail;
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_split_across_chunks_is_held_back() {
+        let mut pending = "héllo".as_bytes()[..2].to_vec();
+        assert_eq!(take_utf8(&mut pending), "h");
+        assert_eq!(pending, vec![0xC3]);
+        pending.extend_from_slice(&"héllo".as_bytes()[2..]);
+        assert_eq!(take_utf8(&mut pending), "éllo");
+        assert!(pending.is_empty());
+
+        let mut invalid = vec![b'a', 0xFF, b'b'];
+        assert_eq!(take_utf8(&mut invalid), "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn player_seeks_forward_and_back() {
+        let cast = parse(concat!(
+            "{\"version\": 2, \"width\": 20, \"height\": 4}\n",
+            "[0.5, \"o\", \"one\\r\\n\"]\n",
+            "[1.0, \"i\", \"x\"]\n",
+            "[1.5, \"o\", \"two\\r\\n\"]\n",
+            "[2.0, \"r\", \"30x5\"]\n",
+            "[2.5, \"o\", \"thr",
+        ))
+        .unwrap();
+        assert_eq!(cast.events.len(), 3);
+        assert_eq!(cast.duration(), 2.0);
+
+        let mut player = Player::new(cast, 100);
+        assert!(player.seek(1.0));
+        assert!(player.screen().contents().contains("one"));
+        assert!(!player.screen().contents().contains("two"));
+        assert_eq!(player.next_event_in(), Some(0.5));
+
+        assert!(player.seek(10.0));
+        assert!(player.at_end());
+        assert_eq!(player.position(), 2.0);
+        assert_eq!(player.screen().size(), (5, 30));
+
+        assert!(player.seek(0.0));
+        assert!(!player.screen().contents().contains("one"));
+        assert_eq!(player.screen().size(), (4, 20));
+    }
+
+    #[test]
+    fn malformed_recordings_are_rejected() {
+        assert!(parse("").is_err());
+        assert!(parse("{\"version\": 1, \"width\": 80, \"height\": 24}").is_err());
+        assert!(parse(concat!(
+            "{\"version\": 2, \"width\": 80, \"height\": 24}\n",
+            "not json\n",
+            "[1.0, \"o\", \"x\"]\n",
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn writer_emits_header_and_events() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty-cast-{}-{}.cast",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let mut writer = CastWriter::create(&path, 80, 24, "xterm-256color", "web").unwrap();
+        writer.output(b"$ ls\r\n\xE2\x9C").unwrap();
+        writer.output(b"\x93\r\n").unwrap();
+        writer.input("ls\r").unwrap();
+        writer.resize(100, 30).unwrap();
+        assert_eq!(writer.finish().unwrap(), 4);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[0]["env"]["TERM"], "xterm-256color");
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "$ ls\r\n");
+        assert_eq!(lines[2][2], "✓\r\n");
+        assert_eq!(lines[3][1], "i");
+        assert_eq!(lines[4][2], "100x30");
+    }
+}
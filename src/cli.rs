@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, AppConfig};
+use crate::model::ConnectionSettings;
+
+/// What the command line asked Rusty to open. Also the message a second instance hands to
+/// the one already running.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchRequest {
+    pub profile: Option<String>,
+    pub destination: Option<Destination>,
+    /// `-p`; wins over a port in the destination or the profile.
+    pub port: Option<u16>,
+    /// `-i`; replaces the profile's key.
+    pub identity: Option<String>,
+    /// `-l`; wins over a user in the destination or the profile.
+    pub user: Option<String>,
+    pub layout: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Destination {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+}
+
+impl LaunchRequest {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn opens_tab(&self) -> bool {
+        self.profile.is_some() || self.destination.is_some()
+    }
+
+    /// Settings for the tab this request opens and the profile it came from, if any.
+    pub fn connection_settings(
+        &self,
+        cfg: &AppConfig,
+    ) -> Result<(ConnectionSettings, Option<String>), String> {
+        let (mut settings, profile_name) = match &self.profile {
+            Some(name) => {
+                let index = config::find_profile_index(cfg, name)
+                    .ok_or_else(|| format!("No profile named \"{name}\"."))?;
+                let profile = &cfg.profiles[index];
+                (
+                    config::write_profile_settings(profile),
+                    Some(profile.name.clone()),
+                )
+            }
+            None => (ConnectionSettings::default(), None),
+        };
+        if let Some(destination) = &self.destination {
+            settings.host = destination.host.clone();
+            if let Some(user) = &destination.user {
+                settings.username = user.clone();
+            }
+            if let Some(port) = destination.port {
+                settings.port = port;
+            }
+        }
+        if let Some(user) = &self.user {
+            settings.username = user.clone();
+        }
+        if let Some(port) = self.port {
+            settings.port = port;
+        }
+        if let Some(identity) = &self.identity {
+            settings.private_key_path = identity.clone();
+            settings.key_passphrase.clear();
+        }
+        Ok((settings, profile_name))
+    }
+}
+
+/// Parses the arguments after the program name. Accepts `--profile NAME`, `--layout NAME`,
+/// OpenSSH's `-p PORT`, `-i KEY` and `-l USER`, and one destination given as
+/// `[user@]host` or `ssh://[user@]host[:port]`.
+pub fn parse_args<I, S>(args: I) -> Result<LaunchRequest, String>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut request = LaunchRequest::default();
+    let mut args = args.into_iter().map(Into::into);
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .filter(|value| !value.trim().is_empty())
+                .map(|value| value.trim().to_string())
+                .ok_or_else(|| format!("{name} needs a value."))
+        };
+        match flag.as_str() {
+            "--profile" => request.profile = Some(value("--profile")?),
+            "--layout" => request.layout = Some(value("--layout")?),
+            "-p" => request.port = Some(parse_port(&value("-p")?)?),
+            "-i" => request.identity = Some(absolute_path(&value("-i")?)),
+            "-l" => request.user = Some(value("-l")?),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option {arg}.")),
+            _ => {
+                if request.destination.is_some() {
+                    return Err(format!(
+                        "Unexpected argument {arg}: only one host can be opened."
+                    ));
+                }
+                request.destination = Some(parse_destination(&arg)?);
+            }
+        }
+    }
+    Ok(request)
+}
+
+/// Resolves a relative key path against this process's working directory; the request may be
+/// handed to an instance started elsewhere.
+fn absolute_path(path: &str) -> String {
+    std::path::absolute(path)
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+fn parse_destination(arg: &str) -> Result<Destination, String> {
+    let (rest, is_url) = match arg.strip_prefix("ssh://") {
+        Some(rest) => (rest.trim_end_matches('/'), true),
+        None => (arg, false),
+    };
+    let (user, host_port) = match rest.rsplit_once('@') {
+        Some((user, host)) if !user.is_empty() => (Some(user.to_string()), host),
+        Some((_, host)) => (None, host),
+        None => (None, rest),
+    };
+    let (host, port) = if !is_url {
+        // Like OpenSSH, a bare destination has no port; `host:22` is a host name.
+        (host_port, None)
+    } else if let Some(bracketed) = host_port.strip_prefix('[') {
+        let (host, after) = bracketed
+            .split_once(']')
+            .ok_or_else(|| format!("Missing ']' in {arg}."))?;
+        let port = match after.strip_prefix(':') {
+            Some(port) => Some(parse_port(port)?),
+            None if after.is_empty() => None,
+            None => return Err(format!("Unexpected text after host in {arg}.")),
+        };
+        (host, port)
+    } else {
+        match host_port.rsplit_once(':') {
+            Some((host, port)) => (host, Some(parse_port(port)?)),
+            None => (host_port, None),
+        }
+    };
+    if host.is_empty() {
+        return Err(format!("No host in {arg}."));
+    }
+    Ok(Destination {
+        user,
+        host: host.to_string(),
+        port,
+    })
+}
+
+fn parse_port(text: &str) -> Result<u16, String> {
+    text.parse::<u16>()
+        .ok()
+        .filter(|port| *port > 0)
+        .ok_or_else(|| format!("Invalid port {text}."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_profiles_hosts_and_urls() {
+        let request = parse_args(["--profile", "prod-db"]).unwrap();
+        assert_eq!(request.profile.as_deref(), Some("prod-db"));
+        assert!(request.destination.is_none());
+
+        let request = parse_args(["alice@web", "-p", "2222", "-i", "C:\\keys\\id"]).unwrap();
+        assert_eq!(
+            request.destination,
+            Some(Destination {
+                user: Some("alice".to_string()),
+                host: "web".to_string(),
+                port: None,
+            })
+        );
+        assert_eq!(request.port, Some(2222));
+        let expected = std::path::absolute("C:\\keys\\id").unwrap();
+        assert_eq!(request.identity, Some(expected.display().to_string()));
+
+        let request = parse_args(["web", "-i", "id_ed25519"]).unwrap();
+        let identity = std::path::PathBuf::from(request.identity.unwrap());
+        assert!(identity.is_absolute());
+        assert_eq!(
+            identity,
+            std::env::current_dir().unwrap().join("id_ed25519")
+        );
+
+        let request = parse_args(["ssh://bob@[2001:db8::1]:2200/"]).unwrap();
+        let destination = request.destination.unwrap();
+        assert_eq!(destination.user.as_deref(), Some("bob"));
+        assert_eq!(destination.host, "2001:db8::1");
+        assert_eq!(destination.port, Some(2200));
+
+        let request = parse_args(["--layout=work"]).unwrap();
+        assert_eq!(request.layout.as_deref(), Some("work"));
+        assert!(parse_args(Vec::<String>::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse_args(["--profile"]).is_err());
+        assert!(parse_args(["-p", "0", "web"]).is_err());
+        assert!(parse_args(["ssh://web:ssh"]).is_err());
+        assert!(parse_args(["--verbose"]).is_err());
+        assert!(parse_args(["web", "db"]).is_err());
+    }
+
+    #[test]
+    fn overrides_apply_on_top_of_profile() {
+        let mut cfg = AppConfig::default();
+        let mut profile = config::ConnectionProfile {
+            name: "prod".to_string(),
+            ..Default::default()
+        };
+        profile.settings.host = "db.internal".to_string();
+        profile.settings.username = "deploy".to_string();
+        cfg.profiles.push(profile);
+
+        let request = parse_args(["--profile", "prod", "-l", "root", "-p", "2022"]).unwrap();
+        let (settings, name) = request.connection_settings(&cfg).unwrap();
+        assert_eq!(name.as_deref(), Some("prod"));
+        assert_eq!(settings.host, "db.internal");
+        assert_eq!(settings.username, "root");
+        assert_eq!(settings.port, 2022);
+
+        let request = parse_args(["--profile", "missing"]).unwrap();
+        assert!(request.connection_settings(&cfg).is_err());
+    }
+}
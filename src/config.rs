@@ -5,7 +5,7 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use base64::Engine as _;
 use serde::{Deserialize, Serialize};
 
-use crate::model::ConnectionSettings;
+use crate::model::{ConnectionSettings, KnownHostsTarget, ProxySettings};
 use crate::{crypto, logger};
 
 const CFG_MAGIC_PREFIX: &str = "RUSTYCFG1:";
@@ -20,6 +20,10 @@ fn default_terminal_scrollback_lines() -> usize {
     5000
 }
 
+fn default_true() -> bool {
+    true
+}
+
 #[derive(Clone, Debug)]
 pub struct ConfigLoadOutcome {
     pub config: AppConfig,
@@ -237,6 +241,30 @@ pub struct AppConfig {
     pub update_available_version: Option<String>,
     #[serde(default)]
     pub update_available_url: Option<String>,
+    /// Proxy used for outbound connections unless a profile overrides it.
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    /// Where newly trusted host keys are saved.
+    #[serde(default)]
+    pub known_hosts_target: KnownHostsTarget,
+    /// Session recordings also capture keystrokes.
+    #[serde(default)]
+    pub record_input: bool,
+    /// Recorded keystrokes typed at a password prompt are masked.
+    #[serde(default = "default_true")]
+    pub redact_hidden_input: bool,
+    /// Named snapshots of tabs and splits, opened from settings or with `--layout`.
+    #[serde(default)]
+    pub saved_layouts: Vec<SavedLayout>,
+    /// Command for new local shell tabs; empty runs the system's default shell.
+    #[serde(default)]
+    pub local_shell_command: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SavedLayout {
+    pub name: String,
+    pub layout_json: String,
 }
 
 impl Default for AppConfig {
@@ -263,6 +291,12 @@ impl Default for AppConfig {
             transfer_history: Vec::new(),
             update_available_version: None,
             update_available_url: None,
+            proxy: ProxySettings::default(),
+            known_hosts_target: KnownHostsTarget::default(),
+            record_input: false,
+            redact_hidden_input: true,
+            saved_layouts: Vec::new(),
+            local_shell_command: String::new(),
         }
     }
 }
@@ -311,6 +345,14 @@ pub fn config_path() -> PathBuf {
     PathBuf::from("config.json")
 }
 
+/// Port and token of the running instance, read by later launches to hand over their request.
+pub fn instance_path() -> PathBuf {
+    if let Some(dir) = config_dir() {
+        return dir.join("instance");
+    }
+    PathBuf::from("instance")
+}
+
 fn corrupt_backup_path(path: &Path) -> PathBuf {
     let stem = path
         .file_stem()
@@ -429,17 +471,20 @@ fn sanitized_for_plaintext_fallback(cfg: &AppConfig) -> AppConfig {
     for profile in &mut sanitized.profiles {
         profile.remember_password = false;
         profile.remember_key_passphrase = false;
-        profile.settings.password.clear();
-        profile.settings.key_passphrase.clear();
+        profile.settings.clear_passwords();
+        profile.settings.clear_key_passphrases();
     }
 
     for transfer in &mut sanitized.transfer_history {
-        transfer.settings.password.clear();
-        transfer.settings.key_passphrase.clear();
+        transfer.settings.clear_passwords();
+        transfer.settings.clear_key_passphrases();
     }
 
+    sanitized.proxy.password.clear();
+
     // Session layout snapshots may embed serialized connection settings with secrets.
     sanitized.saved_session_layout_json = None;
+    sanitized.saved_layouts.clear();
     sanitized
 }
 
@@ -735,10 +780,10 @@ pub fn sanitized_profile_name(name: &str) -> String {
 pub fn write_profile_settings(profile: &ConnectionProfile) -> ConnectionSettings {
     let mut s = profile.settings.clone();
     if !profile.remember_password {
-        s.password.clear();
+        s.clear_passwords();
     }
     if !profile.remember_key_passphrase {
-        s.key_passphrase.clear();
+        s.clear_key_passphrases();
     }
     s
 }
@@ -751,10 +796,10 @@ pub fn read_profile_from_settings(
 ) -> ConnectionProfile {
     let mut s = settings.clone();
     if !remember_password {
-        s.password.clear();
+        s.clear_passwords();
     }
     if !remember_key_passphrase {
-        s.key_passphrase.clear();
+        s.clear_key_passphrases();
     }
     ConnectionProfile {
         name,
@@ -774,6 +819,15 @@ mod tests {
     fn plaintext_fallback_strips_secrets() {
         let mut cfg = AppConfig {
             saved_session_layout_json: Some("{\"embedded\":\"secret\"}".to_string()),
+            saved_layouts: vec![SavedLayout {
+                name: "work".to_string(),
+                layout_json: "{\"embedded\":\"secret\"}".to_string(),
+            }],
+            proxy: ProxySettings {
+                host: "proxy.example.com".to_string(),
+                password: "proxy-pw".to_string(),
+                ..ProxySettings::default()
+            },
             ..AppConfig::default()
         };
         cfg.profiles.push(ConnectionProfile {
@@ -785,6 +839,22 @@ mod tests {
                 password: "pw".to_string(),
                 private_key_path: "id_ed25519".to_string(),
                 key_passphrase: "keypw".to_string(),
+                identities: vec![crate::model::IdentityFile {
+                    private_key_path: "id_deploy".to_string(),
+                    key_passphrase: "deploy-keypw".to_string(),
+                }],
+                jump_hosts: vec![crate::model::JumpHostSettings {
+                    host: "bastion.example.com".to_string(),
+                    password: "hop-pw".to_string(),
+                    key_passphrase: "hop-keypw".to_string(),
+                    ..Default::default()
+                }],
+                proxy_override: Some(ProxySettings {
+                    host: "profile-proxy.example.com".to_string(),
+                    password: "profile-proxy-pw".to_string(),
+                    ..ProxySettings::default()
+                }),
+                ..ConnectionSettings::default()
             },
             remember_password: true,
             remember_key_passphrase: true,
@@ -799,6 +869,7 @@ mod tests {
                 password: "pw".to_string(),
                 private_key_path: "id_ed25519".to_string(),
                 key_passphrase: "keypw".to_string(),
+                ..ConnectionSettings::default()
             },
             remote_path: "/tmp/file".to_string(),
             local_path: "file".to_string(),
@@ -814,9 +885,28 @@ mod tests {
         assert_eq!(sanitized.profiles[0].settings.key_passphrase, "");
         assert!(!sanitized.profiles[0].remember_password);
         assert!(!sanitized.profiles[0].remember_key_passphrase);
+        assert_eq!(
+            sanitized.profiles[0].settings.identities[0].key_passphrase,
+            ""
+        );
+        assert_eq!(sanitized.profiles[0].settings.jump_hosts[0].password, "");
+        assert_eq!(
+            sanitized.profiles[0].settings.jump_hosts[0].key_passphrase,
+            ""
+        );
+        assert_eq!(
+            sanitized.profiles[0]
+                .settings
+                .proxy_override
+                .as_ref()
+                .map(|proxy| proxy.password.as_str()),
+            Some("")
+        );
+        assert_eq!(sanitized.proxy.password, "");
         assert_eq!(sanitized.transfer_history[0].settings.password, "");
         assert_eq!(sanitized.transfer_history[0].settings.key_passphrase, "");
         assert!(sanitized.saved_session_layout_json.is_none());
+        assert!(sanitized.saved_layouts.is_empty());
     }
 
     #[test]
@@ -895,6 +985,193 @@ mod tests {
         assert!(cfg.transfer_history.is_empty());
     }
 
+    #[test]
+    fn legacy_profiles_get_default_timeouts_and_keepalives() {
+        let json = br#"{
+            "profiles": [
+                {
+                    "name": "prod",
+                    "settings": { "host": "example.com", "port": 22, "username": "alice" }
+                }
+            ]
+        }"#;
+
+        let cfg: AppConfig = serde_json::from_slice(json).unwrap();
+        let settings = &cfg.profiles[0].settings;
+
+        assert_eq!(settings.connect_timeout_secs, 15);
+        assert_eq!(settings.keepalive_interval_secs, 20);
+        assert_eq!(settings.keepalive_max_missed, 3);
+    }
+
+    fn identity(path: &str, passphrase: &str) -> crate::model::IdentityFile {
+        crate::model::IdentityFile {
+            private_key_path: path.to_string(),
+            key_passphrase: passphrase.to_string(),
+        }
+    }
+
+    #[test]
+    fn identity_files_lists_primary_key_first_and_skips_blank_paths() {
+        let mut settings = ConnectionSettings {
+            private_key_path: "C:\\keys\\main".to_string(),
+            key_passphrase: "main-pw".to_string(),
+            identities: vec![
+                identity("C:\\keys\\work", "work-pw"),
+                identity("   ", "ignored"),
+                identity("C:\\keys\\old", ""),
+            ],
+            ..ConnectionSettings::default()
+        };
+
+        assert_eq!(
+            settings.identity_files(),
+            vec![
+                identity("C:\\keys\\main", "main-pw"),
+                identity("C:\\keys\\work", "work-pw"),
+                identity("C:\\keys\\old", ""),
+            ]
+        );
+
+        settings.private_key_path = " ".to_string();
+        assert_eq!(
+            settings.identity_files(),
+            vec![
+                identity("C:\\keys\\work", "work-pw"),
+                identity("C:\\keys\\old", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn set_key_passphrase_updates_the_matching_identity() {
+        let mut settings = ConnectionSettings {
+            private_key_path: "C:\\keys\\main".to_string(),
+            identities: vec![
+                identity("C:\\keys\\work", ""),
+                identity("C:\\keys\\main", ""),
+            ],
+            ..ConnectionSettings::default()
+        };
+
+        settings.set_key_passphrase(Some(" C:\\keys\\work "), "work-pw".to_string());
+        assert_eq!(settings.identities[0].key_passphrase, "work-pw");
+        assert!(settings.key_passphrase.is_empty());
+
+        // A key listed both as primary and as an extra identity belongs to the primary slot.
+        settings.set_key_passphrase(Some("C:\\keys\\main"), "main-pw".to_string());
+        assert_eq!(settings.key_passphrase, "main-pw");
+        assert!(settings.identities[1].key_passphrase.is_empty());
+
+        settings.set_key_passphrase(None, "fallback-pw".to_string());
+        assert_eq!(settings.key_passphrase, "fallback-pw");
+
+        // An unrelated key never overwrites the primary passphrase.
+        settings.set_key_passphrase(Some("C:\\keys\\unknown"), "other-pw".to_string());
+        assert_eq!(settings.key_passphrase, "fallback-pw");
+        assert_eq!(settings.identities[0].key_passphrase, "work-pw");
+        assert!(settings.identities[1].key_passphrase.is_empty());
+    }
+
+    #[test]
+    fn set_key_passphrase_routes_jump_host_keys_to_their_hops() {
+        use crate::model::JumpHostSettings;
+
+        let hop = |host: &str, key: &str| JumpHostSettings {
+            host: host.to_string(),
+            private_key_path: key.to_string(),
+            ..JumpHostSettings::default()
+        };
+        let mut settings = ConnectionSettings {
+            private_key_path: "C:\\keys\\main".to_string(),
+            key_passphrase: "main-pw".to_string(),
+            jump_hosts: vec![
+                hop("bastion", "C:\\keys\\hop"),
+                hop("inner", "C:\\keys\\other"),
+                hop("edge", "C:\\keys\\hop"),
+            ],
+            ..ConnectionSettings::default()
+        };
+
+        settings.set_key_passphrase(Some("C:\\keys\\hop"), "hop-pw".to_string());
+        assert_eq!(settings.key_passphrase, "main-pw");
+        assert_eq!(settings.jump_hosts[0].key_passphrase, "hop-pw");
+        assert!(settings.jump_hosts[1].key_passphrase.is_empty());
+        assert_eq!(settings.jump_hosts[2].key_passphrase, "hop-pw");
+        assert_eq!(settings.jump_hop_settings()[0].key_passphrase, "hop-pw");
+
+        // A hop sharing the target's key is remembered for both.
+        settings.jump_hosts[1].private_key_path = "C:\\keys\\main".to_string();
+        settings.set_key_passphrase(Some("C:\\keys\\main"), "new-main-pw".to_string());
+        assert_eq!(settings.key_passphrase, "new-main-pw");
+        assert_eq!(settings.jump_hosts[1].key_passphrase, "new-main-pw");
+        assert_eq!(settings.jump_hosts[0].key_passphrase, "hop-pw");
+    }
+
+    #[test]
+    fn jump_hops_inherit_only_the_username_and_skip_blank_hosts() {
+        use crate::model::{JumpHostSettings, ProxyKind};
+
+        let target = ConnectionSettings {
+            host: "db.internal".to_string(),
+            port: 2222,
+            username: "alice".to_string(),
+            private_key_path: "C:\\keys\\target".to_string(),
+            key_passphrase: "target-pw".to_string(),
+            proxy_command: "connect -S socks:1080 %h %p".to_string(),
+            proxy_override: Some(ProxySettings {
+                kind: ProxyKind::Socks5,
+                host: "socks.example.com".to_string(),
+                port: 1080,
+                ..ProxySettings::default()
+            }),
+            jump_hosts: vec![
+                JumpHostSettings {
+                    host: "bastion.example.com".to_string(),
+                    ..JumpHostSettings::default()
+                },
+                JumpHostSettings {
+                    host: "  ".to_string(),
+                    username: "ghost".to_string(),
+                    ..JumpHostSettings::default()
+                },
+                JumpHostSettings {
+                    host: "10.0.0.5".to_string(),
+                    port: 2022,
+                    username: "ops".to_string(),
+                    password: "hop-pw".to_string(),
+                    private_key_path: "C:\\keys\\hop".to_string(),
+                    key_passphrase: "hop-key-pw".to_string(),
+                },
+            ],
+            ..ConnectionSettings::default()
+        };
+
+        let hops = target.jump_hop_settings();
+        assert_eq!(hops.len(), 2);
+
+        // A bare hop borrows the target's user but nothing else.
+        assert_eq!(hops[0].host, "bastion.example.com");
+        assert_eq!(hops[0].port, 22);
+        assert_eq!(hops[0].username, "alice");
+        assert!(hops[0].private_key_path.is_empty());
+        assert!(hops[0].key_passphrase.is_empty());
+
+        assert_eq!(hops[1].host, "10.0.0.5");
+        assert_eq!(hops[1].port, 2022);
+        assert_eq!(hops[1].username, "ops");
+        assert_eq!(hops[1].password, "hop-pw");
+        assert_eq!(hops[1].private_key_path, "C:\\keys\\hop");
+        assert_eq!(hops[1].key_passphrase, "hop-key-pw");
+
+        // The proxy only carries the first connection, so hops never repeat it.
+        for hop in &hops {
+            assert!(hop.proxy_command.is_empty());
+            assert!(hop.proxy_override.is_none());
+            assert!(hop.jump_hosts.is_empty());
+        }
+    }
+
     #[test]
     fn legacy_transfer_history_entries_missing_new_fields_still_load() {
         let json = br#"{
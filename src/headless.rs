@@ -0,0 +1,381 @@
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::config;
+use crate::model::ConnectionProtocol;
+use crate::ssh::{
+    self, DetachedTransferRequest, DownloadManagerEvent, IssueKind, UploadConflictChoice,
+    UploadConflictResponse,
+};
+
+const EXIT_CANCELED: i32 = 8;
+/// Progress lines when stdout is not a terminal, so logs stay readable.
+const PIPED_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Get,
+    Put,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct TransferArgs {
+    direction: Direction,
+    profile: String,
+    remote_path: String,
+    local_path: String,
+    on_conflict: UploadConflictChoice,
+}
+
+/// Runs `rusty get` / `rusty put` without opening a window. Returns `None` when the arguments
+/// are not a transfer command, otherwise the process exit code.
+pub fn run_from_args(args: &[String]) -> Option<i32> {
+    let direction = match args.first().map(String::as_str) {
+        Some("get") => Direction::Get,
+        Some("put") => Direction::Put,
+        _ => return None,
+    };
+    attach_parent_console();
+    let transfer = match parse_transfer_args(direction, &args[1..]) {
+        Ok(transfer) => transfer,
+        Err(err) => {
+            eprintln!("rusty: {err}");
+            eprintln!(
+                "usage: rusty get [--on-conflict overwrite|skip|cancel] <profile>:<remote> <local>"
+            );
+            eprintln!(
+                "       rusty put [--on-conflict overwrite|skip|cancel] <local> <profile>:<remote>"
+            );
+            return Some(exit_code(IssueKind::Configuration));
+        }
+    };
+    Some(run_transfer(transfer))
+}
+
+/// Exit status for a transfer that ended with an issue of `kind`; `Info` means success.
+fn exit_code(kind: IssueKind) -> i32 {
+    match kind {
+        IssueKind::Info => 0,
+        IssueKind::Unknown => 1,
+        IssueKind::Configuration => 2,
+        IssueKind::Authentication => 3,
+        IssueKind::HostKey => 4,
+        IssueKind::Permission => 5,
+        IssueKind::Path => 6,
+        IssueKind::Transport => 7,
+    }
+}
+
+fn parse_transfer_args(direction: Direction, args: &[String]) -> Result<TransferArgs, String> {
+    let mut on_conflict = UploadConflictChoice::CancelTransfer;
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        match flag {
+            "--on-conflict" => {
+                let value = inline
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| "--on-conflict needs a value.".to_string())?;
+                on_conflict = match value.as_str() {
+                    "overwrite" => UploadConflictChoice::OverwriteIfDifferent,
+                    "skip" => UploadConflictChoice::Skip,
+                    "cancel" => UploadConflictChoice::CancelTransfer,
+                    other => return Err(format!("Unknown --on-conflict value {other}.")),
+                };
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {arg}.")),
+            _ => positional.push(arg.clone()),
+        }
+    }
+    let [first, second] = positional.as_slice() else {
+        return Err("Expected a source and a destination.".to_string());
+    };
+    let (remote, local) = match direction {
+        Direction::Get => (first, second),
+        Direction::Put => (second, first),
+    };
+    let (profile, remote_path) = remote
+        .split_once(':')
+        .filter(|(profile, path)| !profile.trim().is_empty() && !path.trim().is_empty())
+        .ok_or_else(|| format!("Remote path {remote} must look like <profile>:<path>."))?;
+    Ok(TransferArgs {
+        direction,
+        profile: profile.trim().to_string(),
+        remote_path: remote_path.to_string(),
+        local_path: local.clone(),
+        on_conflict,
+    })
+}
+
+fn run_transfer(transfer: TransferArgs) -> i32 {
+    let outcome = config::load();
+    let cfg = outcome.config;
+    if let Some(notice) = outcome.notice {
+        eprintln!("rusty: {notice}");
+    }
+    let Some(profile) =
+        config::find_profile_index(&cfg, &transfer.profile).and_then(|i| cfg.profiles.get(i))
+    else {
+        eprintln!("rusty: No profile named \"{}\".", transfer.profile);
+        return exit_code(IssueKind::Configuration);
+    };
+    if profile.settings.protocol != ConnectionProtocol::Ssh {
+        eprintln!(
+            "rusty: Profile \"{}\" uses {}; transfers need SSH.",
+            transfer.profile,
+            profile.settings.protocol.label()
+        );
+        return exit_code(IssueKind::Configuration);
+    }
+    ssh::set_default_proxy(cfg.proxy.clone());
+    ssh::set_known_hosts_target(cfg.known_hosts_target);
+
+    // Like scp, a download into an existing folder keeps the remote file name.
+    let mut local_path = transfer.local_path.clone();
+    if transfer.direction == Direction::Get && Path::new(&local_path).is_dir() {
+        if let Some(name) = transfer
+            .remote_path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+        {
+            local_path = Path::new(&local_path).join(name).display().to_string();
+        }
+    }
+
+    let (event_tx, event_rx) = mpsc::channel::<DownloadManagerEvent>();
+    let (_cancel_tx, cancel_rx) = mpsc::channel::<()>();
+    let (conflict_tx, conflict_response_rx) = mpsc::channel::<UploadConflictResponse>();
+    let request = DetachedTransferRequest {
+        settings: config::write_profile_settings(profile),
+        request_id: 1,
+        remote_path: transfer.remote_path.clone(),
+        local_path,
+        event_tx,
+        cancel_rx,
+        conflict_response_rx,
+        log_path: match transfer.direction {
+            Direction::Get => "logs\\cli-download.log".to_string(),
+            Direction::Put => "logs\\cli-upload.log".to_string(),
+        },
+    };
+    // Re-running an interrupted command picks up from the temp file, like Retry in the GUI.
+    let _handle = match transfer.direction {
+        Direction::Get => ssh::start_sftp_download_detached(request, true),
+        Direction::Put => ssh::start_sftp_upload_detached(request, true),
+    };
+
+    let mut progress = ProgressLine::new();
+    for event in event_rx {
+        match event {
+            DownloadManagerEvent::Preparing { message, .. } => progress.message(&message),
+            DownloadManagerEvent::UploadConflictPrompt { prompt } => {
+                let verb = match transfer.on_conflict {
+                    UploadConflictChoice::OverwriteIfDifferent => "overwriting if different",
+                    UploadConflictChoice::Skip => "skipping",
+                    UploadConflictChoice::CancelTransfer => {
+                        "canceling (pass --on-conflict overwrite or skip to continue)"
+                    }
+                };
+                progress.message(&format!("{} already exists; {verb}.", prompt.remote_path));
+                let _ = conflict_tx.send(UploadConflictResponse {
+                    choice: transfer.on_conflict,
+                    apply_to_all: true,
+                });
+            }
+            DownloadManagerEvent::Started {
+                remote_path,
+                local_path,
+                downloaded_bytes,
+                total_bytes,
+                ..
+            } => {
+                let (from, to) = match transfer.direction {
+                    Direction::Get => (remote_path, local_path),
+                    Direction::Put => (local_path, remote_path),
+                };
+                progress.message(&format!("{from} -> {to}"));
+                progress.update(downloaded_bytes, total_bytes, 0.0);
+            }
+            DownloadManagerEvent::Progress {
+                downloaded_bytes,
+                total_bytes,
+                speed_bps,
+                ..
+            } => progress.update(downloaded_bytes, total_bytes, speed_bps),
+            DownloadManagerEvent::Finished {
+                local_path,
+                message,
+                ..
+            } => {
+                progress.message(&message.unwrap_or_else(|| format!("Done: {local_path}")));
+                return exit_code(IssueKind::Info);
+            }
+            DownloadManagerEvent::Paused { issue, .. }
+            | DownloadManagerEvent::Failed { issue, .. } => {
+                progress.finish();
+                eprintln!("rusty: {}", issue.message);
+                return exit_code(issue.kind).max(1);
+            }
+            DownloadManagerEvent::Canceled { .. } => {
+                progress.finish();
+                eprintln!("rusty: Transfer canceled.");
+                return EXIT_CANCELED;
+            }
+        }
+    }
+    progress.finish();
+    eprintln!("rusty: Transfer stopped without a result.");
+    exit_code(IssueKind::Unknown)
+}
+
+/// Progress on stdout: rewritten in place on a terminal, a line every few seconds otherwise.
+struct ProgressLine {
+    interactive: bool,
+    dirty: bool,
+    last_print: Option<Instant>,
+}
+
+impl ProgressLine {
+    fn new() -> Self {
+        Self {
+            interactive: std::io::stdout().is_terminal(),
+            dirty: false,
+            last_print: None,
+        }
+    }
+
+    fn message(&mut self, text: &str) {
+        self.finish();
+        println!("{text}");
+    }
+
+    fn update(&mut self, done: u64, total: Option<u64>, speed_bps: f64) {
+        if !self.interactive
+            && self
+                .last_print
+                .is_some_and(|last| last.elapsed() < PIPED_PROGRESS_INTERVAL)
+        {
+            return;
+        }
+        self.last_print = Some(Instant::now());
+        let mut line = match total.filter(|total| *total > 0) {
+            Some(total) => format!(
+                "{:>5.1}%  {} / {}",
+                done as f64 * 100.0 / total as f64,
+                human_bytes(done),
+                human_bytes(total)
+            ),
+            None => human_bytes(done),
+        };
+        if speed_bps > 0.0 {
+            line.push_str(&format!("  {}/s", human_bytes(speed_bps as u64)));
+        }
+        let mut stdout = std::io::stdout().lock();
+        if self.interactive {
+            let _ = write!(stdout, "\r{line:<48}");
+            self.dirty = true;
+        } else {
+            let _ = writeln!(stdout, "{line}");
+        }
+        let _ = stdout.flush();
+    }
+
+    fn finish(&mut self) {
+        if self.dirty {
+            println!();
+            self.dirty = false;
+        }
+    }
+}
+
+fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// The release build is a GUI-subsystem binary with no console of its own; borrow the one
+/// the command was typed into so stdout and stderr reach it.
+#[cfg(target_os = "windows")]
+fn attach_parent_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // SAFETY: AttachConsole has no preconditions; failure just leaves output detached.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_parent_console() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_get_and_put() {
+        let get =
+            parse_transfer_args(Direction::Get, &args(&["prod:/var/log/app.log", "."])).unwrap();
+        assert_eq!(get.profile, "prod");
+        assert_eq!(get.remote_path, "/var/log/app.log");
+        assert_eq!(get.local_path, ".");
+        assert_eq!(get.on_conflict, UploadConflictChoice::CancelTransfer);
+
+        let put = parse_transfer_args(
+            Direction::Put,
+            &args(&["--on-conflict=skip", "C:\\build\\app.zip", "prod:releases/"]),
+        )
+        .unwrap();
+        assert_eq!(put.local_path, "C:\\build\\app.zip");
+        assert_eq!(put.remote_path, "releases/");
+        assert_eq!(put.on_conflict, UploadConflictChoice::Skip);
+
+        assert!(parse_transfer_args(Direction::Get, &args(&["/tmp/x", "."])).is_err());
+        assert!(parse_transfer_args(Direction::Get, &args(&["prod:/x"])).is_err());
+        assert!(
+            parse_transfer_args(Direction::Put, &args(&["--on-conflict", "ask", "a", "p:b"]))
+                .is_err()
+        );
+        assert_eq!(run_from_args(&args(&["alice@web"])), None);
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let kinds = [
+            IssueKind::Unknown,
+            IssueKind::Configuration,
+            IssueKind::Authentication,
+            IssueKind::HostKey,
+            IssueKind::Permission,
+            IssueKind::Path,
+            IssueKind::Transport,
+        ];
+        let mut codes: Vec<i32> = kinds.iter().map(|kind| exit_code(*kind)).collect();
+        codes.push(EXIT_CANCELED);
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), kinds.len() + 1);
+        assert!(!codes.contains(&exit_code(IssueKind::Info)));
+    }
+}
@@ -0,0 +1,376 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use russh::keys::{self, PublicKey};
+use sha1::Sha1;
+
+use crate::ssh_config::wildcard_match;
+
+/// Line markers from sshd(8) "SSH_KNOWN_HOSTS FILE FORMAT".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Marker {
+    /// Host certificate signer. russh neither offers the `*-cert-v01@openssh.com` host key
+    /// algorithms nor decodes a certificate in the key exchange, so certificates can't be
+    /// validated; these lines are only recognised so the CA key never passes as a pinned key.
+    CertAuthority,
+    Revoked,
+}
+
+/// One key line of a known_hosts file. `key` is `None` for key types russh cannot parse;
+/// such lines never match but are kept so line numbers stay meaningful.
+#[derive(Clone, Debug)]
+pub struct KnownHostEntry {
+    pub line: usize,
+    pub marker: Option<Marker>,
+    pub hosts: String,
+    pub algorithm: String,
+    pub key: Option<PublicKey>,
+}
+
+impl KnownHostEntry {
+    pub fn matches_host(&self, host: &str, port: u16) -> bool {
+        host_patterns_match(&self.hosts, host, port)
+    }
+
+    pub fn fingerprint(&self) -> Option<String> {
+        self.key
+            .as_ref()
+            .map(|key| key.fingerprint(keys::HashAlg::Sha256).to_string())
+    }
+
+    /// The host field with hashed names collapsed, for display and search.
+    pub fn host_label(&self) -> String {
+        let mut hashed = 0;
+        let mut names: Vec<&str> = Vec::new();
+        for pattern in self.hosts.split(',').filter(|p| !p.is_empty()) {
+            if pattern.starts_with("|1|") {
+                hashed += 1;
+            } else {
+                names.push(pattern);
+            }
+        }
+        match hashed {
+            0 => names.join(", "),
+            1 if names.is_empty() => "(hashed)".to_string(),
+            n if names.is_empty() => format!("({n} hashed)"),
+            n => format!("{} (+{n} hashed)", names.join(", ")),
+        }
+    }
+
+    /// The port of the first plain pattern; hashed and wildcard-only lines report 22.
+    pub fn port(&self) -> u16 {
+        self.plain_names().next().map_or(22, |(_, port)| port)
+    }
+
+    /// A concrete host to contact for this line. Hashed lines only reveal it when `query`
+    /// (a `host` or `[host]:port` the user typed) matches the hash.
+    pub fn probe_target(&self, query: &str) -> Option<(String, u16)> {
+        if let Some((host, port)) = self.plain_names().next() {
+            return Some((host.to_string(), port));
+        }
+        let (host, port) = split_host_pattern(query.trim());
+        (!host.is_empty() && self.matches_host(host, port)).then(|| (host.to_string(), port))
+    }
+
+    fn plain_names(&self) -> impl Iterator<Item = (&str, u16)> {
+        self.hosts
+            .split(',')
+            .filter(|p| !p.is_empty() && !p.starts_with(['!', '|']) && !p.contains(['*', '?']))
+            .map(split_host_pattern)
+    }
+}
+
+/// Outcome of looking a server key up in every known_hosts file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HostKeyVerdict {
+    Trusted {
+        path: PathBuf,
+        line: usize,
+    },
+    Revoked {
+        path: PathBuf,
+        line: usize,
+    },
+    /// A different key of the same algorithm is pinned for this host.
+    Changed {
+        path: PathBuf,
+        line: usize,
+    },
+    /// Nothing pinned; `cert_authorities` counts `@cert-authority` lines covering the host,
+    /// which the prompt mentions since they can't vouch for the key.
+    Unknown {
+        cert_authorities: usize,
+    },
+}
+
+pub fn parse(text: &str) -> Vec<KnownHostEntry> {
+    let mut entries = Vec::new();
+    for (idx, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let mut first = fields.next().unwrap_or_default();
+        let marker = match first {
+            "@cert-authority" => Some(Marker::CertAuthority),
+            "@revoked" => Some(Marker::Revoked),
+            _ => None,
+        };
+        if marker.is_some() {
+            first = fields.next().unwrap_or_default();
+        }
+        let (Some(algorithm), Some(blob)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if first.is_empty() {
+            continue;
+        }
+        entries.push(KnownHostEntry {
+            line: idx + 1,
+            marker,
+            hosts: first.to_string(),
+            algorithm: algorithm.to_string(),
+            key: keys::parse_public_key_base64(blob).ok(),
+        });
+    }
+    entries
+}
+
+/// Reads `path`; a missing file is treated as empty.
+pub fn read_file(path: &Path) -> std::io::Result<Vec<KnownHostEntry>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(parse(&text)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// The name OpenSSH records for a host: bare on port 22, `[host]:port` otherwise.
+pub fn host_port_name(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{host}]:{port}")
+    }
+}
+
+/// Splits `[host]:port` into its parts; anything else is a bare host on port 22.
+pub fn split_host_pattern(pattern: &str) -> (&str, u16) {
+    pattern
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("]:"))
+        .and_then(|(host, port)| Some((host, port.parse().ok()?)))
+        .unwrap_or((pattern, 22))
+}
+
+/// Matches a comma-separated host field, honouring `|1|` hashes, wildcards and `!` negation.
+pub fn host_patterns_match(patterns: &str, host: &str, port: u16) -> bool {
+    let name = host_port_name(host, port);
+    let mut matched = false;
+    for pattern in patterns.split(',').filter(|p| !p.is_empty()) {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if single_pattern_matches(negated, &name) {
+                return false;
+            }
+        } else if single_pattern_matches(pattern, &name) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+fn single_pattern_matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_prefix("|1|") {
+        Some(hashed) => hashed_name_matches(hashed, name),
+        None => wildcard_match(pattern, name),
+    }
+}
+
+fn hashed_name_matches(hashed: &str, name: &str) -> bool {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let Some((salt, hash)) = hashed.split_once('|') else {
+        return false;
+    };
+    let (Ok(salt), Ok(hash)) = (engine.decode(salt), engine.decode(hash)) else {
+        return false;
+    };
+    let Ok(mac) = Hmac::<Sha1>::new_from_slice(&salt) else {
+        return false;
+    };
+    mac.chain_update(name.as_bytes())
+        .verify_slice(&hash)
+        .is_ok()
+}
+
+/// Looks `key` up across `paths` in order. A revoked match wins over everything, then a
+/// pinned match, then a conflicting pin of the same algorithm.
+pub fn check(
+    paths: &[PathBuf],
+    host: &str,
+    port: u16,
+    key: &PublicKey,
+) -> std::io::Result<HostKeyVerdict> {
+    let mut trusted = None;
+    let mut changed = None;
+    let mut cert_authorities = 0;
+    for path in paths {
+        for entry in read_file(path)? {
+            let Some(recorded) = entry.key.as_ref() else {
+                continue;
+            };
+            if !entry.matches_host(host, port) {
+                continue;
+            }
+            let location = (path.clone(), entry.line);
+            match entry.marker {
+                Some(Marker::Revoked) if recorded == key => {
+                    let (path, line) = location;
+                    return Ok(HostKeyVerdict::Revoked { path, line });
+                }
+                Some(Marker::Revoked) => {}
+                Some(Marker::CertAuthority) => cert_authorities += 1,
+                None if recorded == key => {
+                    trusted.get_or_insert(location);
+                }
+                None if recorded.algorithm() == key.algorithm() => {
+                    changed.get_or_insert(location);
+                }
+                None => {}
+            }
+        }
+    }
+    Ok(match (trusted, changed) {
+        (Some((path, line)), _) => HostKeyVerdict::Trusted { path, line },
+        (None, Some((path, line))) => HostKeyVerdict::Changed { path, line },
+        (None, None) => HostKeyVerdict::Unknown { cert_authorities },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const KEY_A: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIJdD7y3aLq454yWBdwLWbieU1ebz9/cu7/QEXn9OIeZJ";
+    const KEY_B: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIA6rWI3G1sz07DnfFlrouTcysQlj2P+jpNSOEWD9OJ3X";
+
+    fn hashed(name: &str, salt: &[u8]) -> String {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let mac = Hmac::<Sha1>::new_from_slice(salt)
+            .unwrap()
+            .chain_update(name.as_bytes())
+            .finalize()
+            .into_bytes();
+        format!("|1|{}|{}", engine.encode(salt), engine.encode(mac))
+    }
+
+    fn temp_file(tag: &str, contents: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!(
+            "rusty-known-hosts-{tag}-{}-{nanos}",
+            std::process::id()
+        ));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn host_patterns_cover_hashes_wildcards_ports_and_negation() {
+        assert!(host_patterns_match("web,db", "db", 22));
+        assert!(!host_patterns_match("db", "db", 2222));
+        assert!(host_patterns_match("[db]:2222", "db", 2222));
+        assert!(host_patterns_match(
+            "*.example.com,!bad.example.com",
+            "a.example.com",
+            22
+        ));
+        assert!(!host_patterns_match(
+            "*.example.com,!bad.example.com",
+            "bad.example.com",
+            22
+        ));
+        assert!(host_patterns_match(
+            &hashed("[db]:2222", b"salty"),
+            "db",
+            2222
+        ));
+        assert!(!host_patterns_match(&hashed("db", b"salty"), "web", 22));
+        assert_eq!(split_host_pattern("[db]:2222"), ("db", 2222));
+        assert_eq!(split_host_pattern("db"), ("db", 22));
+    }
+
+    #[test]
+    fn check_reports_markers_pins_and_conflicts_across_files() {
+        let openssh = temp_file(
+            "openssh",
+            &format!(
+                "# comment\n@cert-authority *.example.com ssh-ed25519 {KEY_B}\n{} ssh-ed25519 {KEY_A}\nold ssh-ed25519 {KEY_B}\n",
+                hashed("pinned", b"0123456789")
+            ),
+        );
+        let rusty = temp_file(
+            "rusty",
+            &format!("@revoked\tgone  ssh-ed25519 {KEY_A}\nweird ssh-unknown AAAA\n"),
+        );
+        let paths = vec![openssh.clone(), rusty.clone()];
+        let key_a = keys::parse_public_key_base64(KEY_A).unwrap();
+
+        assert_eq!(
+            check(&paths, "pinned", 22, &key_a).unwrap(),
+            HostKeyVerdict::Trusted {
+                path: openssh.clone(),
+                line: 3
+            }
+        );
+        assert_eq!(
+            check(&paths, "old", 22, &key_a).unwrap(),
+            HostKeyVerdict::Changed {
+                path: openssh.clone(),
+                line: 4
+            }
+        );
+        assert_eq!(
+            check(&paths, "gone", 22, &key_a).unwrap(),
+            HostKeyVerdict::Revoked {
+                path: rusty.clone(),
+                line: 1
+            }
+        );
+        assert_eq!(
+            check(&paths, "web.example.com", 22, &key_a).unwrap(),
+            HostKeyVerdict::Unknown {
+                cert_authorities: 1
+            }
+        );
+        // A server presenting the CA key itself is not trusted by the @cert-authority line.
+        let key_b = keys::parse_public_key_base64(KEY_B).unwrap();
+        assert_eq!(
+            check(&paths, "web.example.com", 22, &key_b).unwrap(),
+            HostKeyVerdict::Unknown {
+                cert_authorities: 1
+            }
+        );
+        let entries = read_file(&openssh).unwrap();
+        assert_eq!(entries[1].host_label(), "(hashed)");
+        assert_eq!(entries[1].probe_target("web"), None);
+        assert_eq!(
+            entries[1].probe_target("pinned"),
+            Some(("pinned".to_string(), 22))
+        );
+        assert_eq!(entries[2].probe_target(""), Some(("old".to_string(), 22)));
+        assert_eq!(
+            entries[1].fingerprint(),
+            Some(key_a.fingerprint(keys::HashAlg::Sha256).to_string())
+        );
+        assert_eq!(read_file(&rusty).unwrap().len(), 2);
+
+        let _ = fs::remove_file(openssh);
+        let _ = fs::remove_file(rusty);
+    }
+}
@@ -0,0 +1,632 @@
+use std::io::Read;
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread;
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+use crate::logger;
+use crate::ssh::{self, CsiQueryScanner, IssueKind, UiMessage, WorkerMessage};
+use crate::terminal_emulator::Parser;
+
+enum ShellEvent {
+    Output(Vec<u8>),
+    /// The shell process ended, with its exit code when the OS reported one.
+    Exited(Option<i64>),
+}
+
+/// What a local shell pane runs when no command is configured.
+pub fn default_shell_command() -> String {
+    #[cfg(windows)]
+    let (var, fallback) = ("COMSPEC", "cmd.exe");
+    #[cfg(not(windows))]
+    let (var, fallback) = ("SHELL", "/bin/sh");
+    std::env::var(var)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+/// Program name for tab titles, e.g. `powershell` for `"C:\...\powershell.exe" -NoLogo`.
+pub fn shell_display_name(command: &str) -> String {
+    let command = command.trim();
+    let program = match command.strip_prefix('"') {
+        Some(rest) => rest.split('"').next().unwrap_or(rest),
+        None => command.split_whitespace().next().unwrap_or(command),
+    };
+    let name = program.rsplit(['\\', '/']).next().unwrap_or(program);
+    match name.len().checked_sub(4) {
+        Some(stem) if name.is_char_boundary(stem) && name[stem..].eq_ignore_ascii_case(".exe") => {
+            name[..stem].to_string()
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Runs `command` (or the default shell) behind a pseudo console and speaks the same
+/// [`WorkerMessage`]/[`UiMessage`] protocol as an SSH terminal, so the pane renders and takes
+/// input exactly like one.
+pub fn start_local_shell(
+    client_id: u64,
+    command: String,
+    scrollback_len: usize,
+    ui_tx: Sender<UiMessage>,
+    worker_rx: Receiver<WorkerMessage>,
+    log_path: String,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        logger::log_line(&log_path, "Starting local shell worker.");
+
+        let result = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt.block_on(run_local_shell_async(
+                client_id,
+                &command,
+                scrollback_len,
+                &ui_tx,
+                worker_rx,
+                &log_path,
+            )),
+            Err(err) => Err(anyhow!("Failed to create async runtime: {err}")),
+        };
+
+        if let Err(err) = result {
+            logger::log_line(&log_path, &format!("Local shell error: {err:#}"));
+            ssh::send_ui_status(&ui_tx, IssueKind::Unknown, format!("{err:#}"));
+            ssh::send_message(&ui_tx, UiMessage::Connected(false));
+        }
+    })
+}
+
+async fn run_local_shell_async(
+    client_id: u64,
+    command: &str,
+    scrollback_len: usize,
+    ui_tx: &Sender<UiMessage>,
+    worker_rx: Receiver<WorkerMessage>,
+    log_path: &str,
+) -> Result<()> {
+    let command = if command.trim().is_empty() {
+        default_shell_command()
+    } else {
+        command.trim().to_string()
+    };
+    let (events_tx, mut events) = unbounded_channel();
+    let mut pty = match imp::Pty::spawn(&command, 80, 24, events_tx) {
+        Ok(pty) => pty,
+        Err(err) => {
+            let message = format!("Failed to start {command}: {err}");
+            logger::log_line(log_path, &message);
+            ssh::send_ui_status(ui_tx, IssueKind::Configuration, message);
+            ssh::send_message(ui_tx, UiMessage::Connected(false));
+            return Ok(());
+        }
+    };
+    logger::log_line(
+        log_path,
+        &format!("Local shell {client_id} started: {command}"),
+    );
+    ssh::send_ui_status(ui_tx, IssueKind::Info, format!("Running {command}."));
+    ssh::send_message(ui_tx, UiMessage::Connected(true));
+
+    let len = scrollback_len.clamp(0, 200_000);
+    let len = if len == 0 {
+        ssh::TERM_SCROLLBACK_LEN
+    } else {
+        len
+    };
+    let mut parser = Parser::new(24, 80, len);
+    let mut scanner = CsiQueryScanner::default();
+    // Query replies are collected here and written to the shell after each chunk.
+    let mut replies: Vec<u8> = Vec::new();
+    let mut screen_dirty = true;
+    let mut scrollback_dirty = true;
+    let mut last_scrollback_max: Option<usize> = None;
+    let mut screen_emit_interval = ssh::TERM_SCREEN_EMIT_INTERVAL_BASE;
+    let mut last_screen_emit = Instant::now()
+        .checked_sub(screen_emit_interval)
+        .unwrap_or_else(Instant::now);
+    let mut screen_rate_window_started = Instant::now();
+    let mut screen_rate_window_bytes: u64 = 0;
+    let mut exit_code: Option<i64> = None;
+    let mut worker_rx = ssh::bridge_receiver_to_async(worker_rx);
+
+    loop {
+        let rate_window_elapsed = screen_rate_window_started.elapsed();
+        if rate_window_elapsed >= ssh::TERM_SCREEN_RATE_WINDOW {
+            screen_emit_interval =
+                ssh::adaptive_screen_emit_interval(screen_rate_window_bytes, rate_window_elapsed);
+            screen_rate_window_started = Instant::now();
+            screen_rate_window_bytes = 0;
+        }
+
+        if scrollback_dirty {
+            let scrollback_max = parser.screen().scrollback_max();
+            if last_scrollback_max != Some(scrollback_max) {
+                ssh::send_scrollback_max(ui_tx, &mut parser);
+                last_scrollback_max = Some(scrollback_max);
+            }
+            scrollback_dirty = false;
+        }
+
+        if screen_dirty && last_screen_emit.elapsed() >= screen_emit_interval {
+            ssh::send_screen(ui_tx, &mut parser);
+            screen_dirty = false;
+            last_screen_emit = Instant::now();
+            continue;
+        }
+
+        let mut stopped = false;
+        let mut finished = false;
+        tokio::select! {
+            message = worker_rx.recv() => {
+                match message {
+                    Some(WorkerMessage::Input { data, .. }) => {
+                        pty.write_all(&data).context("Failed to write to the shell")?;
+                    }
+                    Some(WorkerMessage::Resize { rows, cols, .. }) => {
+                        if let Err(err) = pty.resize(cols, rows) {
+                            logger::log_line(
+                                log_path,
+                                &format!("Local shell {client_id} resize failed: {err}"),
+                            );
+                        }
+                        parser.set_size(rows, cols);
+                        screen_dirty = true;
+                        scrollback_dirty = true;
+                    }
+                    Some(WorkerMessage::SetScrollback { rows, .. }) => {
+                        parser.set_scrollback(rows);
+                        screen_dirty = true;
+                        scrollback_dirty = true;
+                    }
+                    Some(WorkerMessage::Disconnect)
+                    | Some(WorkerMessage::DetachTerminalClient { .. })
+                    | None => {
+                        stopped = true;
+                    }
+                    Some(_) => {}
+                }
+            }
+            event = events.recv() => {
+                match event {
+                    Some(ShellEvent::Output(data)) => {
+                        ssh::process_with_query_responses(
+                            &mut parser,
+                            &mut scanner,
+                            &mut replies,
+                            &data,
+                        )
+                        .await?;
+                        if !replies.is_empty() {
+                            pty.write_all(&replies).context("Failed to write to the shell")?;
+                            replies.clear();
+                        }
+                        ssh::send_clipboard_writes(ui_tx, &mut parser);
+                        screen_rate_window_bytes =
+                            screen_rate_window_bytes.saturating_add(data.len() as u64);
+                        screen_dirty = true;
+                        scrollback_dirty = true;
+                    }
+                    Some(ShellEvent::Exited(code)) => {
+                        exit_code = code;
+                        // Output stops only once the pseudo console is gone; keep reading until then.
+                        pty.close();
+                    }
+                    None => {
+                        finished = true;
+                    }
+                }
+            }
+            _ = tokio::time::sleep(screen_emit_interval.saturating_sub(last_screen_emit.elapsed())), if screen_dirty => {
+                ssh::send_screen(ui_tx, &mut parser);
+                screen_dirty = false;
+                last_screen_emit = Instant::now();
+            }
+        }
+
+        if stopped {
+            logger::log_line(log_path, &format!("Local shell {client_id} stopped."));
+            drop(pty);
+            ssh::send_ui_status(ui_tx, IssueKind::Info, "Disconnected.");
+            ssh::send_message(ui_tx, UiMessage::Connected(false));
+            return Ok(());
+        }
+        if finished {
+            let (kind, status) = match exit_code {
+                Some(0) | None => (IssueKind::Info, "Shell exited.".to_string()),
+                Some(code) => (
+                    IssueKind::Unknown,
+                    format!("Shell exited with code {code}."),
+                ),
+            };
+            logger::log_line(
+                log_path,
+                &format!("Local shell {client_id} finished: {status}"),
+            );
+            ssh::send_screen(ui_tx, &mut parser);
+            ssh::send_ui_status(ui_tx, kind, status);
+            ssh::send_message(ui_tx, UiMessage::Connected(false));
+            return Ok(());
+        }
+    }
+}
+
+fn spawn_reader(mut output: impl Read + Send + 'static, events: UnboundedSender<ShellEvent>) {
+    thread::spawn(move || {
+        let mut buf = [0u8; 16 * 1024];
+        loop {
+            match output.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    if events.send(ShellEvent::Output(buf[..n].to_vec())).is_err() {
+                        break;
+                    }
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                // A closed pseudo console reports a broken pipe rather than EOF.
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+fn home_dir() -> Option<std::path::PathBuf> {
+    #[cfg(windows)]
+    let var = "USERPROFILE";
+    #[cfg(not(windows))]
+    let var = "HOME";
+    std::env::var_os(var)
+        .map(std::path::PathBuf::from)
+        .filter(|path| path.is_dir())
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::*;
+    use std::ffi::c_void;
+    use std::fs::File;
+    use std::io::{self, Write};
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle};
+    use std::ptr;
+    use std::sync::Arc;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::Console::{
+        ClosePseudoConsole, CreatePseudoConsole, ResizePseudoConsole, COORD, HPCON,
+    };
+    use windows_sys::Win32::System::Pipes::CreatePipe;
+    use windows_sys::Win32::System::Threading::{
+        CreateProcessW, DeleteProcThreadAttributeList, GetExitCodeProcess,
+        InitializeProcThreadAttributeList, TerminateProcess, UpdateProcThreadAttribute,
+        WaitForSingleObject, EXTENDED_STARTUPINFO_PRESENT, INFINITE, LPPROC_THREAD_ATTRIBUTE_LIST,
+        PROCESS_INFORMATION, PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE, STARTUPINFOEXW,
+    };
+
+    /// A process attached to a ConPTY pseudo console.
+    pub struct Pty {
+        console: Option<HPCON>,
+        input: File,
+        process: Arc<OwnedHandle>,
+    }
+
+    impl Pty {
+        pub fn spawn(
+            command: &str,
+            cols: u16,
+            rows: u16,
+            events: UnboundedSender<ShellEvent>,
+        ) -> io::Result<Self> {
+            let (console_input, input) = pipe()?;
+            let (output, console_output) = pipe()?;
+            let mut console: HPCON = 0;
+            let hr = unsafe {
+                CreatePseudoConsole(
+                    coord(cols, rows),
+                    console_input.as_raw_handle() as HANDLE,
+                    console_output.as_raw_handle() as HANDLE,
+                    0,
+                    &mut console,
+                )
+            };
+            if hr < 0 {
+                return Err(io::Error::other(format!(
+                    "CreatePseudoConsole failed (0x{hr:08X})"
+                )));
+            }
+            // The pseudo console holds its own copies of these ends.
+            drop(console_input);
+            drop(console_output);
+
+            let process = match start_process(command, console) {
+                Ok(process) => Arc::new(process),
+                Err(err) => {
+                    unsafe { ClosePseudoConsole(console) };
+                    return Err(err);
+                }
+            };
+            spawn_reader(output, events.clone());
+            let waited = Arc::clone(&process);
+            thread::spawn(move || {
+                let handle = waited.as_raw_handle() as HANDLE;
+                let mut code = 0u32;
+                let ok = unsafe {
+                    WaitForSingleObject(handle, INFINITE);
+                    GetExitCodeProcess(handle, &mut code)
+                };
+                let _ = events.send(ShellEvent::Exited((ok != 0).then_some(i64::from(code))));
+            });
+
+            Ok(Self {
+                console: Some(console),
+                input,
+                process,
+            })
+        }
+
+        pub fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+            self.input.write_all(data)?;
+            self.input.flush()
+        }
+
+        pub fn resize(&mut self, cols: u16, rows: u16) -> io::Result<()> {
+            let Some(console) = self.console else {
+                return Ok(());
+            };
+            let hr = unsafe { ResizePseudoConsole(console, coord(cols, rows)) };
+            if hr < 0 {
+                return Err(io::Error::other(format!(
+                    "ResizePseudoConsole failed (0x{hr:08X})"
+                )));
+            }
+            Ok(())
+        }
+
+        /// Closes the pseudo console, which ends its output stream and any process still
+        /// attached to it.
+        pub fn close(&mut self) {
+            if let Some(console) = self.console.take() {
+                unsafe { ClosePseudoConsole(console) };
+            }
+        }
+    }
+
+    impl Drop for Pty {
+        fn drop(&mut self) {
+            if self.console.is_some() {
+                unsafe { TerminateProcess(self.process.as_raw_handle() as HANDLE, 1) };
+            }
+            self.close();
+        }
+    }
+
+    fn coord(cols: u16, rows: u16) -> COORD {
+        COORD {
+            X: i16::try_from(cols.max(1)).unwrap_or(i16::MAX),
+            Y: i16::try_from(rows.max(1)).unwrap_or(i16::MAX),
+        }
+    }
+
+    /// Returns `(read end, write end)`.
+    fn pipe() -> io::Result<(File, File)> {
+        let mut read: HANDLE = 0;
+        let mut write: HANDLE = 0;
+        if unsafe { CreatePipe(&mut read, &mut write, ptr::null(), 0) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(unsafe {
+            (
+                File::from_raw_handle(read as _),
+                File::from_raw_handle(write as _),
+            )
+        })
+    }
+
+    fn start_process(command: &str, console: HPCON) -> io::Result<OwnedHandle> {
+        let mut size = 0usize;
+        // Sizing call: fails with ERROR_INSUFFICIENT_BUFFER and reports the size.
+        unsafe { InitializeProcThreadAttributeList(ptr::null_mut(), 1, 0, &mut size) };
+        let mut buffer = vec![0u64; size.div_ceil(8).max(1)];
+        let list = buffer.as_mut_ptr() as LPPROC_THREAD_ATTRIBUTE_LIST;
+        if unsafe { InitializeProcThreadAttributeList(list, 1, 0, &mut size) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let result = (|| {
+            let ok = unsafe {
+                UpdateProcThreadAttribute(
+                    list,
+                    0,
+                    PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE as usize,
+                    console as *const c_void,
+                    std::mem::size_of::<HPCON>(),
+                    ptr::null_mut(),
+                    ptr::null(),
+                )
+            };
+            if ok == 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut startup: STARTUPINFOEXW = unsafe { std::mem::zeroed() };
+            startup.StartupInfo.cb = std::mem::size_of::<STARTUPINFOEXW>() as u32;
+            startup.lpAttributeList = list;
+            let mut command_line: Vec<u16> = command.encode_utf16().chain(Some(0)).collect();
+            let directory: Option<Vec<u16>> = home_dir().map(|dir| {
+                dir.as_os_str()
+                    .encode_wide()
+                    .chain(Some(0))
+                    .collect::<Vec<u16>>()
+            });
+            let mut info: PROCESS_INFORMATION = unsafe { std::mem::zeroed() };
+            let ok = unsafe {
+                CreateProcessW(
+                    ptr::null(),
+                    command_line.as_mut_ptr(),
+                    ptr::null(),
+                    ptr::null(),
+                    0,
+                    EXTENDED_STARTUPINFO_PRESENT,
+                    ptr::null(),
+                    directory.as_ref().map_or(ptr::null(), |dir| dir.as_ptr()),
+                    &startup.StartupInfo,
+                    &mut info,
+                )
+            };
+            if ok == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            unsafe {
+                CloseHandle(info.hThread);
+                Ok(OwnedHandle::from_raw_handle(info.hProcess as _))
+            }
+        })();
+        unsafe { DeleteProcThreadAttributeList(list) };
+        result
+    }
+}
+
+/// Without ConPTY the shell runs on plain pipes: no resizing and no line editing, but enough
+/// to exercise the pane on other platforms.
+#[cfg(not(windows))]
+mod imp {
+    use super::*;
+    use std::io::{self, Write};
+    use std::process::{Child, ChildStdin, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    pub struct Pty {
+        input: Option<ChildStdin>,
+        child: Arc<Mutex<Child>>,
+    }
+
+    impl Pty {
+        pub fn spawn(
+            command: &str,
+            _cols: u16,
+            _rows: u16,
+            events: UnboundedSender<ShellEvent>,
+        ) -> io::Result<Self> {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c")
+                .arg(command)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            if let Some(dir) = home_dir() {
+                cmd.current_dir(dir);
+            }
+            let mut child = cmd.spawn()?;
+            let input = child.stdin.take();
+            if let Some(stdout) = child.stdout.take() {
+                spawn_reader(stdout, events.clone());
+            }
+            if let Some(stderr) = child.stderr.take() {
+                spawn_reader(stderr, events.clone());
+            }
+            let child = Arc::new(Mutex::new(child));
+            let waited = Arc::clone(&child);
+            thread::spawn(move || loop {
+                let status = match waited.lock() {
+                    Ok(mut child) => child.try_wait(),
+                    Err(_) => return,
+                };
+                match status {
+                    Ok(Some(status)) => {
+                        let _ = events.send(ShellEvent::Exited(status.code().map(i64::from)));
+                        return;
+                    }
+                    Ok(None) => thread::sleep(Duration::from_millis(100)),
+                    Err(_) => {
+                        let _ = events.send(ShellEvent::Exited(None));
+                        return;
+                    }
+                }
+            });
+            Ok(Self { input, child })
+        }
+
+        pub fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+            let Some(input) = self.input.as_mut() else {
+                return Ok(());
+            };
+            input.write_all(data)?;
+            input.flush()
+        }
+
+        pub fn resize(&mut self, _cols: u16, _rows: u16) -> io::Result<()> {
+            Ok(())
+        }
+
+        pub fn close(&mut self) {
+            self.input = None;
+        }
+    }
+
+    impl Drop for Pty {
+        fn drop(&mut self) {
+            self.close();
+            if let Ok(mut child) = self.child.lock() {
+                let _ = child.kill();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_names_drop_path_and_extension() {
+        assert_eq!(shell_display_name("C:\\Windows\\System32\\cmd.exe"), "cmd");
+        assert_eq!(
+            shell_display_name("\"C:\\Program Files\\PowerShell\\7\\pwsh.EXE\" -NoLogo"),
+            "pwsh"
+        );
+        assert_eq!(shell_display_name("wsl.exe ~"), "wsl");
+        assert_eq!(shell_display_name("/bin/bash"), "bash");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn shell_output_reaches_the_screen_and_exit_is_reported() {
+        let (ui_tx, ui_rx) = std::sync::mpsc::channel();
+        let (_worker_tx, worker_rx) = std::sync::mpsc::channel();
+        let handle = start_local_shell(
+            1,
+            "printf 'hello\\n'; exit 3".to_string(),
+            100,
+            ui_tx,
+            worker_rx,
+            std::env::temp_dir()
+                .join("rusty-local-shell-test.log")
+                .display()
+                .to_string(),
+        );
+        handle.join().unwrap();
+
+        let mut saw_hello = false;
+        let mut statuses = Vec::new();
+        let mut connected = Vec::new();
+        for message in ui_rx.try_iter() {
+            match message {
+                UiMessage::Screen(screen) => saw_hello |= screen.contents().contains("hello"),
+                UiMessage::Status(status) => statuses.push(status.message),
+                UiMessage::Connected(ok) => connected.push(ok),
+                _ => {}
+            }
+        }
+        assert!(saw_hello);
+        assert_eq!(connected, vec![true, false]);
+        assert_eq!(
+            statuses.last().map(String::as_str),
+            Some("Shell exited with code 3.")
+        );
+    }
+}
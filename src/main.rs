@@ -1,12 +1,20 @@
 #![cfg_attr(windows, windows_subsystem = "windows")]
 
 mod app;
+mod asciicast;
 mod async_config;
+mod cli;
 mod config;
 mod crypto;
+mod headless;
+mod known_hosts;
+mod local_shell;
 mod logger;
 mod model;
+mod single_instance;
 mod ssh;
+mod ssh_config;
+mod telnet;
 mod terminal_emulator;
 mod terminal_themes;
 mod tray;
@@ -31,6 +39,20 @@ fn main() -> eframe::Result<()> {
         &format!("=== startup pid={} ===", std::process::id()),
     );
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = headless::run_from_args(&args) {
+        std::process::exit(code);
+    }
+
+    let launch = cli::parse_args(args);
+    if launch.as_ref().map_or(true, |request| !request.is_empty()) {
+        if single_instance::hand_off(&launch) {
+            return Ok(());
+        }
+        single_instance::queue(launch);
+    }
+    single_instance::listen();
+
     if std::env::var_os("WGPU_DX12_COMPILER").is_none() {
         std::env::set_var("WGPU_DX12_COMPILER", "fxc");
     }
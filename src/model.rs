@@ -1,8 +1,15 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+pub const DEFAULT_TERMINAL_TYPE: &str = "xterm-256color";
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ConnectionSettings {
+    /// SSH unless the profile opts into a plaintext Telnet or raw TCP session.
+    #[serde(default)]
+    pub protocol: ConnectionProtocol,
     pub host: String,
     pub port: u16,
     pub username: String,
@@ -11,17 +18,536 @@ pub struct ConnectionSettings {
     pub private_key_path: String,
     #[serde(default)]
     pub key_passphrase: String,
+    /// OpenSSH user certificate presented with the private key; empty falls back to a
+    /// `<key>-cert.pub` file next to the key.
+    #[serde(default)]
+    pub certificate_path: String,
+    /// Further keys offered in order after `private_key_path` (OpenSSH repeats `IdentityFile`).
+    #[serde(default)]
+    pub identities: Vec<IdentityFile>,
+    /// Bastion hosts traversed in order before reaching `host` (OpenSSH `ProxyJump`).
+    #[serde(default)]
+    pub jump_hosts: Vec<JumpHostSettings>,
+    /// Local command whose stdin/stdout carry the SSH stream (OpenSSH `ProxyCommand`).
+    /// `%h`, `%p` and `%r` expand to the first hop's host, port and user.
+    #[serde(default)]
+    pub proxy_command: String,
+    /// Overrides the global proxy; `None` uses the global setting.
+    #[serde(default)]
+    pub proxy_override: Option<ProxySettings>,
+    /// Local listeners tunnelled to remote endpoints over the session (OpenSSH `-L`).
+    #[serde(default)]
+    pub local_forwards: Vec<LocalForward>,
+    /// Server-side listeners tunnelled back to local targets (OpenSSH `-R`).
+    #[serde(default)]
+    pub remote_forwards: Vec<RemoteForward>,
+    /// Local SOCKS4/5 listeners whose CONNECTs are tunnelled over the session (OpenSSH `-D`).
+    #[serde(default)]
+    pub dynamic_forwards: Vec<DynamicForward>,
+    /// Let the remote side use the local ssh-agent (OpenSSH `ForwardAgent`).
+    #[serde(default)]
+    pub forward_agent: bool,
+    #[serde(default)]
+    pub algorithms: AlgorithmPreferences,
+    /// Seconds allowed for each TCP connect and SSH handshake; 0 waits indefinitely.
+    /// Missing in older configs, so the container default (15) applies rather than 0.
+    pub connect_timeout_secs: u32,
+    /// Seconds of server silence before a keepalive is sent; 0 disables keepalives.
+    pub keepalive_interval_secs: u32,
+    /// Unanswered keepalives before the connection is treated as lost; 0 never gives up.
+    pub keepalive_max_missed: u32,
+    /// Reconnect with backoff when the session drops without the user asking.
+    #[serde(default)]
+    pub auto_reconnect: bool,
+    /// Typed into the shell after login, one command per line (e.g. `cd /srv/app`, `sudo -i`).
+    #[serde(default)]
+    pub startup_commands: String,
+    /// Regex matched against the text left of the cursor before each startup command is sent
+    /// (e.g. `[$#] $`); empty sends them straight away.
+    #[serde(default)]
+    pub startup_prompt_pattern: String,
+    /// Runs this instead of a login shell, like `ssh host command` (OpenSSH `RemoteCommand`).
+    #[serde(default)]
+    pub remote_command: String,
+    /// Terminal type announced for the PTY and as `TERM`; empty uses `xterm-256color`.
+    #[serde(default)]
+    pub terminal_type: String,
+    /// Sent with `set_env` before the shell starts (OpenSSH `SetEnv`). Servers only accept
+    /// names allowed by their `AcceptEnv` policy.
+    #[serde(default)]
+    pub environment: Vec<EnvVar>,
+    /// Applies to this host and every jump host on the way.
+    #[serde(default)]
+    pub host_key_policy: HostKeyPolicy,
 }
 
 impl Default for ConnectionSettings {
     fn default() -> Self {
         Self {
+            protocol: ConnectionProtocol::default(),
             host: String::new(),
             port: 22,
             username: String::new(),
             password: String::new(),
             private_key_path: String::new(),
             key_passphrase: String::new(),
+            certificate_path: String::new(),
+            identities: Vec::new(),
+            jump_hosts: Vec::new(),
+            proxy_command: String::new(),
+            proxy_override: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            dynamic_forwards: Vec::new(),
+            forward_agent: false,
+            algorithms: AlgorithmPreferences::default(),
+            connect_timeout_secs: 15,
+            keepalive_interval_secs: 20,
+            keepalive_max_missed: 3,
+            auto_reconnect: false,
+            startup_commands: String::new(),
+            startup_prompt_pattern: String::new(),
+            remote_command: String::new(),
+            terminal_type: String::new(),
+            environment: Vec::new(),
+            host_key_policy: HostKeyPolicy::default(),
         }
     }
 }
+
+impl ConnectionSettings {
+    /// Enough is filled in to open the session: a host, and a username when it is SSH.
+    pub fn can_connect(&self) -> bool {
+        !self.host.trim().is_empty()
+            && (self.protocol != ConnectionProtocol::Ssh || !self.username.trim().is_empty())
+    }
+
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        (self.connect_timeout_secs > 0)
+            .then(|| Duration::from_secs(self.connect_timeout_secs.into()))
+    }
+
+    pub fn keepalive_interval(&self) -> Option<Duration> {
+        (self.keepalive_interval_secs > 0)
+            .then(|| Duration::from_secs(self.keepalive_interval_secs.into()))
+    }
+
+    pub fn terminal_type(&self) -> &str {
+        match self.terminal_type.trim() {
+            "" => DEFAULT_TERMINAL_TYPE,
+            term => term,
+        }
+    }
+
+    pub fn startup_command_lines(&self) -> impl Iterator<Item = &str> {
+        self.startup_commands
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.trim().is_empty())
+    }
+
+    /// Keys offered during public-key authentication, primary key first. Blank paths are skipped.
+    pub fn identity_files(&self) -> Vec<IdentityFile> {
+        std::iter::once(IdentityFile {
+            private_key_path: self.private_key_path.clone(),
+            key_passphrase: self.key_passphrase.clone(),
+        })
+        .chain(self.identities.iter().cloned())
+        .filter(|identity| !identity.private_key_path.trim().is_empty())
+        .collect()
+    }
+
+    /// Settings for each jump host in connection order. Hops without a host are skipped.
+    pub fn jump_hop_settings(&self) -> Vec<ConnectionSettings> {
+        self.jump_hosts
+            .iter()
+            .filter(|hop| !hop.host.trim().is_empty())
+            .map(|hop| hop.to_connection_settings(self))
+            .collect()
+    }
+
+    /// Remembers `passphrase` for the key at `key_path`: the primary key when the path is
+    /// empty or the primary one, otherwise the matching identity. Jump hosts using the same key
+    /// get it too. A path that matches nothing stores nothing.
+    pub fn set_key_passphrase(&mut self, key_path: Option<&str>, passphrase: String) {
+        let key_path = key_path.map(str::trim).unwrap_or_default();
+        for hop in &mut self.jump_hosts {
+            if !key_path.is_empty() && hop.private_key_path.trim() == key_path {
+                hop.key_passphrase = passphrase.clone();
+            }
+        }
+        if key_path.is_empty() || self.private_key_path.trim() == key_path {
+            self.key_passphrase = passphrase;
+        } else if let Some(identity) = self
+            .identities
+            .iter_mut()
+            .find(|identity| identity.private_key_path.trim() == key_path)
+        {
+            identity.key_passphrase = passphrase;
+        }
+    }
+
+    pub fn clear_passwords(&mut self) {
+        self.password.clear();
+        if let Some(proxy) = &mut self.proxy_override {
+            proxy.password.clear();
+        }
+        for hop in &mut self.jump_hosts {
+            hop.password.clear();
+        }
+    }
+
+    pub fn clear_key_passphrases(&mut self) {
+        self.key_passphrase.clear();
+        for identity in &mut self.identities {
+            identity.key_passphrase.clear();
+        }
+        for hop in &mut self.jump_hosts {
+            hop.key_passphrase.clear();
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct EnvVar {
+    pub name: String,
+    pub value: String,
+}
+
+impl EnvVar {
+    pub fn is_complete(&self) -> bool {
+        !self.name.trim().is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct IdentityFile {
+    pub private_key_path: String,
+    pub key_passphrase: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct JumpHostSettings {
+    pub host: String,
+    pub port: u16,
+    /// Falls back to the target username when empty.
+    pub username: String,
+    pub password: String,
+    pub private_key_path: String,
+    pub key_passphrase: String,
+}
+
+impl Default for JumpHostSettings {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 22,
+            username: String::new(),
+            password: String::new(),
+            private_key_path: String::new(),
+            key_passphrase: String::new(),
+        }
+    }
+}
+
+impl JumpHostSettings {
+    /// Connection settings used to authenticate this hop.
+    pub fn to_connection_settings(&self, target: &ConnectionSettings) -> ConnectionSettings {
+        let username = if self.username.trim().is_empty() {
+            target.username.clone()
+        } else {
+            self.username.clone()
+        };
+        ConnectionSettings {
+            host: self.host.clone(),
+            port: self.port,
+            username,
+            password: self.password.clone(),
+            private_key_path: self.private_key_path.clone(),
+            key_passphrase: self.key_passphrase.clone(),
+            ..ConnectionSettings::default()
+        }
+    }
+}
+
+/// Comma-separated algorithm names in preference order, as in OpenSSH's `KexAlgorithms`,
+/// `Ciphers`, `MACs` and `HostKeyAlgorithms`. An empty list keeps the built-in defaults.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct AlgorithmPreferences {
+    pub kex: String,
+    pub ciphers: String,
+    pub macs: String,
+    pub host_keys: String,
+}
+
+impl AlgorithmPreferences {
+    pub fn names(list: &str) -> impl Iterator<Item = &str> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+}
+
+/// How a profile reaches its host. Only SSH is encrypted; the others exist for switches,
+/// console servers and lab gear that speak nothing else.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ConnectionProtocol {
+    #[default]
+    Ssh,
+    /// RFC 854 Telnet with NAWS, TTYPE, ECHO, SGA and BINARY negotiation.
+    Telnet,
+    /// Bytes passed through unchanged in both directions.
+    Raw,
+}
+
+impl ConnectionProtocol {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ssh => "SSH",
+            Self::Telnet => "Telnet",
+            Self::Raw => "Raw TCP",
+        }
+    }
+
+    pub fn default_port(self) -> u16 {
+        match self {
+            Self::Ssh => 22,
+            Self::Telnet | Self::Raw => 23,
+        }
+    }
+
+    pub fn is_encrypted(self) -> bool {
+        self == Self::Ssh
+    }
+}
+
+/// What to do with a host key that is not pinned yet or no longer matches its pin
+/// (OpenSSH `StrictHostKeyChecking`).
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HostKeyPolicy {
+    /// Prompt for unknown and changed keys; connections that cannot prompt reject them.
+    #[default]
+    Ask,
+    /// Pin unknown keys without asking but refuse changed ones.
+    AcceptNew,
+    /// Only connect to hosts whose key is already pinned.
+    Strict,
+}
+
+impl HostKeyPolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ask => "Ask",
+            Self::AcceptNew => "Accept new",
+            Self::Strict => "Strict",
+        }
+    }
+}
+
+/// Which known_hosts file newly trusted host keys are appended to. Both files are always read.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum KnownHostsTarget {
+    /// `~/.ssh/known_hosts`, shared with OpenSSH.
+    #[default]
+    OpenSsh,
+    /// `known_hosts` in Rusty's data folder, leaving the OpenSSH file untouched.
+    Rusty,
+}
+
+impl KnownHostsTarget {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::OpenSsh => "OpenSSH (~/.ssh/known_hosts)",
+            Self::Rusty => "Rusty only",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProxyKind {
+    #[default]
+    Direct,
+    Socks5,
+    HttpConnect,
+}
+
+impl ProxyKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Direct => "Direct (no proxy)",
+            Self::Socks5 => "SOCKS5",
+            Self::HttpConnect => "HTTP CONNECT",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub kind: ProxyKind,
+    pub host: String,
+    pub port: u16,
+    /// Optional; sent as SOCKS5 username/password or HTTP Basic credentials.
+    pub username: String,
+    pub password: String,
+}
+
+impl Default for ProxySettings {
+    fn default() -> Self {
+        Self {
+            kind: ProxyKind::Direct,
+            host: String::new(),
+            port: 1080,
+            username: String::new(),
+            password: String::new(),
+        }
+    }
+}
+
+impl ProxySettings {
+    pub fn is_enabled(&self) -> bool {
+        self.kind != ProxyKind::Direct && !self.host.trim().is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct LocalForward {
+    pub bind_address: String,
+    pub local_port: u16,
+    pub remote_host: String,
+    pub remote_port: u16,
+}
+
+impl Default for LocalForward {
+    fn default() -> Self {
+        Self {
+            bind_address: "127.0.0.1".to_string(),
+            local_port: 0,
+            remote_host: String::new(),
+            remote_port: 0,
+        }
+    }
+}
+
+impl LocalForward {
+    pub fn is_complete(&self) -> bool {
+        self.local_port != 0 && self.remote_port != 0 && !self.remote_host.trim().is_empty()
+    }
+
+    /// Listen address, treating an empty bind address as loopback.
+    pub fn bind_addr(&self) -> String {
+        bind_socket_addr(&self.bind_address, self.local_port)
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "L {} -> {}:{}",
+            self.bind_addr(),
+            self.remote_host.trim(),
+            self.remote_port
+        )
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RemoteForward {
+    /// Address the server listens on; empty means the server's loopback interface.
+    pub bind_address: String,
+    /// Port the server listens on; 0 lets the server pick one.
+    pub remote_port: u16,
+    pub local_host: String,
+    pub local_port: u16,
+}
+
+impl Default for RemoteForward {
+    fn default() -> Self {
+        Self {
+            bind_address: String::new(),
+            remote_port: 0,
+            local_host: "127.0.0.1".to_string(),
+            local_port: 0,
+        }
+    }
+}
+
+impl RemoteForward {
+    pub fn is_complete(&self) -> bool {
+        self.local_port != 0 && !self.local_host.trim().is_empty()
+    }
+
+    pub fn remote_bind_address(&self) -> String {
+        let bind = self.bind_address.trim();
+        if bind.is_empty() {
+            "localhost".to_string()
+        } else {
+            bind.to_string()
+        }
+    }
+
+    pub fn local_target(&self) -> String {
+        let host = self.local_host.trim();
+        if host.contains(':') && !host.starts_with('[') {
+            format!("[{host}]:{}", self.local_port)
+        } else {
+            format!("{host}:{}", self.local_port)
+        }
+    }
+
+    /// `bound_port` is the port the server reported, which matters when `remote_port` is 0.
+    pub fn describe(&self, bound_port: Option<u32>) -> String {
+        let port = bound_port.unwrap_or(u32::from(self.remote_port));
+        format!(
+            "R {}:{port} -> {}",
+            self.remote_bind_address(),
+            self.local_target()
+        )
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct DynamicForward {
+    pub bind_address: String,
+    pub local_port: u16,
+}
+
+impl Default for DynamicForward {
+    fn default() -> Self {
+        Self {
+            bind_address: "127.0.0.1".to_string(),
+            local_port: 1080,
+        }
+    }
+}
+
+impl DynamicForward {
+    pub fn is_complete(&self) -> bool {
+        self.local_port != 0
+    }
+
+    pub fn bind_addr(&self) -> String {
+        bind_socket_addr(&self.bind_address, self.local_port)
+    }
+
+    pub fn describe(&self) -> String {
+        format!("D {} (SOCKS)", self.bind_addr())
+    }
+}
+
+/// `bind:port` for a local listener; an empty bind address means loopback and IPv6 addresses
+/// get brackets.
+fn bind_socket_addr(bind: &str, port: u16) -> String {
+    let bind = bind.trim();
+    let bind = if bind.is_empty() { "127.0.0.1" } else { bind };
+    if bind.contains(':') && !bind.starts_with('[') {
+        format!("[{bind}]:{port}")
+    } else {
+        format!("{bind}:{port}")
+    }
+}
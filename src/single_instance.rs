@@ -0,0 +1,150 @@
+use std::hash::BuildHasher;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::LaunchRequest;
+use crate::config;
+use crate::logger;
+
+const LOG_PATH: &str = "logs\\single-instance.log";
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(2);
+/// Requests are a few hundred bytes; anything much larger is not from Rusty.
+const MAX_MESSAGE_LEN: u64 = 64 * 1024;
+
+/// Command-line requests for this window: its own, then any handed over by later launches.
+/// Parse errors travel too so the running window can report them.
+pub type Launch = Result<LaunchRequest, String>;
+
+static CHANNEL: Lazy<(Sender<Launch>, Receiver<Launch>)> = Lazy::new(unbounded);
+
+#[derive(Serialize, Deserialize)]
+struct Handoff {
+    token: String,
+    launch: Launch,
+}
+
+pub fn requests() -> Receiver<Launch> {
+    CHANNEL.1.clone()
+}
+
+pub fn queue(launch: Launch) {
+    let _ = CHANNEL.0.send(launch);
+}
+
+/// Sends `launch` to the instance recorded in the instance file. `false` means none answered
+/// and this process should open its own window.
+pub fn hand_off(launch: &Launch) -> bool {
+    let Ok(text) = std::fs::read_to_string(config::instance_path()) else {
+        return false;
+    };
+    let Some((port, token)) = text.trim().split_once(' ') else {
+        return false;
+    };
+    let Ok(port) = port.parse::<u16>() else {
+        return false;
+    };
+    let message = Handoff {
+        token: token.to_string(),
+        launch: launch.clone(),
+    };
+    let sent = (|| -> std::io::Result<bool> {
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let mut stream = TcpStream::connect_timeout(&addr, HANDOFF_TIMEOUT)?;
+        stream.set_read_timeout(Some(HANDOFF_TIMEOUT))?;
+        let mut line = serde_json::to_string(&message)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes())?;
+        let mut reply = String::new();
+        BufReader::new(stream.take(16)).read_line(&mut reply)?;
+        Ok(reply.trim() == "ok")
+    })();
+    match sent {
+        Ok(true) => {
+            logger::log_line(
+                LOG_PATH,
+                &format!("Handed request to instance on port {port}."),
+            );
+            true
+        }
+        Ok(false) => false,
+        Err(err) => {
+            // Usually a stale file left by an instance that did not exit cleanly.
+            logger::log_line(
+                LOG_PATH,
+                &format!("No running instance on port {port} ({err}); starting a new one."),
+            );
+            false
+        }
+    }
+}
+
+/// Accepts requests from later launches on a loopback port and records it, with a random
+/// token, in the instance file so only this user's launches are accepted.
+pub fn listen() {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            logger::log_line(LOG_PATH, &format!("Failed to listen for handoffs: {err}"));
+            return;
+        }
+    };
+    let Ok(port) = listener.local_addr().map(|addr| addr.port()) else {
+        return;
+    };
+    let token = new_token();
+    let path = config::instance_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(err) = std::fs::write(&path, format!("{port} {token}")) {
+        logger::log_line(
+            LOG_PATH,
+            &format!("Failed to write {}: {err}", path.display()),
+        );
+        return;
+    }
+
+    let _ = std::thread::Builder::new()
+        .name("rusty-single-instance".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                if let Err(err) = accept(stream, &token) {
+                    logger::log_line(LOG_PATH, &format!("Rejected handoff: {err}"));
+                }
+            }
+        });
+}
+
+fn accept(mut stream: TcpStream, token: &str) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(HANDOFF_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new((&stream).take(MAX_MESSAGE_LEN)).read_line(&mut line)?;
+    let message: Handoff = serde_json::from_str(&line)?;
+    if message.token != token {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "wrong token",
+        ));
+    }
+    queue(message.launch);
+    crate::tray::request_app_repaint();
+    stream.write_all(b"ok\n")
+}
+
+fn new_token() -> String {
+    let state = std::collections::hash_map::RandomState::new();
+    let now = Instant::now();
+    format!(
+        "{:016x}{:016x}",
+        state.hash_one((now, std::process::id())),
+        std::collections::hash_map::RandomState::new().hash_one(now)
+    )
+}
@@ -1,9 +1,10 @@
+use std::cell::RefCell;
 use std::collections::{HashSet, VecDeque};
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, TryRecvError};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant, UNIX_EPOCH};
 
@@ -17,16 +18,23 @@ use russh_sftp::protocol::{FileAttributes, FileType as SftpFileType, OpenFlags};
 use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
+use crate::asciicast::CastWriter;
+use crate::known_hosts::{self, HostKeyVerdict};
 use crate::logger;
-use crate::model::ConnectionSettings;
+use crate::model::{
+    AlgorithmPreferences, ConnectionSettings, HostKeyPolicy, KnownHostsTarget, ProxyKind,
+    ProxySettings,
+};
 
 const SESSION_HOUSEKEEPING_INTERVAL: Duration = Duration::from_millis(250);
 pub const TERM_SCROLLBACK_LEN: usize = 5000;
-const TERM_SCREEN_EMIT_INTERVAL_BASE: Duration = Duration::from_millis(24);
+pub const TERM_SCREEN_EMIT_INTERVAL_BASE: Duration = Duration::from_millis(24);
 const TERM_SCREEN_EMIT_INTERVAL_MEDIUM: Duration = Duration::from_millis(33);
 const TERM_SCREEN_EMIT_INTERVAL_HIGH: Duration = Duration::from_millis(50);
 const TERM_SCREEN_EMIT_INTERVAL_EXTREME: Duration = Duration::from_millis(66);
-const TERM_SCREEN_RATE_WINDOW: Duration = Duration::from_millis(250);
+pub const TERM_SCREEN_RATE_WINDOW: Duration = Duration::from_millis(250);
+const STARTUP_PROMPT_TIMEOUT: Duration = Duration::from_secs(15);
+const CHANNEL_REQUEST_REPLY_TIMEOUT: Duration = Duration::from_secs(10);
 const FAST_REMOTE_COMPARE_BATCH_LIMIT: usize = 64;
 
 fn app_data_dir() -> PathBuf {
@@ -57,15 +65,46 @@ fn ensure_known_hosts_parent(path: &Path) {
     }
 }
 
-fn app_known_hosts_path() -> PathBuf {
-    let path = user_home_dir()
-        .map(|home| home.join(".ssh").join("known_hosts"))
-        .unwrap_or_else(|| app_data_dir().join("known_hosts"));
+static KNOWN_HOSTS_TARGET: Mutex<KnownHostsTarget> = Mutex::new(KnownHostsTarget::OpenSsh);
+
+/// Chooses the known_hosts file newly trusted keys are written to.
+pub fn set_known_hosts_target(target: KnownHostsTarget) {
+    if let Ok(mut slot) = KNOWN_HOSTS_TARGET.lock() {
+        *slot = target;
+    }
+}
+
+fn openssh_known_hosts_path() -> Option<PathBuf> {
+    user_home_dir().map(|home| home.join(".ssh").join("known_hosts"))
+}
+
+fn rusty_known_hosts_path() -> PathBuf {
+    app_data_dir().join("known_hosts")
+}
+
+/// Every file consulted when verifying a host key, OpenSSH's own first.
+pub fn known_hosts_read_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = openssh_known_hosts_path().into_iter().collect();
+    paths.push(rusty_known_hosts_path());
+    paths
+}
+
+fn known_hosts_write_path() -> PathBuf {
+    let target = KNOWN_HOSTS_TARGET
+        .lock()
+        .map(|slot| *slot)
+        .unwrap_or_default();
+    let path = match target {
+        KnownHostsTarget::OpenSsh => {
+            openssh_known_hosts_path().unwrap_or_else(rusty_known_hosts_path)
+        }
+        KnownHostsTarget::Rusty => rusty_known_hosts_path(),
+    };
     ensure_known_hosts_parent(&path);
     path
 }
 
-fn remove_known_hosts_line(path: &Path, line_to_remove: usize) -> std::io::Result<()> {
+pub fn remove_known_hosts_line(path: &Path, line_to_remove: usize) -> std::io::Result<()> {
     let contents = std::fs::read_to_string(path)?;
     let mut found = false;
     let mut kept: Vec<&str> = Vec::new();
@@ -142,14 +181,76 @@ pub enum UiMessage {
     ScrollbackMax(usize),
     Clipboard(crate::terminal_emulator::ClipboardWrite),
     Connected(bool),
+    /// Sent before `Connected(false)` when the transport dropped rather than the shell exiting.
+    ConnectionLost,
     AuthPrompt(AuthPrompt),
     HostKeyPrompt(HostKeyPrompt),
+    Forwards(Vec<ForwardStatus>),
+    Negotiated(NegotiatedAlgorithms),
+    /// Path of the recording in progress, `None` once it stopped.
+    Recording(Option<PathBuf>),
+}
+
+/// Algorithms agreed with the server during the last key exchange.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegotiatedAlgorithms {
+    pub kex: String,
+    pub host_key: String,
+    pub cipher: String,
+    pub client_mac: String,
+    pub server_mac: String,
+}
+
+impl NegotiatedAlgorithms {
+    fn from_names(names: &russh::Names) -> Self {
+        Self {
+            kex: names.kex.as_ref().to_string(),
+            host_key: names.key.as_str().to_string(),
+            cipher: names.cipher.as_ref().to_string(),
+            client_mac: names.client_mac.as_ref().to_string(),
+            server_mac: names.server_mac.as_ref().to_string(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let mac = if self.client_mac == self.server_mac {
+            self.client_mac.clone()
+        } else {
+            format!("{} / {}", self.client_mac, self.server_mac)
+        };
+        format!(
+            "kex {}, host key {}, cipher {}, mac {mac}",
+            self.kex, self.host_key, self.cipher
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardState {
+    Starting,
+    Active,
+    Failed,
+    Stopped,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForwardStatus {
+    pub description: String,
+    pub state: ForwardState,
+    pub open_connections: usize,
+    /// Bytes read from the local side and sent through the tunnel.
+    pub bytes_sent: u64,
+    /// Bytes received through the tunnel and written to the local side.
+    pub bytes_received: u64,
+    pub last_error: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct AuthPrompt {
     pub instructions: String,
     pub prompts: Vec<AuthPromptItem>,
+    /// Private key a passphrase prompt is for, so a remembered answer lands on that identity.
+    pub key_path: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -215,6 +316,9 @@ pub struct HostKeyPrompt {
     pub fingerprint: String,
     pub known_hosts_path: String,
     pub changed_line: Option<usize>,
+    /// `@cert-authority` lines covering the host. Host certificates aren't validated, so these
+    /// never vouch for the key; the prompt says so instead of leaving the user to wonder.
+    pub cert_authorities: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -224,6 +328,17 @@ pub enum HostKeyDecision {
     Reject,
 }
 
+/// Asks a terminal client to record its session to an asciinema v2 `.cast` file.
+#[derive(Debug, Clone)]
+pub struct RecordingRequest {
+    pub path: PathBuf,
+    pub title: String,
+    /// Also record keystrokes as `i` events.
+    pub include_input: bool,
+    /// Mask keystrokes typed at what looks like a password prompt, where the PTY has echo off.
+    pub redact_hidden_input: bool,
+}
+
 #[derive(Debug)]
 pub enum WorkerMessage {
     Input {
@@ -241,6 +356,13 @@ pub enum WorkerMessage {
         client_id: u64,
         rows: usize,
     },
+    StartRecording {
+        client_id: u64,
+        request: RecordingRequest,
+    },
+    StopRecording {
+        client_id: u64,
+    },
     AttachTerminalClient {
         client_id: u64,
         ui_tx: Sender<UiMessage>,
@@ -354,6 +476,8 @@ enum TerminalClientCommand {
         height_px: u32,
     },
     SetScrollback(usize),
+    StartRecording(RecordingRequest),
+    StopRecording,
     Disconnect,
 }
 
@@ -462,7 +586,7 @@ enum TerminalQuery {
 }
 
 #[derive(Debug, Clone)]
-struct CsiQueryScanner {
+pub struct CsiQueryScanner {
     state: ScanState,
     params: Vec<u8>,
 }
@@ -587,7 +711,7 @@ async fn respond_to_query<W: tokio::io::AsyncWrite + Unpin>(
     Ok(())
 }
 
-async fn process_with_query_responses<W: tokio::io::AsyncWrite + Unpin>(
+pub async fn process_with_query_responses<W: tokio::io::AsyncWrite + Unpin>(
     parser: &mut Parser,
     scanner: &mut CsiQueryScanner,
     writer: &mut W,
@@ -653,29 +777,29 @@ fn supports_method(methods: &MethodSet, target: MethodKind) -> bool {
     methods.contains(&target)
 }
 
-fn send_message<T>(tx: &Sender<T>, message: T) {
+pub fn send_message<T>(tx: &Sender<T>, message: T) {
     let _ = tx.send(message);
     crate::tray::request_app_repaint();
 }
 
-fn send_screen(ui_tx: &Sender<UiMessage>, parser: &mut Parser) {
+pub fn send_screen(ui_tx: &Sender<UiMessage>, parser: &mut Parser) {
     send_message(ui_tx, UiMessage::Screen(Box::new(parser.screen().clone())));
 }
 
-fn send_scrollback_max(ui_tx: &Sender<UiMessage>, parser: &mut Parser) {
+pub fn send_scrollback_max(ui_tx: &Sender<UiMessage>, parser: &mut Parser) {
     send_message(
         ui_tx,
         UiMessage::ScrollbackMax(parser.screen().scrollback_max()),
     );
 }
 
-fn send_clipboard_writes(ui_tx: &Sender<UiMessage>, parser: &mut Parser) {
+pub fn send_clipboard_writes(ui_tx: &Sender<UiMessage>, parser: &mut Parser) {
     for write in parser.take_clipboard_writes() {
         send_message(ui_tx, UiMessage::Clipboard(write));
     }
 }
 
-fn bridge_receiver_to_async<T: Send + 'static>(rx: Receiver<T>) -> UnboundedReceiver<T> {
+pub fn bridge_receiver_to_async<T: Send + 'static>(rx: Receiver<T>) -> UnboundedReceiver<T> {
     let (async_tx, async_rx) = unbounded_channel();
     thread::spawn(move || {
         while let Ok(message) = rx.recv() {
@@ -687,7 +811,7 @@ fn bridge_receiver_to_async<T: Send + 'static>(rx: Receiver<T>) -> UnboundedRece
     async_rx
 }
 
-fn adaptive_screen_emit_interval(window_bytes: u64, window_elapsed: Duration) -> Duration {
+pub fn adaptive_screen_emit_interval(window_bytes: u64, window_elapsed: Duration) -> Duration {
     let secs = window_elapsed.as_secs_f64();
     if secs <= 0.0 {
         return TERM_SCREEN_EMIT_INTERVAL_BASE;
@@ -1604,6 +1728,42 @@ enum ConnectionError {
     MissingUsername,
     AuthenticationFailed,
     DetachedTransferNeedsCredentials,
+    ProxyCommandSpawn {
+        command: String,
+        reason: String,
+    },
+    ProxyCommandExited {
+        status: String,
+        stderr: String,
+    },
+    /// A `%h`/`%r` value that would be interpreted by the shell running the proxy command.
+    ProxyCommandUnsafeToken {
+        token: &'static str,
+        value: String,
+    },
+    ProxyHandshake(String),
+    UnsupportedAlgorithm {
+        kind: &'static str,
+        name: String,
+    },
+    ConnectTimeout {
+        target: String,
+        limit: Duration,
+    },
+    HostKeyRevoked {
+        host: String,
+        path: String,
+        line: usize,
+    },
+    HostKeyChanged {
+        host: String,
+        path: String,
+        line: usize,
+    },
+    UnknownHostKey {
+        host: String,
+        fingerprint: String,
+    },
 }
 
 impl std::fmt::Display for ConnectionError {
@@ -1616,6 +1776,51 @@ impl std::fmt::Display for ConnectionError {
                 f,
                 "Detached transfer requires saved credentials or a live authenticated tab"
             ),
+            Self::ProxyCommandSpawn { command, reason } => {
+                write!(f, "Failed to start proxy command `{command}`: {reason}")
+            }
+            Self::ProxyCommandUnsafeToken { token, value } => write!(
+                f,
+                "Refusing to run the proxy command: {token} `{value}` contains shell metacharacters"
+            ),
+            Self::ProxyCommandExited { status, stderr } => {
+                if stderr.is_empty() {
+                    write!(f, "Proxy command exited ({status})")
+                } else {
+                    write!(f, "Proxy command exited ({status}): {stderr}")
+                }
+            }
+            Self::ProxyHandshake(message) => write!(f, "Proxy error: {message}"),
+            Self::UnsupportedAlgorithm { kind, name } => {
+                write!(f, "Unsupported {kind} algorithm `{name}`")
+            }
+            Self::ConnectTimeout { target, limit } => {
+                if limit.subsec_nanos() == 0 {
+                    write!(
+                        f,
+                        "Timed out connecting to {target} after {}s",
+                        limit.as_secs()
+                    )
+                } else {
+                    write!(
+                        f,
+                        "Timed out connecting to {target} after {}ms",
+                        limit.as_millis()
+                    )
+                }
+            }
+            Self::HostKeyRevoked { host, path, line } => write!(
+                f,
+                "The key offered by {host} is marked @revoked in {path} (line {line})"
+            ),
+            Self::HostKeyChanged { host, path, line } => write!(
+                f,
+                "The key offered by {host} does not match the one pinned in {path} (line {line})"
+            ),
+            Self::UnknownHostKey { host, fingerprint } => write!(
+                f,
+                "{host} is not in known_hosts and strict checking is on (offered {fingerprint})"
+            ),
         }
     }
 }
@@ -1683,11 +1888,19 @@ fn issue_kind_from_anyhow(err: &anyhow::Error) -> IssueKind {
     for cause in err.chain() {
         if let Some(conn_err) = cause.downcast_ref::<ConnectionError>() {
             return match conn_err {
-                ConnectionError::MissingHost | ConnectionError::MissingUsername => {
-                    IssueKind::Configuration
-                }
+                ConnectionError::MissingHost
+                | ConnectionError::MissingUsername
+                | ConnectionError::ProxyCommandSpawn { .. }
+                | ConnectionError::ProxyCommandUnsafeToken { .. }
+                | ConnectionError::UnsupportedAlgorithm { .. } => IssueKind::Configuration,
                 ConnectionError::AuthenticationFailed
                 | ConnectionError::DetachedTransferNeedsCredentials => IssueKind::Authentication,
+                ConnectionError::ProxyCommandExited { .. }
+                | ConnectionError::ProxyHandshake(_)
+                | ConnectionError::ConnectTimeout { .. } => IssueKind::Transport,
+                ConnectionError::HostKeyRevoked { .. }
+                | ConnectionError::HostKeyChanged { .. }
+                | ConnectionError::UnknownHostKey { .. } => IssueKind::HostKey,
             };
         }
         if let Some(russh_err) = cause.downcast_ref::<russh::Error>() {
@@ -1722,7 +1935,7 @@ fn issue_kind_from_anyhow(err: &anyhow::Error) -> IssueKind {
     IssueKind::Unknown
 }
 
-fn connection_status_from_error(err: &anyhow::Error) -> StatusUpdate {
+pub fn connection_status_from_error(err: &anyhow::Error) -> StatusUpdate {
     let kind = issue_kind_from_anyhow(err);
     let message = match kind {
         IssueKind::Authentication => {
@@ -1752,7 +1965,7 @@ fn transfer_issue_from_error(err: &anyhow::Error) -> TransferIssue {
     TransferIssue { kind, message }
 }
 
-fn send_ui_status(ui_tx: &Sender<UiMessage>, kind: IssueKind, message: impl Into<String>) {
+pub fn send_ui_status(ui_tx: &Sender<UiMessage>, kind: IssueKind, message: impl Into<String>) {
     send_message(
         ui_tx,
         UiMessage::Status(StatusUpdate {
@@ -3910,9 +4123,317 @@ async fn run_sftp_client_async(
     Ok(())
 }
 
+/// Per-connection options applied to every terminal channel of a connection group.
+#[derive(Debug, Clone, Default)]
+struct TerminalChannelOptions {
+    forward_agent: bool,
+    session_observer: Option<SessionObserver>,
+    /// Run with `exec` instead of starting a login shell.
+    remote_command: Option<String>,
+    startup_commands: Vec<String>,
+    startup_prompt_pattern: String,
+    terminal_type: String,
+    environment: Vec<(String, String)>,
+}
+
+impl TerminalChannelOptions {
+    fn from_settings(settings: &ConnectionSettings, observer: &SessionObserver) -> Self {
+        let remote_command = settings.remote_command.trim();
+        Self {
+            forward_agent: settings.forward_agent,
+            session_observer: Some(Arc::clone(observer)),
+            remote_command: (!remote_command.is_empty()).then(|| remote_command.to_string()),
+            startup_commands: settings
+                .startup_command_lines()
+                .map(str::to_string)
+                .collect(),
+            startup_prompt_pattern: settings.startup_prompt_pattern.trim().to_string(),
+            terminal_type: settings.terminal_type().to_string(),
+            environment: settings
+                .environment
+                .iter()
+                .filter(|var| var.is_complete())
+                .map(|var| (var.name.trim().to_string(), var.value.clone()))
+                .collect(),
+        }
+    }
+
+    /// Status shown when the channel goes away: a dead transport takes precedence.
+    fn channel_closed_status(&self) -> String {
+        self.session_observer
+            .as_ref()
+            .and_then(connection_lost_reason)
+            .unwrap_or_else(|| "Transport disconnected. The SSH channel closed.".to_string())
+    }
+
+    /// Whether the channel ended because the whole session went away.
+    fn session_lost(&self, session: &SshHandle) -> bool {
+        session.is_closed()
+            || self
+                .session_observer
+                .as_ref()
+                .and_then(connection_lost_reason)
+                .is_some()
+    }
+}
+
+/// An asciicast recording attached to one terminal client.
+pub struct SessionRecording {
+    pub writer: CastWriter,
+    path: PathBuf,
+    include_input: bool,
+    redact_hidden_input: bool,
+}
+
+impl SessionRecording {
+    pub fn start(
+        request: RecordingRequest,
+        cols: u16,
+        rows: u16,
+        term: &str,
+        ui_tx: &Sender<UiMessage>,
+        log_path: &str,
+    ) -> Option<Self> {
+        match CastWriter::create(&request.path, cols, rows, term, &request.title) {
+            Ok(writer) => {
+                let message = format!("Recording to {}.", request.path.display());
+                logger::log_line(log_path, &message);
+                send_ui_status(ui_tx, IssueKind::Info, message);
+                send_message(ui_tx, UiMessage::Recording(Some(request.path.clone())));
+                Some(Self {
+                    writer,
+                    path: request.path,
+                    include_input: request.include_input,
+                    redact_hidden_input: request.redact_hidden_input,
+                })
+            }
+            Err(err) => {
+                let message = format!(
+                    "Failed to start recording to {}: {err}",
+                    request.path.display()
+                );
+                logger::log_line(log_path, &message);
+                send_ui_status(ui_tx, issue_kind_from_io_kind(err.kind()), message);
+                send_message(ui_tx, UiMessage::Recording(None));
+                None
+            }
+        }
+    }
+
+    pub fn input(&mut self, data: &[u8], before_cursor: Option<&str>) -> std::io::Result<()> {
+        if !self.include_input {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(data);
+        if self.redact_hidden_input && before_cursor.is_some_and(looks_like_secret_prompt) {
+            self.writer.input(&redact_input(&text))
+        } else {
+            self.writer.input(&text)
+        }
+    }
+
+    /// Stops the recording if the last write failed, e.g. because the disk filled up.
+    pub fn check(
+        recording: &mut Option<Self>,
+        result: std::io::Result<()>,
+        ui_tx: &Sender<UiMessage>,
+        log_path: &str,
+    ) {
+        let Err(err) = result else {
+            return;
+        };
+        if let Some(active) = recording.take() {
+            let message = format!("Recording to {} stopped: {err}", active.path.display());
+            logger::log_line(log_path, &message);
+            send_ui_status(ui_tx, issue_kind_from_io_kind(err.kind()), message);
+            send_message(ui_tx, UiMessage::Recording(None));
+        }
+    }
+
+    pub fn stop(recording: &mut Option<Self>, ui_tx: &Sender<UiMessage>, log_path: &str) {
+        let Some(active) = recording.take() else {
+            return;
+        };
+        let path = active.path.display().to_string();
+        let (kind, message) = match active.writer.finish() {
+            Ok(events) => (
+                IssueKind::Info,
+                format!("Recording saved to {path} ({events} events)."),
+            ),
+            Err(err) => (
+                issue_kind_from_io_kind(err.kind()),
+                format!("Failed to finish recording {path}: {err}"),
+            ),
+        };
+        logger::log_line(log_path, &message);
+        send_ui_status(ui_tx, kind, message);
+        send_message(ui_tx, UiMessage::Recording(None));
+    }
+}
+
+/// The PTY's echo flag lives on the server and SSH never reports it, so a secret prompt left
+/// of the cursor stands in for "echo is off". A prompt ending in `:` or `?` counts when any
+/// whole word is a secret word; one without punctuation only when its last word is.
+fn looks_like_secret_prompt(before_cursor: &str) -> bool {
+    const SECRET_WORDS: [&str; 9] = [
+        "password",
+        "passphrase",
+        "passcode",
+        "pin",
+        "secret",
+        "token",
+        "code",
+        "otp",
+        "2fa",
+    ];
+    let prompt = before_cursor.trim_end().to_ascii_lowercase();
+    let (prompt, punctuated) = match prompt.strip_suffix([':', '?']) {
+        Some(stripped) => (stripped, true),
+        None => (prompt.as_str(), false),
+    };
+    if !punctuated && !prompt.ends_with(|c: char| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    let mut words = prompt
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty());
+    if punctuated {
+        words.any(|word| SECRET_WORDS.contains(&word))
+    } else {
+        words
+            .next_back()
+            .is_some_and(|word| SECRET_WORDS.contains(&word))
+    }
+}
+
+/// Masks printable characters but keeps control keys such as Enter and Backspace.
+fn redact_input(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { c } else { '*' })
+        .collect()
+}
+
+/// Sends one `env` request and waits for the server's verdict; `false` means it was refused.
+async fn request_channel_env(
+    channel: &mut russh::Channel<client::Msg>,
+    name: &str,
+    value: &str,
+) -> Result<bool> {
+    channel
+        .set_env(true, name, value)
+        .await
+        .with_context(|| format!("Failed to send environment variable {name}"))?;
+    let reply = tokio::time::timeout(CHANNEL_REQUEST_REPLY_TIMEOUT, async {
+        loop {
+            match channel.wait().await {
+                Some(ChannelMsg::Success) => return Some(true),
+                Some(ChannelMsg::Failure) => return Some(false),
+                Some(_) => {}
+                None => return None,
+            }
+        }
+    })
+    .await;
+    match reply {
+        Ok(Some(accepted)) => Ok(accepted),
+        Ok(None) => Err(anyhow!(
+            "SSH channel closed while setting environment variable {name}"
+        )),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Startup commands typed into a fresh shell, each once the prompt pattern (if any) shows up
+/// left of the cursor.
+struct StartupScript {
+    commands: VecDeque<String>,
+    prompt: Option<fancy_regex::Regex>,
+    deadline: Option<Instant>,
+    /// Set after typing a command: the old prompt stays on screen until output arrives.
+    awaiting_output: bool,
+}
+
+impl StartupScript {
+    fn new(commands: Vec<String>, prompt_pattern: &str) -> Result<Self> {
+        let prompt =
+            if prompt_pattern.is_empty() || commands.is_empty() {
+                None
+            } else {
+                Some(fancy_regex::Regex::new(prompt_pattern).with_context(|| {
+                    format!("Invalid startup prompt pattern {prompt_pattern:?}")
+                })?)
+            };
+        let deadline = prompt
+            .as_ref()
+            .map(|_| Instant::now() + STARTUP_PROMPT_TIMEOUT);
+        Ok(Self {
+            commands: commands.into(),
+            prompt,
+            deadline,
+            awaiting_output: false,
+        })
+    }
+
+    fn is_done(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// The next command to type, if the shell is ready for it.
+    fn next_command(&mut self, text_before_cursor: Option<&str>) -> Option<String> {
+        if let Some(prompt) = &self.prompt {
+            if self.awaiting_output {
+                return None;
+            }
+            let line = text_before_cursor?;
+            if !prompt.is_match(line).unwrap_or(false) {
+                return None;
+            }
+            self.deadline = Some(Instant::now() + STARTUP_PROMPT_TIMEOUT);
+            self.awaiting_output = true;
+        }
+        self.commands.pop_front()
+    }
+
+    fn saw_output(&mut self) {
+        self.awaiting_output = false;
+    }
+
+    /// Drops the remaining commands once the prompt has not shown up in time, so nothing is
+    /// typed into a password prompt or a program that took over the terminal.
+    fn expire(&mut self) -> usize {
+        let dropped = self.commands.len();
+        self.commands.clear();
+        self.deadline = None;
+        dropped
+    }
+}
+
+/// Status shown when an `exec` channel finishes.
+fn remote_command_exit_status(
+    exit_status: Option<u32>,
+    exit_signal: Option<&str>,
+) -> (IssueKind, String) {
+    match (exit_status, exit_signal) {
+        (_, Some(signal)) => (
+            IssueKind::Unknown,
+            format!("Command terminated by signal {signal}."),
+        ),
+        (Some(0), None) => (IssueKind::Info, "Command exited with status 0.".to_string()),
+        (Some(status), None) => (
+            IssueKind::Unknown,
+            format!("Command exited with status {status}."),
+        ),
+        (None, None) => (
+            IssueKind::Info,
+            "Command finished without reporting an exit status.".to_string(),
+        ),
+    }
+}
+
 async fn run_terminal_client_async(
     client_id: u64,
     session: Rc<SshHandle>,
+    options: TerminalChannelOptions,
     ui_tx: Sender<UiMessage>,
     mut worker_rx: UnboundedReceiver<TerminalClientCommand>,
     scrollback_len: usize,
@@ -3927,27 +4448,79 @@ async fn run_terminal_client_async(
         .await
         .context("Failed to open SSH channel")?;
 
+    if options.forward_agent {
+        logger::log_line(
+            &log_path,
+            &format!("Terminal client {client_id} requesting agent forwarding."),
+        );
+        if let Err(err) = channel.agent_forward(false).await {
+            logger::log_line(
+                &log_path,
+                &format!("Agent forwarding request failed: {err}"),
+            );
+        }
+    }
+
     logger::log_line(
         &log_path,
         &format!("Terminal client {client_id} requesting PTY."),
     );
+    let term = options.terminal_type.as_str();
     channel
-        .request_pty(false, "xterm-256color", 80, 24, 0, 0, &[])
+        .request_pty(false, term, 80, 24, 0, 0, &[])
         .await
         .context("Failed to request PTY")?;
 
-    let _ = channel.set_env(false, "TERM", "xterm-256color").await;
+    let _ = channel.set_env(false, "TERM", term).await;
 
-    logger::log_line(
-        &log_path,
-        &format!("Terminal client {client_id} starting shell."),
-    );
-    channel
-        .request_shell(true)
-        .await
-        .context("Failed to start shell")?;
+    let mut refused_env: Vec<&str> = Vec::new();
+    for (name, value) in &options.environment {
+        if !request_channel_env(&mut channel, name, value).await? {
+            refused_env.push(name);
+        }
+    }
+    if !refused_env.is_empty() {
+        logger::log_line(
+            &log_path,
+            &format!(
+                "Terminal client {client_id} environment refused by server: {}",
+                refused_env.join(", ")
+            ),
+        );
+    }
+
+    if let Some(command) = options.remote_command.as_deref() {
+        logger::log_line(
+            &log_path,
+            &format!("Terminal client {client_id} running command: {command}"),
+        );
+        channel
+            .exec(true, command)
+            .await
+            .context("Failed to start remote command")?;
+    } else {
+        logger::log_line(
+            &log_path,
+            &format!("Terminal client {client_id} starting shell."),
+        );
+        channel
+            .request_shell(true)
+            .await
+            .context("Failed to start shell")?;
+    }
 
-    send_ui_status(&ui_tx, IssueKind::Info, "Connected successfully.");
+    if refused_env.is_empty() {
+        send_ui_status(&ui_tx, IssueKind::Info, "Connected successfully.");
+    } else {
+        send_ui_status(
+            &ui_tx,
+            IssueKind::Configuration,
+            format!(
+                "Connected, but the server refused {} (check AcceptEnv in sshd_config).",
+                refused_env.join(", ")
+            ),
+        );
+    }
     send_message(&ui_tx, UiMessage::Connected(true));
     logger::log_line(
         &log_path,
@@ -3970,7 +4543,44 @@ async fn run_terminal_client_async(
 
     let mut writer = channel.make_writer();
 
+    let startup_commands = if options.remote_command.is_some() {
+        Vec::new()
+    } else {
+        options.startup_commands.clone()
+    };
+    let mut startup = match StartupScript::new(startup_commands, &options.startup_prompt_pattern) {
+        Ok(script) => script,
+        Err(err) => {
+            logger::log_line(&log_path, &format!("{err:#}"));
+            send_ui_status(
+                &ui_tx,
+                IssueKind::Configuration,
+                format!("Startup commands skipped: {err:#}"),
+            );
+            StartupScript::new(Vec::new(), "")?
+        }
+    };
+    let mut exit_status: Option<u32> = None;
+    let mut exit_signal: Option<String> = None;
+    let mut recording: Option<SessionRecording> = None;
+
     loop {
+        while !startup.is_done() {
+            let before_cursor = parser.screen().text_before_cursor();
+            let Some(command) = startup.next_command(before_cursor.as_deref()) else {
+                break;
+            };
+            logger::log_line(
+                &log_path,
+                &format!("Terminal client {client_id} startup command: {command}"),
+            );
+            writer
+                .write_all(format!("{command}\r").as_bytes())
+                .await
+                .context("Channel write failed")?;
+            writer.flush().await.context("Channel flush failed")?;
+        }
+
         let rate_window_elapsed = screen_rate_window_started.elapsed();
         if rate_window_elapsed >= TERM_SCREEN_RATE_WINDOW {
             screen_emit_interval =
@@ -3996,6 +4606,7 @@ async fn run_terminal_client_async(
         }
 
         let mut disconnected = false;
+        let startup_deadline = startup.deadline.filter(|_| !startup.is_done());
         tokio::select! {
             command = worker_rx.recv() => {
                 match command {
@@ -4005,6 +4616,11 @@ async fn run_terminal_client_async(
                             .await
                             .context("Channel write failed")?;
                         writer.flush().await.context("Channel flush failed")?;
+                        if let Some(active) = recording.as_mut() {
+                            let before_cursor = parser.screen().text_before_cursor();
+                            let result = active.input(&data, before_cursor.as_deref());
+                            SessionRecording::check(&mut recording, result, &ui_tx, &log_path);
+                        }
                     }
                     Some(TerminalClientCommand::Resize {
                         rows,
@@ -4019,6 +4635,25 @@ async fn run_terminal_client_async(
                         parser.set_size(rows, cols);
                         screen_dirty = true;
                         scrollback_dirty = true;
+                        if let Some(active) = recording.as_mut() {
+                            let result = active.writer.resize(cols, rows);
+                            SessionRecording::check(&mut recording, result, &ui_tx, &log_path);
+                        }
+                    }
+                    Some(TerminalClientCommand::StartRecording(request)) => {
+                        SessionRecording::stop(&mut recording, &ui_tx, &log_path);
+                        let (rows, cols) = parser.screen().size();
+                        recording = SessionRecording::start(
+                            request,
+                            cols,
+                            rows,
+                            &options.terminal_type,
+                            &ui_tx,
+                            &log_path,
+                        );
+                    }
+                    Some(TerminalClientCommand::StopRecording) => {
+                        SessionRecording::stop(&mut recording, &ui_tx, &log_path);
                     }
                     Some(TerminalClientCommand::SetScrollback(rows)) => {
                         parser.set_scrollback(rows);
@@ -4033,6 +4668,10 @@ async fn run_terminal_client_async(
             message = channel.wait() => {
                 match message {
                     Some(ChannelMsg::Data { data }) => {
+                        if let Some(active) = recording.as_mut() {
+                            let result = active.writer.output(&data);
+                            SessionRecording::check(&mut recording, result, &ui_tx, &log_path);
+                        }
                         process_with_query_responses(
                             &mut parser,
                             &mut scanner,
@@ -4040,6 +4679,7 @@ async fn run_terminal_client_async(
                             data.as_ref(),
                         )
                         .await?;
+                        startup.saw_output();
                         send_clipboard_writes(&ui_tx, &mut parser);
                         screen_rate_window_bytes =
                             screen_rate_window_bytes.saturating_add(data.len() as u64);
@@ -4047,6 +4687,10 @@ async fn run_terminal_client_async(
                         scrollback_dirty = true;
                     }
                     Some(ChannelMsg::ExtendedData { data, .. }) => {
+                        if let Some(active) = recording.as_mut() {
+                            let result = active.writer.output(&data);
+                            SessionRecording::check(&mut recording, result, &ui_tx, &log_path);
+                        }
                         process_with_query_responses(
                             &mut parser,
                             &mut scanner,
@@ -4060,16 +4704,43 @@ async fn run_terminal_client_async(
                         screen_dirty = true;
                         scrollback_dirty = true;
                     }
+                    Some(ChannelMsg::ExitStatus { exit_status: status }) => {
+                        exit_status = Some(status);
+                    }
+                    Some(ChannelMsg::ExitSignal { signal_name, .. }) => {
+                        exit_signal = Some(format!("{signal_name:?}"));
+                    }
+                    // A command's exit status may follow its EOF; wait for the close, or for
+                    // the stream to end when the server never sends one.
+                    Some(ChannelMsg::Eof) if options.remote_command.is_some() => {}
+                    Some(ChannelMsg::Close) | None
+                        if options.remote_command.is_some() && !options.session_lost(&session) =>
+                    {
+                        let (kind, status) =
+                            remote_command_exit_status(exit_status, exit_signal.as_deref());
+                        logger::log_line(
+                            &log_path,
+                            &format!("Terminal client {client_id} command finished: {status}"),
+                        );
+                        SessionRecording::stop(&mut recording, &ui_tx, &log_path);
+                        send_ui_status(&ui_tx, kind, status);
+                        send_message(&ui_tx, UiMessage::Connected(false));
+                        return Ok(());
+                    }
                     Some(ChannelMsg::Eof) | Some(ChannelMsg::Close) => {
                         logger::log_line(
                             &log_path,
                             &format!("Terminal client {client_id} channel EOF."),
                         );
+                        SessionRecording::stop(&mut recording, &ui_tx, &log_path);
                         send_ui_status(
                             &ui_tx,
                             IssueKind::Transport,
-                            "Transport disconnected. The SSH channel closed.",
+                            options.channel_closed_status(),
                         );
+                        if options.session_lost(&session) {
+                            send_message(&ui_tx, UiMessage::ConnectionLost);
+                        }
                         send_message(&ui_tx, UiMessage::Connected(false));
                         return Ok(());
                     }
@@ -4079,16 +4750,37 @@ async fn run_terminal_client_async(
                             &log_path,
                             &format!("Terminal client {client_id} channel closed."),
                         );
+                        SessionRecording::stop(&mut recording, &ui_tx, &log_path);
                         send_ui_status(
                             &ui_tx,
                             IssueKind::Transport,
-                            "Transport disconnected. The SSH channel closed.",
+                            options.channel_closed_status(),
                         );
+                        if options.session_lost(&session) {
+                            send_message(&ui_tx, UiMessage::ConnectionLost);
+                        }
                         send_message(&ui_tx, UiMessage::Connected(false));
                         return Ok(());
                     }
                 }
             }
+            _ = tokio::time::sleep_until(startup_deadline.unwrap_or_else(Instant::now).into()), if startup_deadline.is_some() => {
+                let dropped = startup.expire();
+                logger::log_line(
+                    &log_path,
+                    &format!(
+                        "Terminal client {client_id} dropped {dropped} startup command(s): prompt not seen."
+                    ),
+                );
+                send_ui_status(
+                    &ui_tx,
+                    IssueKind::Configuration,
+                    format!(
+                        "Startup commands stopped: prompt not seen within {}s.",
+                        STARTUP_PROMPT_TIMEOUT.as_secs()
+                    ),
+                );
+            }
             _ = tokio::time::sleep(screen_emit_interval.saturating_sub(last_screen_emit.elapsed())), if screen_dirty => {
                 send_screen(&ui_tx, &mut parser);
                 screen_dirty = false;
@@ -4103,6 +4795,7 @@ async fn run_terminal_client_async(
             );
             let _ = channel.eof().await;
             let _ = channel.close().await;
+            SessionRecording::stop(&mut recording, &ui_tx, &log_path);
             send_ui_status(&ui_tx, IssueKind::Info, "Disconnected.");
             send_message(&ui_tx, UiMessage::Connected(false));
             return Ok(());
@@ -4135,6 +4828,7 @@ fn spawn_terminal_client(
     active_clients: &mut Vec<ActiveTerminalClient>,
     client_id: u64,
     session: Rc<SshHandle>,
+    options: TerminalChannelOptions,
     ui_tx: Sender<UiMessage>,
     scrollback_len: usize,
     log_path: &str,
@@ -4151,6 +4845,7 @@ fn spawn_terminal_client(
         if let Err(err) = run_terminal_client_async(
             client_id,
             session,
+            options,
             ui_tx.clone(),
             command_rx,
             scrollback_len,
@@ -4182,6 +4877,7 @@ fn request_auth_responses(
     username: &str,
     instructions: &str,
     prompts: Vec<AuthPromptItem>,
+    key_path: Option<&str>,
     log_path: &str,
 ) -> Result<Vec<String>> {
     if prompts.is_empty() {
@@ -4205,6 +4901,7 @@ fn request_auth_responses(
         UiMessage::AuthPrompt(AuthPrompt {
             instructions: instructions.to_string(),
             prompts: prompts.clone(),
+            key_path: key_path.map(str::to_string),
         }),
     );
 
@@ -4260,38 +4957,162 @@ fn error_mentions_passphrase(err: &anyhow::Error) -> bool {
         || s.contains("private key")
 }
 
-async fn authenticate_with_private_key(
-    session: &mut SshHandle,
-    username: &str,
-    private_key: &Path,
-    passphrase: Option<&str>,
-    best_rsa_hash: Option<keys::HashAlg>,
-) -> Result<AuthResult> {
-    let key_pair = load_secret_key(private_key, passphrase)
-        .with_context(|| format!("Unable to load private key {}", private_key.display()))?;
-    let key = PrivateKeyWithHashAlg::new(Arc::new(key_pair), best_rsa_hash);
+/// Certificate to present with `settings.private_key_path`: the configured path, else the
+/// OpenSSH `<key>-cert.pub` sidecar when it exists.
+pub fn user_certificate_path(settings: &ConnectionSettings) -> Option<PathBuf> {
+    identity_certificate_path(settings, settings.private_key_path.trim())
+}
 
-    session
-        .authenticate_publickey(username, key)
-        .await
-        .context("SSH public key authentication request failed")
+/// The configured certificate belongs to the primary key; other identities only use sidecars.
+fn identity_certificate_path(settings: &ConnectionSettings, key_path: &str) -> Option<PathBuf> {
+    let explicit = settings.certificate_path.trim();
+    if !explicit.is_empty() && key_path == settings.private_key_path.trim() {
+        return Some(PathBuf::from(explicit));
+    }
+    if key_path.is_empty() {
+        return None;
+    }
+    let sidecar = PathBuf::from(format!("{key_path}-cert.pub"));
+    sidecar.exists().then_some(sidecar)
 }
 
-async fn authenticate_keyboard_interactive(
-    session: &mut SshHandle,
-    username: &str,
-    ui_tx: &Sender<UiMessage>,
-    worker_rx: &Receiver<WorkerMessage>,
-    cached_password: Option<&str>,
-    log_path: &str,
-) -> Result<AuthResult> {
-    let mut reply = session
-        .authenticate_keyboard_interactive_start(username, None::<String>)
-        .await
-        .context("SSH keyboard-interactive request failed")?;
+/// Principals and validity window of an OpenSSH user certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateSummary {
+    pub key_id: String,
+    pub principals: Vec<String>,
+    pub valid_after: u64,
+    pub valid_before: u64,
+}
 
-    loop {
-        match reply {
+impl CertificateSummary {
+    pub fn read(path: &Path) -> Result<Self> {
+        let cert = keys::load_openssh_certificate(path)
+            .with_context(|| format!("Unable to read certificate {}", path.display()))?;
+        Ok(Self::from_certificate(&cert))
+    }
+
+    fn from_certificate(cert: &keys::Certificate) -> Self {
+        Self {
+            key_id: cert.key_id().to_string(),
+            principals: cert.valid_principals().to_vec(),
+            valid_after: cert.valid_after(),
+            valid_before: cert.valid_before(),
+        }
+    }
+
+    pub fn is_expired(&self, now_unix: u64) -> bool {
+        now_unix >= self.valid_before
+    }
+
+    pub fn is_not_yet_valid(&self, now_unix: u64) -> bool {
+        now_unix < self.valid_after
+    }
+
+    /// Principals as shown in settings; an empty list means the certificate names none.
+    pub fn principals_label(&self) -> String {
+        if self.principals.is_empty() {
+            "(any)".to_string()
+        } else {
+            self.principals.join(", ")
+        }
+    }
+}
+
+/// Loads the certificate for `settings`, logging and skipping one that cannot be read so the
+/// bare key is still offered.
+fn load_user_certificate(
+    settings: &ConnectionSettings,
+    key_path: &str,
+    log_path: &str,
+) -> Option<keys::Certificate> {
+    let path = identity_certificate_path(settings, key_path)?;
+    match keys::load_openssh_certificate(&path) {
+        Ok(cert) => {
+            let summary = CertificateSummary::from_certificate(&cert);
+            let now = std::time::SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            logger::log_line(
+                log_path,
+                &format!(
+                    "Using OpenSSH certificate {} (key id {:?}, principals {}).",
+                    path.display(),
+                    summary.key_id,
+                    summary.principals_label()
+                ),
+            );
+            if summary.is_expired(now) {
+                logger::log_line(
+                    log_path,
+                    "Certificate has expired; the server will likely reject it.",
+                );
+            }
+            Some(cert)
+        }
+        Err(err) => {
+            logger::log_line(
+                log_path,
+                &format!("Ignoring certificate {}: {err}", path.display()),
+            );
+            None
+        }
+    }
+}
+
+async fn authenticate_with_private_key(
+    session: &mut SshHandle,
+    username: &str,
+    private_key: &Path,
+    passphrase: Option<&str>,
+    certificate: Option<&keys::Certificate>,
+    best_rsa_hash: Option<keys::HashAlg>,
+) -> Result<AuthResult> {
+    let key_pair = load_secret_key(private_key, passphrase)
+        .with_context(|| format!("Unable to load private key {}", private_key.display()))?;
+    let key_pair = Arc::new(key_pair);
+
+    if let Some(cert) = certificate {
+        let result = session
+            .authenticate_openssh_cert(username, Arc::clone(&key_pair), cert.clone())
+            .await
+            .context("SSH certificate authentication request failed")?;
+        // As OpenSSH does, fall back to the plain key when the certificate is refused outright.
+        if !matches!(
+            result,
+            AuthResult::Failure {
+                partial_success: false,
+                ..
+            }
+        ) {
+            return Ok(result);
+        }
+    }
+
+    let key = PrivateKeyWithHashAlg::new(key_pair, best_rsa_hash);
+
+    session
+        .authenticate_publickey(username, key)
+        .await
+        .context("SSH public key authentication request failed")
+}
+
+async fn authenticate_keyboard_interactive(
+    session: &mut SshHandle,
+    username: &str,
+    ui_tx: &Sender<UiMessage>,
+    worker_rx: &Receiver<WorkerMessage>,
+    cached_password: Option<&str>,
+    log_path: &str,
+) -> Result<AuthResult> {
+    let mut reply = session
+        .authenticate_keyboard_interactive_start(username, None::<String>)
+        .await
+        .context("SSH keyboard-interactive request failed")?;
+
+    loop {
+        match reply {
             KeyboardInteractiveAuthResponse::Success => return Ok(AuthResult::Success),
             KeyboardInteractiveAuthResponse::Failure {
                 remaining_methods,
@@ -4320,6 +5141,7 @@ async fn authenticate_keyboard_interactive(
                             username,
                             &instructions,
                             items,
+                            None,
                             log_path,
                         )?
                     }
@@ -4330,6 +5152,7 @@ async fn authenticate_keyboard_interactive(
                         username,
                         &instructions,
                         items,
+                        None,
                         log_path,
                     )?
                 };
@@ -4378,6 +5201,39 @@ async fn authenticate_keyboard_interactive_with_password(
     }
 }
 
+/// Named pipe of the Windows OpenSSH agent service, used when `SSH_AUTH_SOCK` is unset.
+#[cfg(windows)]
+const WINDOWS_AGENT_PIPE: &str = r"\\.\pipe\openssh-ssh-agent";
+
+/// Raw byte stream to the local ssh-agent, used to proxy forwarded agent channels.
+async fn connect_local_agent_stream() -> Result<BoxedTransport> {
+    #[cfg(unix)]
+    {
+        let path = std::env::var("SSH_AUTH_SOCK").context("SSH_AUTH_SOCK is not set")?;
+        let stream = tokio::net::UnixStream::connect(&path)
+            .await
+            .with_context(|| format!("Failed to connect to ssh-agent at {path}"))?;
+        Ok(Box::new(stream))
+    }
+
+    #[cfg(windows)]
+    {
+        let path =
+            std::env::var("SSH_AUTH_SOCK").unwrap_or_else(|_| WINDOWS_AGENT_PIPE.to_string());
+        let pipe = tokio::net::windows::named_pipe::ClientOptions::new()
+            .open(&path)
+            .with_context(|| format!("Failed to connect to ssh-agent at {path}"))?;
+        Ok(Box::new(pipe))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        Err(anyhow!(
+            "ssh-agent forwarding is unsupported on this platform"
+        ))
+    }
+}
+
 async fn authenticate_via_agent(
     session: &mut SshHandle,
     username: &str,
@@ -4391,8 +5247,7 @@ async fn authenticate_via_agent(
         if let Ok(sock) = std::env::var("SSH_AUTH_SOCK") {
             keys::agent::client::AgentClient::connect_named_pipe(sock).await?
         } else {
-            keys::agent::client::AgentClient::connect_named_pipe(r"\\.\pipe\openssh-ssh-agent")
-                .await?
+            keys::agent::client::AgentClient::connect_named_pipe(WINDOWS_AGENT_PIPE).await?
         }
     };
 
@@ -4448,46 +5303,13 @@ async fn authenticate_via_agent(
     Ok(last_failure)
 }
 
-async fn open_authenticated_session_for_transfer(
+async fn authenticate_interactive(
+    session: &mut SshHandle,
     settings: &ConnectionSettings,
+    ui_tx: &Sender<UiMessage>,
+    worker_rx: &Receiver<WorkerMessage>,
     log_path: &str,
-) -> Result<SshHandle> {
-    if settings.host.trim().is_empty() {
-        return Err(ConnectionError::MissingHost.into());
-    }
-    if settings.username.trim().is_empty() {
-        return Err(ConnectionError::MissingUsername.into());
-    }
-
-    let host = settings.host.trim().to_string();
-    let port = settings.port;
-    let addr = format!("{host}:{port}");
-    logger::log_line(
-        log_path,
-        &format!("Detached transfer connecting TCP to {addr}."),
-    );
-    let tcp = tokio::net::TcpStream::connect(&addr)
-        .await
-        .with_context(|| format!("Failed to connect to {addr}"))?;
-    let _ = tcp.set_nodelay(true);
-
-    let config = client::Config {
-        inactivity_timeout: None,
-        keepalive_interval: Some(Duration::from_secs(20)),
-        keepalive_max: 0,
-        ..Default::default()
-    };
-    let config = Arc::new(config);
-
-    logger::log_line(log_path, "Creating detached transfer SSH session.");
-    let mut session = client::connect_stream(
-        config,
-        tcp,
-        KnownHostsClient::non_interactive(host, port, log_path.to_string()),
-    )
-    .await
-    .context("SSH handshake failed")?;
-
+) -> Result<()> {
     let username = settings.username.trim();
     let mut remaining_methods = MethodSet::empty();
     let mut authenticated = false;
@@ -4497,16 +5319,29 @@ async fn open_authenticated_session_for_transfer(
         .await
         .context("Failed to query server auth methods")?;
     match probe {
-        AuthResult::Success => authenticated = true,
+        AuthResult::Success => {
+            authenticated = true;
+        }
         AuthResult::Failure {
             remaining_methods: methods,
             ..
-        } => remaining_methods = methods,
+        } => {
+            remaining_methods = methods;
+        }
     }
 
+    logger::log_line(
+        log_path,
+        &format!(
+            "Server auth methods: {}",
+            method_set_to_csv(&remaining_methods)
+        ),
+    );
+
     let mut supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
     let mut supports_pass = supports_method(&remaining_methods, MethodKind::Password);
     let mut supports_pubkey = supports_method(&remaining_methods, MethodKind::PublicKey);
+    let mut pubkey_partially_accepted = false;
 
     let best_rsa_hash = if supports_pubkey {
         match session.best_supported_rsa_hash().await {
@@ -4514,7 +5349,7 @@ async fn open_authenticated_session_for_transfer(
             Err(err) => {
                 logger::log_line(
                     log_path,
-                    &format!("Unable to query best RSA hash for detached transfer session: {err}"),
+                    &format!("Unable to query best RSA hash from server: {err}"),
                 );
                 None
             }
@@ -4523,47 +5358,393 @@ async fn open_authenticated_session_for_transfer(
         None
     };
 
-    if !authenticated && supports_pubkey && !settings.private_key_path.trim().is_empty() {
-        let private_key = Path::new(settings.private_key_path.trim());
-        if private_key.exists() {
-            let saved_passphrase = if settings.key_passphrase.trim().is_empty() {
+    let identities = settings.identity_files();
+    for (index, identity) in identities.iter().enumerate() {
+        if authenticated || !supports_pubkey {
+            break;
+        }
+        let key_path = identity.private_key_path.trim();
+        logger::log_line(
+            log_path,
+            &format!(
+                "Authenticating via private key {}/{}: {key_path}",
+                index + 1,
+                identities.len()
+            ),
+        );
+
+        let private_key = Path::new(key_path);
+        if !private_key.exists() {
+            logger::log_line(log_path, "Private key path does not exist.");
+        } else {
+            let sidecar_pub = Path::new(&format!("{key_path}.pub")).exists();
+            if sidecar_pub {
+                logger::log_line(
+                    log_path,
+                    "Sidecar .pub file detected; russh derives public key from the private key.",
+                );
+            }
+
+            if let Some(fmt) = detect_private_key_format(private_key) {
+                logger::log_line(log_path, &format!("Detected private key format: {fmt}"));
+            } else {
+                logger::log_line(
+                    log_path,
+                    "Could not determine private key format from file header.",
+                );
+            }
+
+            let saved_passphrase = if identity.key_passphrase.trim().is_empty() {
                 None
             } else {
-                Some(settings.key_passphrase.as_str())
+                Some(identity.key_passphrase.as_str())
             };
-            if let Ok(auth_result) = authenticate_with_private_key(
-                &mut session,
+            let certificate = load_user_certificate(settings, key_path, log_path);
+
+            let mut key_auth_result = match authenticate_with_private_key(
+                session,
                 username,
                 private_key,
                 saved_passphrase,
+                certificate.as_ref(),
                 best_rsa_hash,
             )
             .await
             {
-                let _ = apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
-                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
-                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
-                supports_pubkey = supports_method(&remaining_methods, MethodKind::PublicKey);
-            }
-        }
-    }
+                Ok(result) => Some(result),
+                Err(err) => {
+                    logger::log_line(log_path, &format!("Private key auth failed: {err}"));
+                    if error_mentions_passphrase(&err) {
+                        logger::log_line(
+                            log_path,
+                            "Private key may need a passphrase; prompting user.",
+                        );
+                        match request_auth_responses(
+                            ui_tx,
+                            worker_rx,
+                            username,
+                            &format!(
+                                "Private key authentication may require a passphrase for {key_path}."
+                            ),
+                            vec![AuthPromptItem {
+                                text: "Key passphrase (optional):".to_string(),
+                                echo: false,
+                            }],
+                            Some(key_path),
+                            log_path,
+                        ) {
+                            Ok(responses) => {
+                                let pw = responses.first().cloned().unwrap_or_default();
+                                if !pw.is_empty() {
+                                    match authenticate_with_private_key(
+                                        session,
+                                        username,
+                                        private_key,
+                                        Some(&pw),
+                                        certificate.as_ref(),
+                                        best_rsa_hash,
+                                    )
+                                    .await
+                                    {
+                                        Ok(result) => Some(result),
+                                        Err(err) => {
+                                            logger::log_line(
+                                                log_path,
+                                                &format!(
+                                                    "Private key auth (with passphrase) failed: {err}"
+                                                ),
+                                            );
+                                            None
+                                        }
+                                    }
+                                } else {
+                                    None
+                                }
+                            }
+                            Err(err) => {
+                                logger::log_line(log_path, &format!("Auth prompt failed: {err}"));
+                                None
+                            }
+                        }
+                    } else {
+                        if saved_passphrase.is_some() {
+                            logger::log_line(
+                                log_path,
+                                "Skipping passphrase re-prompt because a saved passphrase already exists and the error was not passphrase-related.",
+                            );
+                        }
+                        None
+                    }
+                }
+            };
 
-    if !authenticated && supports_pubkey {
-        if let Ok(auth_result) = authenticate_via_agent(&mut session, username, best_rsa_hash).await
-        {
-            let _ = apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
-            supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
-            supports_pass = supports_method(&remaining_methods, MethodKind::Password);
-        }
-    }
+            if let Some(auth_result) = key_auth_result.take() {
+                let partial =
+                    apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
 
-    if !authenticated && supports_kbd && !settings.password.is_empty() {
-        if let Ok(auth_result) = authenticate_keyboard_interactive_with_password(
-            &mut session,
-            username,
-            &settings.password,
-        )
+                if authenticated {
+                    logger::log_line(log_path, "Private key authentication succeeded.");
+                } else if partial {
+                    pubkey_partially_accepted = true;
+                    logger::log_line(
+                        log_path,
+                        "Private key accepted, but server requires additional authentication.",
+                    );
+                    send_ui_status(
+                        ui_tx,
+                        IssueKind::Authentication,
+                        "Additional authentication required (server policy).",
+                    );
+                } else {
+                    logger::log_line(
+                        log_path,
+                        "Private key auth failed: Username/PublicKey combination invalid",
+                    );
+                }
+
+                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
+                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
+                supports_pubkey = supports_method(&remaining_methods, MethodKind::PublicKey);
+            }
+        }
+    }
+
+    if !authenticated && supports_pubkey {
+        logger::log_line(log_path, "Authenticating via ssh-agent.");
+        match authenticate_via_agent(session, username, best_rsa_hash).await {
+            Ok(auth_result) => {
+                let partial =
+                    apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+
+                if authenticated {
+                    logger::log_line(log_path, "ssh-agent authentication succeeded.");
+                } else if partial {
+                    pubkey_partially_accepted = true;
+                    logger::log_line(
+                        log_path,
+                        "ssh-agent key accepted, but server requires additional authentication.",
+                    );
+                    send_ui_status(
+                        ui_tx,
+                        IssueKind::Authentication,
+                        "Additional authentication required (server policy).",
+                    );
+                } else {
+                    logger::log_line(log_path, "ssh-agent auth failed.");
+                }
+
+                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
+                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
+            }
+            Err(err) => {
+                logger::log_line(log_path, &format!("ssh-agent auth failed: {err}"));
+            }
+        }
+    }
+
+    if !authenticated && supports_kbd {
+        logger::log_line(log_path, "Authenticating via keyboard-interactive.");
+        match authenticate_keyboard_interactive(
+            session,
+            username,
+            ui_tx,
+            worker_rx,
+            if settings.password.is_empty() {
+                None
+            } else {
+                Some(settings.password.as_str())
+            },
+            log_path,
+        )
+        .await
+        {
+            Ok(auth_result) => {
+                let partial =
+                    apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+
+                if partial && !authenticated {
+                    logger::log_line(
+                        log_path,
+                        "Keyboard-interactive accepted, but additional auth is required.",
+                    );
+                } else if !authenticated {
+                    logger::log_line(log_path, "Keyboard-interactive auth failed.");
+                }
+
+                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
+                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
+            }
+            Err(err) => {
+                logger::log_line(
+                    log_path,
+                    &format!("Keyboard-interactive auth failed: {err}"),
+                );
+            }
+        }
+    }
+
+    if !authenticated && !settings.password.is_empty() && supports_pass {
+        if pubkey_partially_accepted {
+            logger::log_line(
+                log_path,
+                "Falling back to explicit password auth after pubkey partial authentication.",
+            );
+        } else {
+            logger::log_line(log_path, "Authenticating via password.");
+        }
+
+        match session
+            .authenticate_password(username, settings.password.clone())
+            .await
+        {
+            Ok(auth_result) => {
+                let partial =
+                    apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+
+                if partial && !authenticated {
+                    logger::log_line(
+                        log_path,
+                        "Password accepted, but additional auth is required.",
+                    );
+                } else if !authenticated {
+                    logger::log_line(log_path, "Password auth failed.");
+                }
+
+                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
+                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
+            }
+            Err(err) => {
+                logger::log_line(log_path, &format!("Password auth failed: {err}"));
+            }
+        }
+    }
+
+    // Last resort: if the server supports password but not keyboard-interactive, we must
+    // ask the user explicitly for a password (the server cannot "prompt" in the shell).
+    if !authenticated && supports_pass && !supports_kbd {
+        logger::log_line(log_path, "Password needed; prompting user.");
+        let pw = loop {
+            let responses = request_auth_responses(
+                ui_tx,
+                worker_rx,
+                username,
+                "Server requested password authentication.",
+                vec![AuthPromptItem {
+                    text: "Password:".to_string(),
+                    echo: false,
+                }],
+                None,
+                log_path,
+            )?;
+            let pw = responses.first().cloned().unwrap_or_default();
+            if !pw.is_empty() {
+                break pw;
+            }
+            logger::log_line(log_path, "Empty password submitted; reprompting.");
+            send_ui_status(ui_tx, IssueKind::Authentication, "Password required.");
+        };
+
+        let auth_result = session
+            .authenticate_password(username, pw)
+            .await
+            .context("SSH password authentication failed")?;
+        let _ = apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+    }
+
+    if !authenticated {
+        return Err(ConnectionError::AuthenticationFailed.into());
+    }
+
+    Ok(())
+}
+
+async fn authenticate_non_interactive(
+    session: &mut SshHandle,
+    settings: &ConnectionSettings,
+    log_path: &str,
+) -> Result<()> {
+    let username = settings.username.trim();
+    let mut remaining_methods = MethodSet::empty();
+    let mut authenticated = false;
+
+    let probe = session
+        .authenticate_none(username)
         .await
+        .context("Failed to query server auth methods")?;
+    match probe {
+        AuthResult::Success => authenticated = true,
+        AuthResult::Failure {
+            remaining_methods: methods,
+            ..
+        } => remaining_methods = methods,
+    }
+
+    let mut supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
+    let mut supports_pass = supports_method(&remaining_methods, MethodKind::Password);
+    let mut supports_pubkey = supports_method(&remaining_methods, MethodKind::PublicKey);
+
+    let best_rsa_hash = if supports_pubkey {
+        match session.best_supported_rsa_hash().await {
+            Ok(v) => v.flatten(),
+            Err(err) => {
+                logger::log_line(
+                    log_path,
+                    &format!("Unable to query best RSA hash for detached transfer session: {err}"),
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    for identity in settings.identity_files() {
+        if authenticated || !supports_pubkey {
+            break;
+        }
+        let key_path = identity.private_key_path.trim();
+        logger::log_line(
+            log_path,
+            &format!("Detached session trying private key: {key_path}"),
+        );
+        let private_key = Path::new(key_path);
+        if private_key.exists() {
+            let saved_passphrase = if identity.key_passphrase.trim().is_empty() {
+                None
+            } else {
+                Some(identity.key_passphrase.as_str())
+            };
+            let certificate = load_user_certificate(settings, key_path, log_path);
+            if let Ok(auth_result) = authenticate_with_private_key(
+                session,
+                username,
+                private_key,
+                saved_passphrase,
+                certificate.as_ref(),
+                best_rsa_hash,
+            )
+            .await
+            {
+                let _ = apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
+                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
+                supports_pubkey = supports_method(&remaining_methods, MethodKind::PublicKey);
+            }
+        }
+    }
+
+    if !authenticated && supports_pubkey {
+        if let Ok(auth_result) = authenticate_via_agent(session, username, best_rsa_hash).await {
+            let _ = apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+            supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
+            supports_pass = supports_method(&remaining_methods, MethodKind::Password);
+        }
+    }
+
+    if !authenticated && supports_kbd && !settings.password.is_empty() {
+        if let Ok(auth_result) =
+            authenticate_keyboard_interactive_with_password(session, username, &settings.password)
+                .await
         {
             let _ = apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
             supports_pass = supports_method(&remaining_methods, MethodKind::Password);
@@ -4582,887 +5763,2589 @@ async fn open_authenticated_session_for_transfer(
         return Err(ConnectionError::DetachedTransferNeedsCredentials.into());
     }
 
-    Ok(session)
+    Ok(())
 }
 
-type SshHandle = client::Handle<KnownHostsClient>;
+/// Byte stream an SSH handshake runs over: a TCP socket, a proxy command or a jump-host tunnel.
+pub trait TransportStream: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send {}
 
-struct ActiveTerminalClient {
-    client_id: u64,
-    ui_tx: Sender<UiMessage>,
-    command_tx: UnboundedSender<TerminalClientCommand>,
-    abort_handle: tokio::task::AbortHandle,
-}
+impl<T> TransportStream for T where T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send {}
 
-fn abort_terminal_client(client: ActiveTerminalClient) {
-    if client.abort_handle.is_finished() {
-        return;
+pub type BoxedTransport = Box<dyn TransportStream>;
+
+enum SessionAuth<'a> {
+    Interactive {
+        ui_tx: &'a Sender<UiMessage>,
+        worker_rx: &'a Receiver<WorkerMessage>,
+        host_key_rx: &'a Arc<Mutex<Receiver<HostKeyDecision>>>,
+    },
+    NonInteractive,
+}
+
+impl SessionAuth<'_> {
+    fn known_hosts_client(
+        &self,
+        host: String,
+        port: u16,
+        policy: HostKeyPolicy,
+        log_path: &str,
+    ) -> KnownHostsClient {
+        let client = match self {
+            Self::Interactive {
+                ui_tx, host_key_rx, ..
+            } => KnownHostsClient::interactive(
+                host,
+                port,
+                (*ui_tx).clone(),
+                Arc::clone(host_key_rx),
+                log_path.to_string(),
+            ),
+            Self::NonInteractive => {
+                KnownHostsClient::non_interactive(host, port, log_path.to_string())
+            }
+        };
+        client.with_policy(policy)
+    }
+
+    async fn authenticate(
+        &self,
+        session: &mut SshHandle,
+        settings: &ConnectionSettings,
+        log_path: &str,
+    ) -> Result<()> {
+        match self {
+            Self::Interactive {
+                ui_tx, worker_rx, ..
+            } => authenticate_interactive(session, settings, ui_tx, worker_rx, log_path).await,
+            Self::NonInteractive => authenticate_non_interactive(session, settings, log_path).await,
+        }
+    }
+
+    fn report_status(&self, message: &str) {
+        if let Self::Interactive { ui_tx, .. } = self {
+            send_ui_status(ui_tx, IssueKind::Info, message);
+        }
     }
-    client.abort_handle.abort();
 }
 
-fn stop_terminal_client(client: ActiveTerminalClient, message: &str) {
-    if client.abort_handle.is_finished() {
-        return;
+/// Bounds an SSH handshake by the connect timeout like [`within_connect_timeout`], except
+/// that time the user spends on a host key prompt does not count against it.
+async fn within_handshake_timeout<F: Future>(
+    limit: Option<Duration>,
+    target: &str,
+    observer: &SessionObserver,
+    step: F,
+) -> Result<F::Output, ConnectionError> {
+    let Some(limit) = limit else {
+        return Ok(step.await);
+    };
+    let started = Instant::now();
+    let mut step = std::pin::pin!(step);
+    loop {
+        let waited = started
+            .elapsed()
+            .saturating_sub(host_key_prompt_time(observer));
+        let remaining = limit.saturating_sub(waited);
+        if remaining.is_zero() {
+            return Err(ConnectionError::ConnectTimeout {
+                target: target.to_string(),
+                limit,
+            });
+        }
+        if let Ok(output) = tokio::time::timeout(remaining, &mut step).await {
+            return Ok(output);
+        }
     }
-    client.abort_handle.abort();
-    send_message(
-        &client.ui_tx,
-        UiMessage::Status(StatusUpdate {
-            kind: IssueKind::Transport,
-            message: message.to_string(),
-        }),
-    );
-    send_message(&client.ui_tx, UiMessage::Connected(false));
 }
 
-fn stop_active_terminal_clients(active_clients: &mut Vec<ActiveTerminalClient>, message: &str) {
-    for client in active_clients.drain(..) {
-        stop_terminal_client(client, message);
+/// Bounds one connection step by the profile's connect timeout, if any.
+async fn within_connect_timeout<F: Future>(
+    limit: Option<Duration>,
+    target: &str,
+    step: F,
+) -> Result<F::Output, ConnectionError> {
+    match limit {
+        Some(limit) => {
+            tokio::time::timeout(limit, step)
+                .await
+                .map_err(|_| ConnectionError::ConnectTimeout {
+                    target: target.to_string(),
+                    limit,
+                })
+        }
+        None => Ok(step.await),
     }
 }
 
-struct ActiveSftpClient {
-    client_id: u64,
-    ui_tx: Sender<SftpUiMessage>,
-    abort_handle: tokio::task::AbortHandle,
+async fn open_tcp_transport(
+    host: &str,
+    port: u16,
+    connect_timeout: Option<Duration>,
+    log_path: &str,
+) -> Result<BoxedTransport> {
+    let addr = format!("{host}:{port}");
+    logger::log_line(log_path, &format!("Connecting TCP to {addr}."));
+    let tcp = within_connect_timeout(
+        connect_timeout,
+        &addr,
+        tokio::net::TcpStream::connect(&addr),
+    )
+    .await?
+    .with_context(|| format!("Failed to connect to {addr}"))?;
+    let _ = tcp.set_nodelay(true);
+    Ok(Box::new(tcp))
 }
 
-fn stop_sftp_client(client: ActiveSftpClient, message: &str) {
-    if client.abort_handle.is_finished() {
-        return;
+const PROXY_COMMAND_STDERR_LIMIT: usize = 2048;
+
+/// Whether `value` can be pasted into a `cmd /C` or `sh -c` line as a single plain word. Like
+/// OpenSSH 9.6, host and user names with quoting, expansion or separator characters are
+/// refused, since they can come from the command line, `ssh://` links or another instance.
+fn is_shell_safe_token(value: &str) -> bool {
+    !value.starts_with('-')
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || "'`\"$\\;&<>|(){}%^!*?".contains(c))
+}
+
+/// Expands OpenSSH-style `%h`, `%p`, `%r` and `%%` tokens in a proxy command.
+fn expand_proxy_command(
+    template: &str,
+    host: &str,
+    port: u16,
+    username: &str,
+) -> Result<String, ConnectionError> {
+    let checked = |token: &'static str, value: &str| {
+        if is_shell_safe_token(value) {
+            Ok(())
+        } else {
+            Err(ConnectionError::ProxyCommandUnsafeToken {
+                token,
+                value: value.to_string(),
+            })
+        }
+    };
+    let mut out = String::with_capacity(template.len() + host.len());
+    let mut chars = template.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('h') => {
+                checked("host", host)?;
+                out.push_str(host);
+            }
+            Some('p') => out.push_str(&port.to_string()),
+            Some('r') => {
+                checked("user", username)?;
+                out.push_str(username);
+            }
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
     }
-    client.abort_handle.abort();
-    send_message(
-        &client.ui_tx,
-        SftpUiMessage::Status(StatusUpdate {
-            kind: IssueKind::Transport,
-            message: message.to_string(),
-        }),
-    );
-    send_message(&client.ui_tx, SftpUiMessage::Connected(false));
+    Ok(out)
 }
 
-fn stop_active_sftp_clients(active_clients: &mut Vec<ActiveSftpClient>, message: &str) {
-    for client in active_clients.drain(..) {
-        stop_sftp_client(client, message);
+/// Helper process whose stdin/stdout carry the SSH stream. The child is killed once every
+/// clone (including the one held by its stream) is dropped.
+#[derive(Clone)]
+struct ProxyCommandProcess {
+    child: Arc<Mutex<tokio::process::Child>>,
+    stderr: Arc<Mutex<String>>,
+}
+
+impl ProxyCommandProcess {
+    /// Returns the exit reason if the helper has terminated, giving it a moment to be reaped.
+    async fn exit_error(&self) -> Option<ConnectionError> {
+        for _ in 0..10 {
+            let status = match self.child.lock() {
+                Ok(mut child) => child.try_wait().ok().flatten(),
+                Err(_) => return None,
+            };
+            if let Some(status) = status {
+                let stderr = self
+                    .stderr
+                    .lock()
+                    .map(|buf| buf.trim().to_string())
+                    .unwrap_or_default();
+                return Some(ConnectionError::ProxyCommandExited {
+                    status: status.to_string(),
+                    stderr,
+                });
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        None
     }
 }
 
-struct ActiveTransfer {
-    request_id: u64,
-    event_tx: Sender<DownloadManagerEvent>,
-    abort_handle: tokio::task::AbortHandle,
+struct ProxyCommandStream {
+    stdin: tokio::process::ChildStdin,
+    stdout: tokio::process::ChildStdout,
+    _process: ProxyCommandProcess,
 }
 
-fn stop_active_transfers(active_transfers: &mut Vec<ActiveTransfer>, message: &str) {
-    for transfer in active_transfers.drain(..) {
-        if transfer.abort_handle.is_finished() {
-            continue;
+impl tokio::io::AsyncRead for ProxyCommandStream {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.stdout).poll_read(cx, buf)
+    }
+}
+
+impl tokio::io::AsyncWrite for ProxyCommandStream {
+    fn poll_write(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        std::pin::Pin::new(&mut self.stdin).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.stdin).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.stdin).poll_shutdown(cx)
+    }
+}
+
+fn spawn_proxy_command(
+    command: &str,
+    log_path: &str,
+) -> Result<(ProxyCommandStream, ProxyCommandProcess)> {
+    logger::log_line(log_path, &format!("Starting proxy command: {command}"));
+
+    #[cfg(windows)]
+    let mut cmd = {
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C").arg(command).creation_flags(CREATE_NO_WINDOW);
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+
+    let spawn_error = |reason: String| ConnectionError::ProxyCommandSpawn {
+        command: command.to_string(),
+        reason,
+    };
+    let mut child = cmd.spawn().map_err(|err| spawn_error(err.to_string()))?;
+    let (Some(stdin), Some(stdout), stderr_pipe) =
+        (child.stdin.take(), child.stdout.take(), child.stderr.take())
+    else {
+        return Err(spawn_error("standard streams are unavailable".to_string()).into());
+    };
+
+    let stderr = Arc::new(Mutex::new(String::new()));
+    if let Some(stderr_pipe) = stderr_pipe {
+        let stderr = Arc::clone(&stderr);
+        let log_path = log_path.to_string();
+        tokio::spawn(async move {
+            use tokio::io::AsyncBufReadExt;
+
+            let mut lines = tokio::io::BufReader::new(stderr_pipe).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                logger::log_line(&log_path, &format!("Proxy command stderr: {line}"));
+                if let Ok(mut buf) = stderr.lock() {
+                    if !buf.is_empty() {
+                        buf.push('\n');
+                    }
+                    buf.push_str(&line);
+                    if buf.len() > PROXY_COMMAND_STDERR_LIMIT {
+                        let mut cut = buf.len() - PROXY_COMMAND_STDERR_LIMIT;
+                        while !buf.is_char_boundary(cut) {
+                            cut += 1;
+                        }
+                        buf.drain(..cut);
+                    }
+                }
+            }
+        });
+    }
+
+    let process = ProxyCommandProcess {
+        child: Arc::new(Mutex::new(child)),
+        stderr,
+    };
+    let stream = ProxyCommandStream {
+        stdin,
+        stdout,
+        _process: process.clone(),
+    };
+    Ok((stream, process))
+}
+
+/// Prefers the proxy command's exit reason over the generic handshake error it caused.
+async fn handshake_error(
+    proxy: Option<&ProxyCommandProcess>,
+    observer: &SessionObserver,
+    err: russh::Error,
+    context: String,
+) -> anyhow::Error {
+    let rejection = observer
+        .lock()
+        .ok()
+        .and_then(|mut observed| observed.host_key_rejection.take());
+    if let Some(rejection) = rejection {
+        return anyhow::Error::new(rejection).context(context);
+    }
+    if let Some(proxy) = proxy {
+        if let Some(exit) = proxy.exit_error().await {
+            return anyhow::Error::new(exit).context(context);
         }
-        transfer.abort_handle.abort();
-        send_message(
-            &transfer.event_tx,
-            DownloadManagerEvent::Paused {
-                request_id: transfer.request_id,
-                issue: TransferIssue {
-                    kind: IssueKind::Transport,
-                    message: message.to_string(),
-                },
-            },
-        );
     }
+    anyhow::Error::new(err).context(context)
 }
 
-enum HostKeyVerificationMode {
-    Interactive {
-        ui_tx: Sender<UiMessage>,
-        decision_rx: Receiver<HostKeyDecision>,
-        log_path: String,
-    },
-    NonInteractive {
-        log_path: String,
-    },
+static DEFAULT_PROXY: Mutex<Option<ProxySettings>> = Mutex::new(None);
+const PROXY_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+const HTTP_PROXY_RESPONSE_LIMIT: usize = 16 * 1024;
+
+/// Sets the global proxy used by every connection whose profile has no override.
+pub fn set_default_proxy(proxy: ProxySettings) {
+    if let Ok(mut slot) = DEFAULT_PROXY.lock() {
+        *slot = Some(proxy);
+    }
 }
 
-struct KnownHostsClient {
-    host: String,
-    port: u16,
-    known_hosts_path: PathBuf,
-    mode: HostKeyVerificationMode,
+fn effective_proxy(settings: &ConnectionSettings) -> ProxySettings {
+    if let Some(proxy) = &settings.proxy_override {
+        return proxy.clone();
+    }
+    DEFAULT_PROXY
+        .lock()
+        .ok()
+        .and_then(|slot| slot.clone())
+        .unwrap_or_default()
 }
 
-impl KnownHostsClient {
-    fn interactive(
-        host: String,
-        port: u16,
-        ui_tx: Sender<UiMessage>,
-        decision_rx: Receiver<HostKeyDecision>,
-        log_path: String,
-    ) -> Self {
-        Self {
-            host,
-            port,
-            known_hosts_path: app_known_hosts_path(),
-            mode: HostKeyVerificationMode::Interactive {
-                ui_tx,
-                decision_rx,
-                log_path,
-            },
+/// TCP stream to the profile's host and port, through the effective proxy when one is set.
+pub async fn open_direct_transport(
+    settings: &ConnectionSettings,
+    log_path: &str,
+) -> Result<BoxedTransport> {
+    let host = settings.host.trim();
+    let connect_timeout = settings.connect_timeout();
+    let proxy = effective_proxy(settings);
+    if proxy.is_enabled() {
+        open_proxy_transport(&proxy, host, settings.port, connect_timeout, log_path).await
+    } else {
+        open_tcp_transport(host, settings.port, connect_timeout, log_path).await
+    }
+}
+
+async fn open_proxy_transport(
+    proxy: &ProxySettings,
+    host: &str,
+    port: u16,
+    connect_timeout: Option<Duration>,
+    log_path: &str,
+) -> Result<BoxedTransport> {
+    let proxy_addr = format!("{}:{}", proxy.host.trim(), proxy.port);
+    logger::log_line(
+        log_path,
+        &format!(
+            "Connecting TCP to {} proxy {proxy_addr} for {host}:{port}.",
+            proxy.kind.label()
+        ),
+    );
+    let mut tcp = within_connect_timeout(
+        connect_timeout,
+        &format!("proxy {proxy_addr}"),
+        tokio::net::TcpStream::connect(&proxy_addr),
+    )
+    .await?
+    .with_context(|| format!("Failed to connect to proxy {proxy_addr}"))?;
+    let _ = tcp.set_nodelay(true);
+
+    let handshake = async {
+        match proxy.kind {
+            ProxyKind::Socks5 => socks5_connect(&mut tcp, proxy, host, port).await,
+            ProxyKind::HttpConnect => http_connect(&mut tcp, proxy, host, port).await,
+            ProxyKind::Direct => Ok(()),
+        }
+    };
+    match tokio::time::timeout(PROXY_HANDSHAKE_TIMEOUT, handshake).await {
+        Ok(result) => result.with_context(|| format!("Proxy {proxy_addr} handshake failed"))?,
+        Err(_) => {
+            return Err(ConnectionError::ProxyHandshake(format!(
+                "proxy {proxy_addr} did not respond within {}s",
+                PROXY_HANDSHAKE_TIMEOUT.as_secs()
+            ))
+            .into())
         }
     }
+    logger::log_line(
+        log_path,
+        &format!("Proxy tunnel to {host}:{port} established."),
+    );
+    Ok(Box::new(tcp))
+}
 
-    fn non_interactive(host: String, port: u16, log_path: String) -> Self {
-        Self {
-            host,
-            port,
-            known_hosts_path: app_known_hosts_path(),
-            mode: HostKeyVerificationMode::NonInteractive { log_path },
+fn socks5_reply_text(code: u8) -> &'static str {
+    match code {
+        0x01 => "general SOCKS server failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+fn socks5_address(host: &str) -> Result<Vec<u8>> {
+    match host.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(ip)) => {
+            let mut out = vec![0x01];
+            out.extend_from_slice(&ip.octets());
+            Ok(out)
+        }
+        Ok(std::net::IpAddr::V6(ip)) => {
+            let mut out = vec![0x04];
+            out.extend_from_slice(&ip.octets());
+            Ok(out)
+        }
+        Err(_) => {
+            let len = u8::try_from(host.len()).map_err(|_| {
+                ConnectionError::ProxyHandshake(format!("host name too long for SOCKS5: {host}"))
+            })?;
+            let mut out = vec![0x03, len];
+            out.extend_from_slice(host.as_bytes());
+            Ok(out)
         }
     }
+}
 
-    fn verify_server_key(
-        &mut self,
-        server_public_key: &keys::PublicKey,
-    ) -> Result<bool, russh::Error> {
-        match keys::known_hosts::check_known_hosts_path(
-            &self.host,
-            self.port,
-            server_public_key,
-            &self.known_hosts_path,
-        ) {
-            Ok(true) => Ok(true),
-            Ok(false) => self.handle_host_key_prompt(server_public_key, None),
-            Err(keys::Error::KeyChanged { line }) => {
-                self.handle_host_key_prompt(server_public_key, Some(line))
+async fn socks5_connect<S>(
+    stream: &mut S,
+    proxy: &ProxySettings,
+    host: &str,
+    port: u16,
+) -> Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let fail = |msg: String| anyhow::Error::new(ConnectionError::ProxyHandshake(msg));
+    let use_password = !proxy.username.is_empty();
+    let greeting: &[u8] = if use_password {
+        &[0x05, 0x02, 0x00, 0x02]
+    } else {
+        &[0x05, 0x01, 0x00]
+    };
+    stream.write_all(greeting).await?;
+
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).await?;
+    if choice[0] != 0x05 {
+        return Err(fail("proxy is not a SOCKS5 server".to_string()));
+    }
+    match choice[1] {
+        0x00 => {}
+        0x02 if use_password => {
+            let user = proxy.username.as_bytes();
+            let pass = proxy.password.as_bytes();
+            let (Ok(user_len), Ok(pass_len)) = (u8::try_from(user.len()), u8::try_from(pass.len()))
+            else {
+                return Err(fail("SOCKS5 credentials are too long".to_string()));
+            };
+            let mut auth = vec![0x01, user_len];
+            auth.extend_from_slice(user);
+            auth.push(pass_len);
+            auth.extend_from_slice(pass);
+            stream.write_all(&auth).await?;
+
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await?;
+            if status[1] != 0x00 {
+                return Err(fail(
+                    "SOCKS5 proxy rejected the username or password".to_string(),
+                ));
             }
-            Err(err) => Err(err.into()),
+        }
+        0xFF => {
+            return Err(fail(if use_password {
+                "SOCKS5 proxy rejected the offered authentication methods".to_string()
+            } else {
+                "SOCKS5 proxy requires authentication".to_string()
+            }))
+        }
+        other => {
+            return Err(fail(format!(
+                "SOCKS5 proxy selected unsupported auth method {other:#04x}"
+            )))
         }
     }
 
-    fn handle_host_key_prompt(
-        &mut self,
-        server_public_key: &keys::PublicKey,
-        changed_line: Option<usize>,
-    ) -> Result<bool, russh::Error> {
-        match &mut self.mode {
-            HostKeyVerificationMode::Interactive {
-                ui_tx,
-                decision_rx,
-                log_path,
-            } => {
-                let fingerprint =
-                    format!("{}", server_public_key.fingerprint(keys::HashAlg::Sha256));
-                let prompt = HostKeyPrompt {
-                    host: self.host.clone(),
-                    port: self.port,
-                    algorithm: server_public_key.algorithm().to_string(),
-                    fingerprint,
-                    known_hosts_path: self.known_hosts_path.to_string_lossy().into_owned(),
-                    changed_line,
-                };
-                let prompt_message = if let Some(line) = changed_line {
-                    format!(
-                        "Host key changed for {}:{} (known_hosts line {}); waiting for user decision.",
-                        self.host, self.port, line
-                    )
-                } else {
-                    format!(
-                        "Unknown host key for {}:{}; waiting for user trust decision.",
-                        self.host, self.port
-                    )
-                };
-                logger::log_line(log_path.as_str(), &prompt_message);
-                send_message(ui_tx, UiMessage::HostKeyPrompt(prompt));
+    let mut request = vec![0x05, 0x01, 0x00];
+    request.extend(socks5_address(host)?);
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
 
-                match decision_rx.recv_timeout(Duration::from_secs(600)) {
-                    Ok(HostKeyDecision::TrustAndSave) => {
-                        keys::known_hosts::learn_known_hosts_path(
-                            &self.host,
-                            self.port,
-                            server_public_key,
-                            &self.known_hosts_path,
-                        )?;
-                        logger::log_line(
-                            log_path.as_str(),
-                            &format!(
-                                "Pinned host key for {}:{} to {}.",
-                                self.host,
-                                self.port,
-                                self.known_hosts_path.display()
-                            ),
-                        );
-                        Ok(true)
-                    }
-                    Ok(HostKeyDecision::ReplaceAndSave) => {
-                        let Some(line) = changed_line else {
-                            logger::log_line(
-                                log_path.as_str(),
-                                &format!(
-                                    "Ignoring replace-host-key decision for {}:{} because no existing entry was provided.",
-                                    self.host, self.port
-                                ),
-                            );
-                            return Ok(false);
-                        };
-                        remove_known_hosts_line(&self.known_hosts_path, line)?;
-                        keys::known_hosts::learn_known_hosts_path(
-                            &self.host,
-                            self.port,
-                            server_public_key,
-                            &self.known_hosts_path,
-                        )?;
-                        logger::log_line(
-                            log_path.as_str(),
-                            &format!(
-                                "Replaced pinned host key for {}:{} in {} (old line {}).",
-                                self.host,
-                                self.port,
-                                self.known_hosts_path.display(),
-                                line
-                            ),
-                        );
-                        Ok(true)
-                    }
-                    Ok(HostKeyDecision::Reject) => {
-                        logger::log_line(
-                            log_path.as_str(),
-                            &format!("User rejected host key for {}:{}.", self.host, self.port),
-                        );
-                        Ok(false)
-                    }
-                    Err(RecvTimeoutError::Timeout) => {
-                        logger::log_line(
-                            log_path.as_str(),
-                            &format!(
-                                "Timed out waiting for host key decision for {}:{}.",
-                                self.host, self.port
-                            ),
-                        );
-                        Ok(false)
-                    }
-                    Err(RecvTimeoutError::Disconnected) => Ok(false),
-                }
-            }
-            HostKeyVerificationMode::NonInteractive { log_path } => {
-                let fingerprint =
-                    format!("{}", server_public_key.fingerprint(keys::HashAlg::Sha256));
-                if let Some(line) = changed_line {
-                    logger::log_line(
-                        log_path.as_str(),
-                        &format!(
-                            "Detached transfer rejected changed host key for {}:{} at known_hosts line {} (fingerprint {}).",
-                            self.host, self.port, line, fingerprint
-                        ),
-                    );
-                    return Err(keys::Error::KeyChanged { line }.into());
-                }
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0x00 {
+        return Err(fail(format!(
+            "SOCKS5 proxy could not reach {host}:{port}: {}",
+            socks5_reply_text(reply[1])
+        )));
+    }
+    let bound_len = match reply[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await?;
+            usize::from(len[0])
+        }
+        other => {
+            return Err(fail(format!(
+                "SOCKS5 proxy replied with unknown address type {other:#04x}"
+            )))
+        }
+    };
+    let mut bound = vec![0u8; bound_len + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
 
-                logger::log_line(
-                    log_path.as_str(),
-                    &format!(
-                        "Detached transfer rejected unknown host key for {}:{} (fingerprint {}).",
-                        self.host, self.port, fingerprint
-                    ),
-                );
-                Ok(false)
+async fn http_connect<S>(stream: &mut S, proxy: &ProxySettings, host: &str, port: u16) -> Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    use base64::Engine as _;
+
+    let authority = if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    };
+    let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if !proxy.username.is_empty() {
+        let token = base64::engine::general_purpose::STANDARD
+            .encode(format!("{}:{}", proxy.username, proxy.password));
+        request.push_str(&format!("Proxy-Authorization: Basic {token}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // Read byte-wise so nothing past the header block (the SSH banner) is consumed.
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= HTTP_PROXY_RESPONSE_LIMIT {
+            return Err(ConnectionError::ProxyHandshake(
+                "HTTP proxy response headers are too large".to_string(),
+            )
+            .into());
+        }
+        stream.read_exact(&mut byte).await?;
+        response.push(byte[0]);
+    }
+    check_http_connect_response(&String::from_utf8_lossy(&response))
+}
+
+fn check_http_connect_response(response: &str) -> Result<()> {
+    let status_line = response.lines().next().unwrap_or_default().trim();
+    let mut parts = status_line.splitn(3, ' ');
+    let version = parts.next().unwrap_or_default();
+    let code = parts.next().and_then(|code| code.parse::<u16>().ok());
+    if !version.starts_with("HTTP/") {
+        return Err(ConnectionError::ProxyHandshake(format!(
+            "proxy did not answer with HTTP: {status_line}"
+        ))
+        .into());
+    }
+    match code {
+        Some(200..=299) => Ok(()),
+        Some(407) => Err(ConnectionError::ProxyHandshake(
+            "HTTP proxy requires authentication (407)".to_string(),
+        )
+        .into()),
+        _ => Err(ConnectionError::ProxyHandshake(format!(
+            "HTTP proxy refused CONNECT: {status_line}"
+        ))
+        .into()),
+    }
+}
+
+fn parse_algorithm_list<T>(
+    kind: &'static str,
+    list: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<Option<Vec<T>>, ConnectionError> {
+    let parsed = AlgorithmPreferences::names(list)
+        .map(|name| {
+            parse(name).ok_or_else(|| ConnectionError::UnsupportedAlgorithm {
+                kind,
+                name: name.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((!parsed.is_empty()).then_some(parsed))
+}
+
+/// russh preferences for the profile's algorithm lists; empty lists keep russh's defaults.
+fn preferred_algorithms(prefs: &AlgorithmPreferences) -> Result<russh::Preferred, ConnectionError> {
+    let mut preferred = russh::Preferred::default();
+    if let Some(mut kex) = parse_algorithm_list("key exchange", &prefs.kex, |name| {
+        russh::kex::Name::try_from(name).ok()
+    })? {
+        // Extension markers ride along in the kex list; dropping them would disable
+        // ext-info and strict kex.
+        for marker in [
+            russh::kex::EXTENSION_SUPPORT_AS_CLIENT,
+            russh::kex::EXTENSION_OPENSSH_STRICT_KEX_AS_CLIENT,
+        ] {
+            if !kex.contains(&marker) {
+                kex.push(marker);
             }
         }
+        preferred.kex = kex.into();
+    }
+    if let Some(cipher) = parse_algorithm_list("cipher", &prefs.ciphers, |name| {
+        russh::cipher::Name::try_from(name).ok()
+    })? {
+        preferred.cipher = cipher.into();
     }
+    if let Some(mac) = parse_algorithm_list("MAC", &prefs.macs, |name| {
+        russh::mac::Name::try_from(name).ok()
+    })? {
+        preferred.mac = mac.into();
+    }
+    if let Some(key) = parse_algorithm_list("host key", &prefs.host_keys, |name| {
+        keys::key::ALL_KEY_TYPES
+            .iter()
+            .find(|algorithm| algorithm.as_str() == name)
+            .cloned()
+    })? {
+        preferred.key = key.into();
+    }
+    Ok(preferred)
+}
+
+/// Algorithm names accepted in each preference list, for settings hints.
+pub fn supported_algorithm_names() -> [(&'static str, Vec<String>); 4] {
+    [
+        (
+            "Key exchange",
+            russh::kex::ALL_KEX_ALGORITHMS
+                .iter()
+                .map(|name| name.as_ref().to_string())
+                .filter(|name| name.contains('-'))
+                .collect(),
+        ),
+        (
+            "Ciphers",
+            russh::cipher::ALL_CIPHERS
+                .iter()
+                .map(|name| name.as_ref().to_string())
+                .filter(|name| name != "none" && name != "clear")
+                .collect(),
+        ),
+        (
+            "MACs",
+            russh::mac::ALL_MAC_ALGORITHMS
+                .iter()
+                .map(|name| name.as_ref().to_string())
+                .filter(|name| name != "none")
+                .collect(),
+        ),
+        (
+            "Host keys",
+            keys::key::ALL_KEY_TYPES
+                .iter()
+                .map(|algorithm| algorithm.as_str().to_string())
+                .collect(),
+        ),
+    ]
 }
 
-async fn run_detached_sftp_download(
-    request: DetachedTransferRequest,
-    resume_from_local: bool,
-) -> Result<()> {
-    let DetachedTransferRequest {
-        settings,
-        request_id,
-        remote_path,
-        local_path,
-        event_tx,
-        cancel_rx,
-        conflict_response_rx: _,
-        log_path,
-    } = request;
+/// Client configuration shared by interactive sessions and detached transfers.
+fn client_config(settings: &ConnectionSettings) -> Result<client::Config> {
+    Ok(client::Config {
+        // Keep sessions alive indefinitely while idle.
+        inactivity_timeout: None,
+        keepalive_interval: settings.keepalive_interval(),
+        // russh closes the session after this many unanswered keepalives; 0 never does.
+        keepalive_max: settings.keepalive_max_missed as usize,
+        preferred: preferred_algorithms(&settings.algorithms)?,
+        ..Default::default()
+    })
+}
 
-    if transfer_cancel_requested(&cancel_rx) {
-        send_download_event(
-            &event_tx,
-            DownloadManagerEvent::Canceled {
-                request_id,
-                local_path,
-            },
+/// An authenticated target session and the jump sessions it is tunnelled through, which
+/// must outlive it.
+struct AuthenticatedSession {
+    session: SshHandle,
+    jump_sessions: Vec<SshHandle>,
+    negotiated: Option<NegotiatedAlgorithms>,
+    observer: SessionObserver,
+}
+
+/// Connects and authenticates to `settings.host`, tunnelling through each configured jump host
+/// in order.
+async fn connect_authenticated_session(
+    settings: &ConnectionSettings,
+    config: Arc<client::Config>,
+    auth: &SessionAuth<'_>,
+    server_channel_tx: Option<UnboundedSender<ServerChannel>>,
+    log_path: &str,
+) -> Result<AuthenticatedSession> {
+    let host = settings.host.trim().to_string();
+    let port = settings.port;
+    let hops = settings.jump_hop_settings();
+
+    let first = hops.first().unwrap_or(settings);
+    let first_host = first.host.trim().to_string();
+    let first_port = first.port;
+    let connect_timeout = settings.connect_timeout();
+    let mut proxy_process: Option<ProxyCommandProcess> = None;
+    let proxy = effective_proxy(settings);
+    let mut transport: BoxedTransport = if !settings.proxy_command.trim().is_empty() {
+        auth.report_status("Starting proxy command...");
+        let command = expand_proxy_command(
+            settings.proxy_command.trim(),
+            &first_host,
+            first_port,
+            first.username.trim(),
+        )?;
+        let (stream, process) = spawn_proxy_command(&command, log_path)?;
+        proxy_process = Some(process);
+        Box::new(stream)
+    } else if proxy.is_enabled() {
+        auth.report_status(&format!(
+            "Connecting via {} proxy {}:{}...",
+            proxy.kind.label(),
+            proxy.host.trim(),
+            proxy.port
+        ));
+        open_proxy_transport(&proxy, &first_host, first_port, connect_timeout, log_path).await?
+    } else {
+        open_tcp_transport(&first_host, first_port, connect_timeout, log_path).await?
+    };
+
+    let mut jump_sessions: Vec<SshHandle> = Vec::with_capacity(hops.len());
+    for (index, hop) in hops.iter().enumerate() {
+        let hop_host = hop.host.trim().to_string();
+        let hop_port = hop.port;
+        let (next_host, next_port) = hops
+            .get(index + 1)
+            .map(|next| (next.host.trim().to_string(), next.port))
+            .unwrap_or_else(|| (host.clone(), port));
+
+        let progress = format!(
+            "Connecting via jump host {hop_host}:{hop_port} ({}/{})...",
+            index + 1,
+            hops.len()
         );
-        return Ok(());
+        logger::log_line(log_path, &progress);
+        auth.report_status(&progress);
+
+        let hop_observer = SessionObserver::default();
+        let mut hop_session = match within_handshake_timeout(
+            connect_timeout,
+            &format!("jump host {hop_host}:{hop_port}"),
+            &hop_observer,
+            client::connect_stream(
+                Arc::clone(&config),
+                transport,
+                auth.known_hosts_client(
+                    hop_host.clone(),
+                    hop_port,
+                    settings.host_key_policy,
+                    log_path,
+                )
+                .with_observer(Arc::clone(&hop_observer)),
+            ),
+        )
+        .await?
+        {
+            Ok(hop_session) => hop_session,
+            Err(err) => {
+                return Err(handshake_error(
+                    proxy_process.as_ref(),
+                    &hop_observer,
+                    err,
+                    format!("SSH handshake with jump host {hop_host}:{hop_port} failed"),
+                )
+                .await)
+            }
+        };
+
+        auth.authenticate(&mut hop_session, hop, log_path)
+            .await
+            .with_context(|| format!("Jump host {hop_host}:{hop_port} rejected authentication"))?;
+
+        logger::log_line(
+            log_path,
+            &format!("Opening tunnel from {hop_host}:{hop_port} to {next_host}:{next_port}."),
+        );
+        let channel = within_connect_timeout(
+            connect_timeout,
+            &format!("{next_host}:{next_port} via {hop_host}:{hop_port}"),
+            hop_session.channel_open_direct_tcpip(
+                next_host.clone(),
+                u32::from(next_port),
+                "127.0.0.1",
+                0,
+            ),
+        )
+        .await?
+        .with_context(|| {
+                format!("Jump host {hop_host}:{hop_port} could not open a tunnel to {next_host}:{next_port}")
+            })?;
+        transport = Box::new(channel.into_stream());
+        jump_sessions.push(hop_session);
     }
 
-    let session = open_authenticated_session_for_transfer(&settings, &log_path).await?;
-    let sftp = open_sftp_channel(&session, &log_path).await?;
-    run_sftp_download_with_session(
-        sftp,
-        request_id,
-        remote_path,
-        local_path,
-        resume_from_local,
-        &event_tx,
-        &cancel_rx,
+    logger::log_line(log_path, "Performing SSH handshake.");
+    let observer = SessionObserver::default();
+    let target = format!("{host}:{port}");
+    let mut session = match within_handshake_timeout(
+        connect_timeout,
+        &target,
+        &observer,
+        client::connect_stream(
+            config,
+            transport,
+            auth.known_hosts_client(host, port, settings.host_key_policy, log_path)
+                .with_server_channels(server_channel_tx)
+                .with_observer(Arc::clone(&observer)),
+        ),
     )
-    .await
+    .await?
+    {
+        Ok(session) => session,
+        Err(err) => {
+            return Err(handshake_error(
+                proxy_process.as_ref(),
+                &observer,
+                err,
+                "SSH handshake failed".to_string(),
+            )
+            .await)
+        }
+    };
+
+    let negotiated = observer
+        .lock()
+        .ok()
+        .and_then(|observed| observed.negotiated.clone());
+    if let Some(negotiated) = &negotiated {
+        logger::log_line(log_path, &format!("Negotiated {}.", negotiated.summary()));
+    }
+
+    auth.authenticate(&mut session, settings, log_path).await?;
+    Ok(AuthenticatedSession {
+        session,
+        jump_sessions,
+        negotiated,
+        observer,
+    })
 }
 
-async fn run_detached_sftp_upload(
-    request: DetachedTransferRequest,
-    resume_from_remote_temp: bool,
-) -> Result<()> {
-    let DetachedTransferRequest {
-        settings,
-        request_id,
-        remote_path,
-        local_path,
-        event_tx,
-        cancel_rx,
-        conflict_response_rx,
-        log_path,
-    } = request;
+async fn disconnect_jump_sessions(jump_sessions: &[SshHandle]) {
+    for hop_session in jump_sessions.iter().rev() {
+        let _ = hop_session
+            .disconnect(Disconnect::ByApplication, "", "English")
+            .await;
+    }
+}
 
-    if transfer_cancel_requested(&cancel_rx) {
-        send_download_event(
-            &event_tx,
-            DownloadManagerEvent::Canceled {
-                request_id,
-                local_path,
-            },
-        );
-        return Ok(());
+async fn open_authenticated_session_for_transfer(
+    settings: &ConnectionSettings,
+    log_path: &str,
+) -> Result<AuthenticatedSession> {
+    if settings.host.trim().is_empty() {
+        return Err(ConnectionError::MissingHost.into());
+    }
+    if settings.username.trim().is_empty() {
+        return Err(ConnectionError::MissingUsername.into());
     }
 
-    let session = open_authenticated_session_for_transfer(&settings, &log_path).await?;
-    let sftp = open_sftp_channel(&session, &log_path).await?;
-    run_sftp_upload_with_session(
-        sftp,
-        request_id,
-        remote_path,
-        local_path,
-        resume_from_remote_temp,
-        UploadTransferControl {
-            request_id,
-            session: &session,
-            event_tx: &event_tx,
-            cancel_rx: &cancel_rx,
-            conflict_response_rx: &conflict_response_rx,
-            log_path: &log_path,
-        },
+    let config = Arc::new(client_config(settings)?);
+
+    logger::log_line(log_path, "Creating detached transfer SSH session.");
+    connect_authenticated_session(
+        settings,
+        config,
+        &SessionAuth::NonInteractive,
+        None,
+        log_path,
     )
     .await
 }
 
-pub fn start_sftp_download_detached(
-    request: DetachedTransferRequest,
-    resume_from_local: bool,
-) -> thread::JoinHandle<()> {
-    let request_id = request.request_id;
-    let failure_tx = request.event_tx.clone();
-    thread::spawn(move || {
-        let result = match tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-        {
-            Ok(rt) => rt.block_on(run_detached_sftp_download(request, resume_from_local)),
-            Err(err) => Err(anyhow!("Failed to create async runtime: {err}")),
-        };
+type SshHandle = client::Handle<KnownHostsClient>;
 
-        if let Err(err) = result {
-            send_download_event(
-                &failure_tx,
-                DownloadManagerEvent::Failed {
-                    request_id,
-                    issue: transfer_issue_from_error(&err),
-                },
-            );
-        }
-    })
+struct ActiveTerminalClient {
+    client_id: u64,
+    ui_tx: Sender<UiMessage>,
+    command_tx: UnboundedSender<TerminalClientCommand>,
+    abort_handle: tokio::task::AbortHandle,
 }
 
-pub fn start_sftp_upload_detached(
-    request: DetachedTransferRequest,
-    resume_from_remote_temp: bool,
-) -> thread::JoinHandle<()> {
-    let request_id = request.request_id;
-    let failure_tx = request.event_tx.clone();
-    thread::spawn(move || {
-        let result = match tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-        {
-            Ok(rt) => rt.block_on(run_detached_sftp_upload(request, resume_from_remote_temp)),
-            Err(err) => Err(anyhow!("Failed to create async runtime: {err}")),
-        };
+fn abort_terminal_client(client: ActiveTerminalClient) {
+    if client.abort_handle.is_finished() {
+        return;
+    }
+    client.abort_handle.abort();
+}
 
-        if let Err(err) = result {
-            send_download_event(
-                &failure_tx,
-                DownloadManagerEvent::Failed {
-                    request_id,
-                    issue: transfer_issue_from_error(&err),
-                },
-            );
-        }
-    })
+fn stop_terminal_client(client: ActiveTerminalClient, message: &str) {
+    if client.abort_handle.is_finished() {
+        return;
+    }
+    client.abort_handle.abort();
+    send_message(
+        &client.ui_tx,
+        UiMessage::Status(StatusUpdate {
+            kind: IssueKind::Transport,
+            message: message.to_string(),
+        }),
+    );
+    send_message(&client.ui_tx, UiMessage::Connected(false));
 }
 
-impl client::Handler for KnownHostsClient {
-    type Error = russh::Error;
+fn stop_active_terminal_clients(active_clients: &mut Vec<ActiveTerminalClient>, message: &str) {
+    for client in active_clients.drain(..) {
+        stop_terminal_client(client, message);
+    }
+}
 
-    fn check_server_key(
-        &mut self,
-        server_public_key: &keys::PublicKey,
-    ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
-        let result = self.verify_server_key(server_public_key);
-        async move { result }
+struct ActiveSftpClient {
+    client_id: u64,
+    ui_tx: Sender<SftpUiMessage>,
+    abort_handle: tokio::task::AbortHandle,
+}
+
+fn stop_sftp_client(client: ActiveSftpClient, message: &str) {
+    if client.abort_handle.is_finished() {
+        return;
     }
+    client.abort_handle.abort();
+    send_message(
+        &client.ui_tx,
+        SftpUiMessage::Status(StatusUpdate {
+            kind: IssueKind::Transport,
+            message: message.to_string(),
+        }),
+    );
+    send_message(&client.ui_tx, SftpUiMessage::Connected(false));
 }
 
-pub fn start_shell(
-    initial_client_id: u64,
-    settings: ConnectionSettings,
-    scrollback_len: usize,
-    ui_tx: Sender<UiMessage>,
-    worker_rx: Receiver<WorkerMessage>,
-    host_key_rx: Receiver<HostKeyDecision>,
-    log_path: String,
-) -> thread::JoinHandle<()> {
-    thread::spawn(move || {
-        logger::log_line(&log_path, "Starting SSH worker.");
+fn stop_active_sftp_clients(active_clients: &mut Vec<ActiveSftpClient>, message: &str) {
+    for client in active_clients.drain(..) {
+        stop_sftp_client(client, message);
+    }
+}
 
-        let result = match tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-        {
-            Ok(rt) => {
-                let local = tokio::task::LocalSet::new();
-                local.block_on(
-                    &rt,
-                    run_shell_async(
-                        initial_client_id,
-                        settings,
-                        scrollback_len,
-                        &ui_tx,
-                        worker_rx,
-                        host_key_rx,
-                        &log_path,
-                    ),
-                )
-            }
-            Err(err) => Err(anyhow!("Failed to create async runtime: {err}")),
-        };
+struct ActiveTransfer {
+    request_id: u64,
+    event_tx: Sender<DownloadManagerEvent>,
+    abort_handle: tokio::task::AbortHandle,
+}
 
-        if let Err(err) = result {
-            logger::log_line(&log_path, &format!("Worker error: {err}"));
-            send_message(
-                &ui_tx,
-                UiMessage::Status(connection_status_from_error(&err)),
-            );
-            send_message(&ui_tx, UiMessage::Connected(false));
+fn stop_active_transfers(active_transfers: &mut Vec<ActiveTransfer>, message: &str) {
+    for transfer in active_transfers.drain(..) {
+        if transfer.abort_handle.is_finished() {
+            continue;
         }
-    })
+        transfer.abort_handle.abort();
+        send_message(
+            &transfer.event_tx,
+            DownloadManagerEvent::Paused {
+                request_id: transfer.request_id,
+                issue: TransferIssue {
+                    kind: IssueKind::Transport,
+                    message: message.to_string(),
+                },
+            },
+        );
+    }
 }
 
-async fn run_shell_async(
-    initial_client_id: u64,
-    settings: ConnectionSettings,
-    scrollback_len: usize,
-    ui_tx: &Sender<UiMessage>,
-    worker_rx: Receiver<WorkerMessage>,
-    host_key_rx: Receiver<HostKeyDecision>,
-    log_path: &str,
-) -> Result<()> {
-    logger::log_line(log_path, "Validating settings.");
-    if settings.host.trim().is_empty() {
-        return Err(ConnectionError::MissingHost.into());
+#[derive(Default)]
+struct ForwardBoard {
+    statuses: Vec<ForwardStatus>,
+    dirty: bool,
+}
+
+impl ForwardBoard {
+    fn register(&mut self, description: String) -> usize {
+        self.statuses.push(ForwardStatus {
+            description,
+            state: ForwardState::Starting,
+            open_connections: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            last_error: None,
+        });
+        self.dirty = true;
+        self.statuses.len() - 1
     }
-    if settings.username.trim().is_empty() {
-        return Err(ConnectionError::MissingUsername.into());
+
+    fn update(&mut self, id: usize, apply: impl FnOnce(&mut ForwardStatus)) {
+        if let Some(status) = self.statuses.get_mut(id) {
+            apply(status);
+            self.dirty = true;
+        }
     }
 
-    let host = settings.host.trim().to_string();
-    let port = settings.port;
-    let addr = format!("{host}:{port}");
-    logger::log_line(log_path, &format!("Connecting TCP to {addr}."));
-    let tcp = tokio::net::TcpStream::connect(&addr)
-        .await
-        .with_context(|| format!("Failed to connect to {addr}"))?;
-    let _ = tcp.set_nodelay(true);
+    fn take_changes(&mut self) -> Option<Vec<ForwardStatus>> {
+        if !self.dirty {
+            return None;
+        }
+        self.dirty = false;
+        Some(self.statuses.clone())
+    }
+}
 
-    let config = client::Config {
-        // Keep sessions alive indefinitely while idle.
-        inactivity_timeout: None,
-        keepalive_interval: Some(Duration::from_secs(20)),
-        // 0 means "do not auto-close after missed keepalive replies".
-        keepalive_max: 0,
-        ..Default::default()
-    };
-    let config = Arc::new(config);
+type SharedForwardBoard = Rc<RefCell<ForwardBoard>>;
 
-    logger::log_line(log_path, "Creating SSH session.");
-    logger::log_line(log_path, "Performing SSH handshake.");
-    let mut session = client::connect_stream(
-        config,
-        tcp,
-        KnownHostsClient::interactive(host, port, ui_tx.clone(), host_key_rx, log_path.to_string()),
-    )
-    .await
-    .context("SSH handshake failed")?;
+fn record_forward_error(board: &SharedForwardBoard, id: usize, log_path: &str, message: String) {
+    logger::log_line(log_path, &message);
+    board
+        .borrow_mut()
+        .update(id, |status| status.last_error = Some(message));
+}
 
-    let username = settings.username.trim();
-    let mut remaining_methods = MethodSet::empty();
-    let mut authenticated = false;
+/// Local end of a forwarded connection; counts bytes into the forward's status as they flow.
+struct CountingStream<S> {
+    inner: S,
+    board: SharedForwardBoard,
+    id: usize,
+}
 
-    let probe = session
-        .authenticate_none(username)
-        .await
-        .context("Failed to query server auth methods")?;
-    match probe {
-        AuthResult::Success => {
-            authenticated = true;
+impl<S: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for CountingStream<S> {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = std::pin::Pin::new(&mut self.inner).poll_read(cx, buf);
+        let read = (buf.filled().len() - before) as u64;
+        if read > 0 {
+            let id = self.id;
+            self.board
+                .borrow_mut()
+                .update(id, |status| status.bytes_sent += read);
         }
-        AuthResult::Failure {
-            remaining_methods: methods,
-            ..
-        } => {
-            remaining_methods = methods;
+        poll
+    }
+}
+
+impl<S: tokio::io::AsyncWrite + Unpin> tokio::io::AsyncWrite for CountingStream<S> {
+    fn poll_write(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let poll = std::pin::Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let std::task::Poll::Ready(Ok(written)) = &poll {
+            let written = *written as u64;
+            let id = self.id;
+            self.board
+                .borrow_mut()
+                .update(id, |status| status.bytes_received += written);
         }
+        poll
     }
 
-    logger::log_line(
-        log_path,
-        &format!(
-            "Server auth methods: {}",
-            method_set_to_csv(&remaining_methods)
-        ),
-    );
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_flush(cx)
+    }
 
-    let mut supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
-    let mut supports_pass = supports_method(&remaining_methods, MethodKind::Password);
-    let mut supports_pubkey = supports_method(&remaining_methods, MethodKind::PublicKey);
-    let mut pubkey_partially_accepted = false;
+    fn poll_shutdown(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
 
-    let best_rsa_hash = if supports_pubkey {
-        match session.best_supported_rsa_hash().await {
-            Ok(v) => v.flatten(),
-            Err(err) => {
+/// Pumps one forwarded connection, tracking it in the forward's open-connection count.
+async fn bridge_forwarded_connection<F>(
+    board: SharedForwardBoard,
+    id: usize,
+    socket: tokio::net::TcpStream,
+    open_channel: F,
+) -> Result<()>
+where
+    F: Future<Output = Result<russh::Channel<client::Msg>>>,
+{
+    let _ = socket.set_nodelay(true);
+    board
+        .borrow_mut()
+        .update(id, |status| status.open_connections += 1);
+    let result = async {
+        let mut stream = open_channel.await?.into_stream();
+        let mut local = CountingStream {
+            inner: socket,
+            board: Rc::clone(&board),
+            id,
+        };
+        tokio::io::copy_bidirectional(&mut local, &mut stream).await?;
+        Ok(())
+    }
+    .await;
+    board.borrow_mut().update(id, |status| {
+        status.open_connections = status.open_connections.saturating_sub(1);
+    });
+    result
+}
+
+/// Server-initiated channels handed from the session handler to the connection worker.
+enum ServerChannel {
+    ForwardedTcpip {
+        channel: russh::Channel<client::Msg>,
+        connected_address: String,
+        connected_port: u32,
+        originator: String,
+    },
+    AgentForward(russh::Channel<client::Msg>),
+}
+
+/// A remote forward the server accepted, with the port it actually bound.
+struct RemoteForwardBinding {
+    forward: crate::model::RemoteForward,
+    bound_port: Option<u32>,
+    error: Option<String>,
+}
+
+/// Sends `tcpip-forward` requests for the profile's remote forwards. Needs exclusive access to
+/// the handle, so it runs before the session is shared.
+async fn request_remote_forwards(
+    session: &mut SshHandle,
+    settings: &ConnectionSettings,
+    log_path: &str,
+) -> Vec<RemoteForwardBinding> {
+    let mut bindings = Vec::new();
+    for forward in settings.remote_forwards.iter().filter(|f| f.is_complete()) {
+        let address = forward.remote_bind_address();
+        let requested = u32::from(forward.remote_port);
+        // Still part of connecting: a server that never answers must not hang the group.
+        let reply = within_connect_timeout(
+            settings.connect_timeout(),
+            &format!("remote forward {address}:{requested}"),
+            session.tcpip_forward(address.clone(), requested),
+        )
+        .await
+        .map_err(|timeout| timeout.to_string())
+        .and_then(|reply| {
+            reply.map_err(|err| {
+                format!("Server refused remote forward {address}:{requested}: {err}")
+            })
+        });
+        let binding = match reply {
+            Ok(allocated) => {
+                let bound = if requested == 0 { allocated } else { requested };
                 logger::log_line(
                     log_path,
-                    &format!("Unable to query best RSA hash from server: {err}"),
+                    &format!("Remote forward {address}:{bound} accepted by server."),
                 );
-                None
+                RemoteForwardBinding {
+                    forward: forward.clone(),
+                    bound_port: Some(bound),
+                    error: None,
+                }
             }
-        }
-    } else {
-        None
-    };
-
-    if !authenticated && supports_pubkey && !settings.private_key_path.trim().is_empty() {
-        let key_path = settings.private_key_path.trim();
-        logger::log_line(
-            log_path,
-            &format!("Authenticating via private key: {key_path}"),
-        );
+            Err(message) => {
+                logger::log_line(log_path, &message);
+                RemoteForwardBinding {
+                    forward: forward.clone(),
+                    bound_port: None,
+                    error: Some(message),
+                }
+            }
+        };
+        bindings.push(binding);
+    }
+    bindings
+}
 
-        let private_key = Path::new(key_path);
-        if !private_key.exists() {
-            logger::log_line(log_path, "Private key path does not exist.");
-        } else {
-            let sidecar_pub = Path::new(&format!("{key_path}.pub")).exists();
-            if sidecar_pub {
-                logger::log_line(
-                    log_path,
-                    "Sidecar .pub file detected; russh derives public key from the private key.",
-                );
+/// Port forwards owned by a connection group; they stop when the shared session ends.
+struct ActiveForwards {
+    board: SharedForwardBoard,
+    abort_handles: Vec<tokio::task::AbortHandle>,
+}
+
+impl ActiveForwards {
+    fn start(
+        settings: &ConnectionSettings,
+        session: &Rc<SshHandle>,
+        remote_bindings: Vec<RemoteForwardBinding>,
+        server_channel_rx: UnboundedReceiver<ServerChannel>,
+        log_path: &str,
+    ) -> Self {
+        let board: SharedForwardBoard = Rc::default();
+        let mut abort_handles = Vec::new();
+        for forward in settings.local_forwards.iter().filter(|f| f.is_complete()) {
+            let id = board.borrow_mut().register(forward.describe());
+            let task = tokio::task::spawn_local(run_local_forward(
+                id,
+                forward.clone(),
+                Rc::clone(session),
+                Rc::clone(&board),
+                log_path.to_string(),
+            ));
+            abort_handles.push(task.abort_handle());
+        }
+
+        for forward in settings.dynamic_forwards.iter().filter(|f| f.is_complete()) {
+            let id = board.borrow_mut().register(forward.describe());
+            let task = tokio::task::spawn_local(run_dynamic_forward(
+                id,
+                forward.bind_addr(),
+                Rc::clone(session),
+                Rc::clone(&board),
+                log_path.to_string(),
+            ));
+            abort_handles.push(task.abort_handle());
+        }
+
+        let mut remote_routes = Vec::new();
+        for binding in remote_bindings {
+            let description = binding.forward.describe(binding.bound_port);
+            let id = board.borrow_mut().register(description);
+            board.borrow_mut().update(id, |status| {
+                status.state = if binding.bound_port.is_some() {
+                    ForwardState::Active
+                } else {
+                    ForwardState::Failed
+                };
+                status.last_error = binding.error.clone();
+            });
+            if let Some(bound_port) = binding.bound_port {
+                remote_routes.push((id, bound_port, binding.forward));
             }
+        }
+        let task = tokio::task::spawn_local(run_server_channel_dispatch(
+            remote_routes,
+            settings.forward_agent,
+            server_channel_rx,
+            Rc::clone(&board),
+            log_path.to_string(),
+        ));
+        abort_handles.push(task.abort_handle());
 
-            if let Some(fmt) = detect_private_key_format(private_key) {
-                logger::log_line(log_path, &format!("Detected private key format: {fmt}"));
-            } else {
-                logger::log_line(
-                    log_path,
-                    "Could not determine private key format from file header.",
-                );
+        Self {
+            board,
+            abort_handles,
+        }
+    }
+
+    fn send_snapshot(&self, ui_tx: &Sender<UiMessage>) {
+        let board = self.board.borrow();
+        if !board.statuses.is_empty() {
+            send_message(ui_tx, UiMessage::Forwards(board.statuses.clone()));
+        }
+    }
+
+    fn broadcast_changes(&self, clients: &[ActiveTerminalClient]) {
+        let Some(statuses) = self.board.borrow_mut().take_changes() else {
+            return;
+        };
+        for client in clients {
+            send_message(&client.ui_tx, UiMessage::Forwards(statuses.clone()));
+        }
+    }
+
+    fn stop(&mut self) {
+        for handle in self.abort_handles.drain(..) {
+            handle.abort();
+        }
+        let mut board = self.board.borrow_mut();
+        for status in &mut board.statuses {
+            status.state = ForwardState::Stopped;
+            status.open_connections = 0;
+        }
+    }
+}
+
+async fn run_local_forward(
+    id: usize,
+    forward: crate::model::LocalForward,
+    session: Rc<SshHandle>,
+    board: SharedForwardBoard,
+    log_path: String,
+) {
+    let bind_addr = forward.bind_addr();
+    let remote_host = forward.remote_host.trim().to_string();
+    let remote_port = forward.remote_port;
+    let listener = match tokio::net::TcpListener::bind(&bind_addr).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            record_forward_error(
+                &board,
+                id,
+                &log_path,
+                format!("Failed to listen on {bind_addr}: {err}"),
+            );
+            board
+                .borrow_mut()
+                .update(id, |status| status.state = ForwardState::Failed);
+            return;
+        }
+    };
+    logger::log_line(
+        &log_path,
+        &format!("Local forward listening on {bind_addr} -> {remote_host}:{remote_port}."),
+    );
+    board
+        .borrow_mut()
+        .update(id, |status| status.state = ForwardState::Active);
+
+    // Dropping the set (when this task is aborted) aborts every tunnelled connection.
+    let mut connections = tokio::task::JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (socket, peer) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        record_forward_error(
+                            &board,
+                            id,
+                            &log_path,
+                            format!("Accept failed on {bind_addr}: {err}"),
+                        );
+                        tokio::time::sleep(SESSION_HOUSEKEEPING_INTERVAL).await;
+                        continue;
+                    }
+                };
+                let session = Rc::clone(&session);
+                let board = Rc::clone(&board);
+                let log_path = log_path.clone();
+                let remote_host = remote_host.clone();
+                connections.spawn_local(async move {
+                    let open_channel = async {
+                        session
+                            .channel_open_direct_tcpip(
+                                remote_host.clone(),
+                                u32::from(remote_port),
+                                peer.ip().to_string(),
+                                u32::from(peer.port()),
+                            )
+                            .await
+                            .with_context(|| {
+                                format!("Server refused tunnel to {remote_host}:{remote_port}")
+                            })
+                    };
+                    if let Err(err) =
+                        bridge_forwarded_connection(Rc::clone(&board), id, socket, open_channel)
+                            .await
+                    {
+                        record_forward_error(
+                            &board,
+                            id,
+                            &log_path,
+                            format!("Forward from {peer} failed: {err:#}"),
+                        );
+                    }
+                });
             }
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
 
-            let saved_passphrase = if settings.key_passphrase.trim().is_empty() {
-                None
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SocksVersion {
+    V4,
+    V5,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SocksConnectRequest {
+    version: SocksVersion,
+    host: String,
+    port: u16,
+}
+
+async fn read_nul_terminated<S>(stream: &mut S, limit: usize) -> Result<Vec<u8>>
+where
+    S: tokio::io::AsyncRead + Unpin,
+{
+    let mut out = Vec::new();
+    loop {
+        let byte = stream.read_u8().await?;
+        if byte == 0 {
+            return Ok(out);
+        }
+        if out.len() >= limit {
+            return Err(anyhow!("SOCKS4 field is too long"));
+        }
+        out.push(byte);
+    }
+}
+
+/// Server side of a SOCKS4/4a/5 handshake, up to (not including) the CONNECT reply.
+async fn socks_server_handshake<S>(stream: &mut S) -> Result<SocksConnectRequest>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    match stream.read_u8().await? {
+        0x04 => {
+            let command = stream.read_u8().await?;
+            let port = stream.read_u16().await?;
+            let mut ip = [0u8; 4];
+            stream.read_exact(&mut ip).await?;
+            let _user_id = read_nul_terminated(stream, 255).await?;
+            if command != 0x01 {
+                stream.write_all(&[0x00, 0x5B, 0, 0, 0, 0, 0, 0]).await?;
+                return Err(anyhow!("SOCKS4 command {command:#04x} is not supported"));
+            }
+            // SOCKS4a: 0.0.0.x (x != 0) means a host name follows the user id.
+            let host = if ip[..3] == [0, 0, 0] && ip[3] != 0 {
+                String::from_utf8_lossy(&read_nul_terminated(stream, 255).await?).into_owned()
             } else {
-                Some(settings.key_passphrase.as_str())
+                std::net::Ipv4Addr::from(ip).to_string()
+            };
+            Ok(SocksConnectRequest {
+                version: SocksVersion::V4,
+                host,
+                port,
+            })
+        }
+        0x05 => {
+            let method_count = stream.read_u8().await?;
+            let mut methods = vec![0u8; usize::from(method_count)];
+            stream.read_exact(&mut methods).await?;
+            if !methods.contains(&0x00) {
+                stream.write_all(&[0x05, 0xFF]).await?;
+                return Err(anyhow!("SOCKS5 client does not offer no-auth"));
+            }
+            stream.write_all(&[0x05, 0x00]).await?;
+
+            let mut header = [0u8; 4];
+            stream.read_exact(&mut header).await?;
+            let host = match header[3] {
+                0x01 => {
+                    let mut ip = [0u8; 4];
+                    stream.read_exact(&mut ip).await?;
+                    std::net::Ipv4Addr::from(ip).to_string()
+                }
+                0x04 => {
+                    let mut ip = [0u8; 16];
+                    stream.read_exact(&mut ip).await?;
+                    std::net::Ipv6Addr::from(ip).to_string()
+                }
+                0x03 => {
+                    let len = stream.read_u8().await?;
+                    let mut name = vec![0u8; usize::from(len)];
+                    stream.read_exact(&mut name).await?;
+                    String::from_utf8_lossy(&name).into_owned()
+                }
+                other => {
+                    socks_server_reply(stream, SocksVersion::V5, Some(0x08)).await?;
+                    return Err(anyhow!("SOCKS5 address type {other:#04x} is not supported"));
+                }
             };
+            let port = stream.read_u16().await?;
+            if header[1] != 0x01 {
+                socks_server_reply(stream, SocksVersion::V5, Some(0x07)).await?;
+                return Err(anyhow!(
+                    "SOCKS5 command {:#04x} is not supported",
+                    header[1]
+                ));
+            }
+            Ok(SocksConnectRequest {
+                version: SocksVersion::V5,
+                host,
+                port,
+            })
+        }
+        other => Err(anyhow!("unsupported SOCKS version {other}")),
+    }
+}
 
-            let mut key_auth_result = match authenticate_with_private_key(
-                &mut session,
-                username,
-                private_key,
-                saved_passphrase,
-                best_rsa_hash,
-            )
-            .await
-            {
-                Ok(result) => Some(result),
-                Err(err) => {
-                    logger::log_line(log_path, &format!("Private key auth failed: {err}"));
-                    if error_mentions_passphrase(&err) {
-                        logger::log_line(
-                            log_path,
-                            "Private key may need a passphrase; prompting user.",
+/// Answers a CONNECT; `failure` carries the SOCKS5 reply code (SOCKS4 only has "rejected").
+async fn socks_server_reply<S>(
+    stream: &mut S,
+    version: SocksVersion,
+    failure: Option<u8>,
+) -> Result<()>
+where
+    S: tokio::io::AsyncWrite + Unpin,
+{
+    let reply: &[u8] = match (version, failure) {
+        (SocksVersion::V4, None) => &[0x00, 0x5A, 0, 0, 0, 0, 0, 0],
+        (SocksVersion::V4, Some(_)) => &[0x00, 0x5B, 0, 0, 0, 0, 0, 0],
+        (SocksVersion::V5, None) => &[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0],
+        (SocksVersion::V5, Some(code)) => &[0x05, code, 0x00, 0x01, 0, 0, 0, 0, 0, 0],
+    };
+    stream.write_all(reply).await?;
+    Ok(())
+}
+
+async fn run_dynamic_forward(
+    id: usize,
+    bind_addr: String,
+    session: Rc<SshHandle>,
+    board: SharedForwardBoard,
+    log_path: String,
+) {
+    let listener = match tokio::net::TcpListener::bind(&bind_addr).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            record_forward_error(
+                &board,
+                id,
+                &log_path,
+                format!("Failed to listen on {bind_addr}: {err}"),
+            );
+            board
+                .borrow_mut()
+                .update(id, |status| status.state = ForwardState::Failed);
+            return;
+        }
+    };
+    logger::log_line(
+        &log_path,
+        &format!("SOCKS forward listening on {bind_addr}."),
+    );
+    board
+        .borrow_mut()
+        .update(id, |status| status.state = ForwardState::Active);
+
+    let mut connections = tokio::task::JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (mut socket, peer) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        record_forward_error(
+                            &board,
+                            id,
+                            &log_path,
+                            format!("Accept failed on {bind_addr}: {err}"),
                         );
-                        match request_auth_responses(
-                            ui_tx,
-                            &worker_rx,
-                            username,
-                            "Private key authentication may require a passphrase.",
-                            vec![AuthPromptItem {
-                                text: "Key passphrase (optional):".to_string(),
-                                echo: false,
-                            }],
-                            log_path,
-                        ) {
-                            Ok(responses) => {
-                                let pw = responses.first().cloned().unwrap_or_default();
-                                if !pw.is_empty() {
-                                    match authenticate_with_private_key(
-                                        &mut session,
-                                        username,
-                                        private_key,
-                                        Some(&pw),
-                                        best_rsa_hash,
+                        tokio::time::sleep(SESSION_HOUSEKEEPING_INTERVAL).await;
+                        continue;
+                    }
+                };
+                let session = Rc::clone(&session);
+                let board = Rc::clone(&board);
+                let log_path = log_path.clone();
+                connections.spawn_local(async move {
+                    let result = async {
+                        let request = tokio::time::timeout(
+                            PROXY_HANDSHAKE_TIMEOUT,
+                            socks_server_handshake(&mut socket),
+                        )
+                        .await
+                        .map_err(|_| anyhow!("SOCKS client sent no request"))??;
+                        let channel = match session
+                            .channel_open_direct_tcpip(
+                                request.host.clone(),
+                                u32::from(request.port),
+                                peer.ip().to_string(),
+                                u32::from(peer.port()),
+                            )
+                            .await
+                        {
+                            Ok(channel) => channel,
+                            Err(err) => {
+                                let _ = socks_server_reply(&mut socket, request.version, Some(0x05))
+                                    .await;
+                                return Err(anyhow::Error::new(err).context(format!(
+                                    "Server refused tunnel to {}:{}",
+                                    request.host, request.port
+                                )));
+                            }
+                        };
+                        socks_server_reply(&mut socket, request.version, None).await?;
+                        bridge_forwarded_connection(
+                            Rc::clone(&board),
+                            id,
+                            socket,
+                            async { Ok(channel) },
+                        )
+                        .await
+                    }
+                    .await;
+                    if let Err(err) = result {
+                        record_forward_error(
+                            &board,
+                            id,
+                            &log_path,
+                            format!("SOCKS connection from {peer} failed: {err:#}"),
+                        );
+                    }
+                });
+            }
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+/// Routes server-initiated channels: `forwarded-tcpip` connections are bridged to the local
+/// target of the remote forward whose bound port they arrived on, and agent channels to the
+/// local ssh-agent when the profile forwards it.
+async fn run_server_channel_dispatch(
+    remote_routes: Vec<(usize, u32, crate::model::RemoteForward)>,
+    forward_agent: bool,
+    mut server_channel_rx: UnboundedReceiver<ServerChannel>,
+    board: SharedForwardBoard,
+    log_path: String,
+) {
+    let mut connections = tokio::task::JoinSet::new();
+    loop {
+        tokio::select! {
+            maybe_channel = server_channel_rx.recv() => {
+                let Some(server_channel) = maybe_channel else {
+                    break;
+                };
+                match server_channel {
+                    ServerChannel::ForwardedTcpip {
+                        channel,
+                        connected_address,
+                        connected_port,
+                        originator,
+                    } => {
+                        let route = remote_routes
+                            .iter()
+                            .filter(|(_, port, _)| *port == connected_port)
+                            .min_by_key(|(_, _, forward)| {
+                                forward.remote_bind_address() != connected_address
+                            });
+                        let Some((id, _, forward)) = route else {
+                            logger::log_line(
+                                &log_path,
+                                &format!(
+                                    "Ignoring forwarded connection for unknown listener {connected_address}:{connected_port}."
+                                ),
+                            );
+                            let _ = channel.close().await;
+                            continue;
+                        };
+                        let id = *id;
+                        let target = forward.local_target();
+                        let board = Rc::clone(&board);
+                        let log_path = log_path.clone();
+                        connections.spawn_local(async move {
+                            let result = match tokio::net::TcpStream::connect(&target).await {
+                                Ok(socket) => {
+                                    bridge_forwarded_connection(
+                                        Rc::clone(&board),
+                                        id,
+                                        socket,
+                                        async { Ok(channel) },
                                     )
                                     .await
-                                    {
-                                        Ok(result) => Some(result),
-                                        Err(err) => {
-                                            logger::log_line(
-                                                log_path,
-                                                &format!(
-                                                    "Private key auth (with passphrase) failed: {err}"
-                                                ),
-                                            );
-                                            None
-                                        }
-                                    }
-                                } else {
-                                    None
                                 }
+                                Err(err) => {
+                                    let _ = channel.close().await;
+                                    Err(anyhow::Error::new(err)
+                                        .context(format!("Failed to connect to {target}")))
+                                }
+                            };
+                            if let Err(err) = result {
+                                record_forward_error(
+                                    &board,
+                                    id,
+                                    &log_path,
+                                    format!("Forwarded connection from {originator} failed: {err:#}"),
+                                );
                             }
-                            Err(err) => {
-                                logger::log_line(log_path, &format!("Auth prompt failed: {err}"));
-                                None
-                            }
-                        }
-                    } else {
-                        if saved_passphrase.is_some() {
+                        });
+                    }
+                    ServerChannel::AgentForward(channel) => {
+                        if !forward_agent {
                             logger::log_line(
-                                log_path,
-                                "Skipping passphrase re-prompt because a saved passphrase already exists and the error was not passphrase-related.",
+                                &log_path,
+                                "Rejected agent channel: agent forwarding is disabled for this profile.",
                             );
+                            let _ = channel.close().await;
+                            continue;
                         }
-                        None
+                        let log_path = log_path.clone();
+                        connections.spawn_local(async move {
+                            let result = async {
+                                let mut agent = connect_local_agent_stream().await?;
+                                let mut stream = channel.into_stream();
+                                tokio::io::copy_bidirectional(&mut stream, &mut agent).await?;
+                                Ok::<(), anyhow::Error>(())
+                            }
+                            .await;
+                            if let Err(err) = result {
+                                logger::log_line(
+                                    &log_path,
+                                    &format!("Forwarded agent channel failed: {err:#}"),
+                                );
+                            }
+                        });
                     }
                 }
-            };
-
-            if let Some(auth_result) = key_auth_result.take() {
-                let partial =
-                    apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+            }
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+    // Keep bridging connections that are already open until the worker stops this task.
+    while connections.join_next().await.is_some() {}
+}
 
-                if authenticated {
-                    logger::log_line(log_path, "Private key authentication succeeded.");
-                } else if partial {
-                    pubkey_partially_accepted = true;
-                    logger::log_line(
-                        log_path,
-                        "Private key accepted, but server requires additional authentication.",
-                    );
-                    send_ui_status(
-                        ui_tx,
-                        IssueKind::Authentication,
-                        "Additional authentication required (server policy).",
+enum HostKeyVerificationMode {
+    Interactive {
+        ui_tx: Sender<UiMessage>,
+        decision_rx: Arc<Mutex<Receiver<HostKeyDecision>>>,
+        log_path: String,
+    },
+    NonInteractive {
+        log_path: String,
+    },
+}
+
+struct KnownHostsClient {
+    host: String,
+    port: u16,
+    policy: HostKeyPolicy,
+    mode: HostKeyVerificationMode,
+    server_channel_tx: Option<UnboundedSender<ServerChannel>>,
+    observer: Option<SessionObserver>,
+    /// Files read and written instead of the user's known_hosts (tests only).
+    known_hosts_files: Option<(Vec<PathBuf>, PathBuf)>,
+}
+
+/// What the session's handler has seen: the algorithms of the last key exchange and, once
+/// the transport dies unexpectedly, why.
+#[derive(Debug, Default)]
+struct SessionObservations {
+    negotiated: Option<NegotiatedAlgorithms>,
+    lost: Option<String>,
+    /// Why the handshake was refused without a prompt (revoked key or host key policy).
+    host_key_rejection: Option<ConnectionError>,
+    /// When the host key prompt still waiting for the user was shown.
+    host_key_prompt_since: Option<Instant>,
+    /// Time spent on host key prompts that have been answered.
+    host_key_prompt_time: Duration,
+}
+
+type SessionObserver = Arc<Mutex<SessionObservations>>;
+
+/// How long the user has spent deciding on host keys during this handshake so far.
+fn host_key_prompt_time(observer: &SessionObserver) -> Duration {
+    observer
+        .lock()
+        .map(|observed| {
+            observed.host_key_prompt_time
+                + observed
+                    .host_key_prompt_since
+                    .map(|since| since.elapsed())
+                    .unwrap_or_default()
+        })
+        .unwrap_or_default()
+}
+
+fn connection_lost_reason(observer: &SessionObserver) -> Option<String> {
+    observer.lock().ok()?.lost.clone()
+}
+
+fn connection_lost_message(err: &russh::Error) -> String {
+    match err {
+        russh::Error::KeepaliveTimeout => {
+            "Connection lost: the server stopped answering keepalives.".to_string()
+        }
+        err => format!("Connection lost: {err}."),
+    }
+}
+
+impl KnownHostsClient {
+    fn interactive(
+        host: String,
+        port: u16,
+        ui_tx: Sender<UiMessage>,
+        decision_rx: Arc<Mutex<Receiver<HostKeyDecision>>>,
+        log_path: String,
+    ) -> Self {
+        Self {
+            host,
+            port,
+            policy: HostKeyPolicy::Ask,
+            mode: HostKeyVerificationMode::Interactive {
+                ui_tx,
+                decision_rx,
+                log_path,
+            },
+            server_channel_tx: None,
+            observer: None,
+            known_hosts_files: None,
+        }
+    }
+
+    fn non_interactive(host: String, port: u16, log_path: String) -> Self {
+        Self {
+            host,
+            port,
+            policy: HostKeyPolicy::Ask,
+            mode: HostKeyVerificationMode::NonInteractive { log_path },
+            server_channel_tx: None,
+            observer: None,
+            known_hosts_files: None,
+        }
+    }
+
+    fn with_policy(mut self, policy: HostKeyPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    fn with_server_channels(mut self, tx: Option<UnboundedSender<ServerChannel>>) -> Self {
+        self.server_channel_tx = tx;
+        self
+    }
+
+    fn with_observer(mut self, observer: SessionObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    #[cfg(test)]
+    fn with_known_hosts_files(mut self, read: Vec<PathBuf>, write: PathBuf) -> Self {
+        self.known_hosts_files = Some((read, write));
+        self
+    }
+
+    fn read_paths(&self) -> Vec<PathBuf> {
+        match &self.known_hosts_files {
+            Some((read, _)) => read.clone(),
+            None => known_hosts_read_paths(),
+        }
+    }
+
+    fn write_path(&self) -> PathBuf {
+        match &self.known_hosts_files {
+            Some((_, write)) => write.clone(),
+            None => known_hosts_write_path(),
+        }
+    }
+
+    fn log_path(&self) -> &str {
+        match &self.mode {
+            HostKeyVerificationMode::Interactive { log_path, .. }
+            | HostKeyVerificationMode::NonInteractive { log_path } => log_path,
+        }
+    }
+
+    fn verify_server_key(
+        &mut self,
+        server_public_key: &keys::PublicKey,
+    ) -> Result<bool, russh::Error> {
+        let paths = self.read_paths();
+        match known_hosts::check(&paths, &self.host, self.port, server_public_key)? {
+            HostKeyVerdict::Trusted { .. } => Ok(true),
+            HostKeyVerdict::Revoked { path, line } => {
+                self.reject_host_key(ConnectionError::HostKeyRevoked {
+                    host: known_hosts::host_port_name(&self.host, self.port),
+                    path: path.display().to_string(),
+                    line,
+                })
+            }
+            HostKeyVerdict::Changed { path, line } => match self.policy {
+                HostKeyPolicy::Ask => {
+                    self.handle_host_key_prompt(server_public_key, Some((path, line)), 0)
+                }
+                HostKeyPolicy::AcceptNew | HostKeyPolicy::Strict => {
+                    self.reject_host_key(ConnectionError::HostKeyChanged {
+                        host: known_hosts::host_port_name(&self.host, self.port),
+                        path: path.display().to_string(),
+                        line,
+                    })
+                }
+            },
+            HostKeyVerdict::Unknown { cert_authorities } => {
+                if cert_authorities > 0 {
+                    // Host certificates are unsupported (see `known_hosts::Marker`), so a
+                    // CA-signed server presents its bare key and is pinned like any other.
+                    logger::log_line(
+                        self.log_path(),
+                        &format!(
+                            "{}:{} is covered by {cert_authorities} @cert-authority entr{}, but host certificates are not validated; treating the key as unknown.",
+                            self.host,
+                            self.port,
+                            if cert_authorities == 1 { "y" } else { "ies" }
+                        ),
                     );
+                }
+                match self.policy {
+                    HostKeyPolicy::Ask => {
+                        self.handle_host_key_prompt(server_public_key, None, cert_authorities)
+                    }
+                    HostKeyPolicy::AcceptNew => self.pin_new_host_key(server_public_key),
+                    HostKeyPolicy::Strict => {
+                        self.reject_host_key(ConnectionError::UnknownHostKey {
+                            host: known_hosts::host_port_name(&self.host, self.port),
+                            fingerprint: server_public_key
+                                .fingerprint(keys::HashAlg::Sha256)
+                                .to_string(),
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    /// Refuses the key without prompting; the reason replaces russh's generic handshake error.
+    fn reject_host_key(&mut self, reason: ConnectionError) -> Result<bool, russh::Error> {
+        logger::log_line(self.log_path(), &format!("{reason}; refusing to connect."));
+        if let Some(observer) = &self.observer {
+            if let Ok(mut observed) = observer.lock() {
+                observed.host_key_rejection = Some(reason);
+            }
+        }
+        Ok(false)
+    }
+
+    fn pin_new_host_key(
+        &mut self,
+        server_public_key: &keys::PublicKey,
+    ) -> Result<bool, russh::Error> {
+        let path = self.write_path();
+        keys::known_hosts::learn_known_hosts_path(&self.host, self.port, server_public_key, &path)?;
+        logger::log_line(
+            self.log_path(),
+            &format!(
+                "Accepted new host key for {}:{} ({}) and pinned it to {}.",
+                self.host,
+                self.port,
+                server_public_key.fingerprint(keys::HashAlg::Sha256),
+                path.display()
+            ),
+        );
+        Ok(true)
+    }
+
+    fn handle_host_key_prompt(
+        &mut self,
+        server_public_key: &keys::PublicKey,
+        changed: Option<(PathBuf, usize)>,
+        cert_authorities: usize,
+    ) -> Result<bool, russh::Error> {
+        let changed_line = changed.as_ref().map(|(_, line)| *line);
+        // A replaced key stays in the file that pinned it; new keys go to the chosen target.
+        let known_hosts_path = changed
+            .map(|(path, _)| path)
+            .unwrap_or_else(|| self.write_path());
+        match &mut self.mode {
+            HostKeyVerificationMode::Interactive {
+                ui_tx,
+                decision_rx,
+                log_path,
+            } => {
+                let fingerprint =
+                    format!("{}", server_public_key.fingerprint(keys::HashAlg::Sha256));
+                let prompt = HostKeyPrompt {
+                    host: self.host.clone(),
+                    port: self.port,
+                    algorithm: server_public_key.algorithm().to_string(),
+                    fingerprint,
+                    known_hosts_path: known_hosts_path.to_string_lossy().into_owned(),
+                    changed_line,
+                    cert_authorities,
+                };
+                let prompt_message = if let Some(line) = changed_line {
+                    format!(
+                        "Host key changed for {}:{} (known_hosts line {}); waiting for user decision.",
+                        self.host, self.port, line
+                    )
                 } else {
+                    format!(
+                        "Unknown host key for {}:{}; waiting for user trust decision.",
+                        self.host, self.port
+                    )
+                };
+                logger::log_line(log_path.as_str(), &prompt_message);
+                if let Some(Ok(mut observed)) = self.observer.as_ref().map(|o| o.lock()) {
+                    observed.host_key_prompt_since = Some(Instant::now());
+                }
+                send_message(ui_tx, UiMessage::HostKeyPrompt(prompt));
+
+                // Every hop of a jump chain shares the tab's decision channel; hops are verified
+                // one at a time, so the lock is never contended.
+                let decision = match decision_rx.lock() {
+                    Ok(rx) => rx.recv_timeout(Duration::from_secs(600)),
+                    Err(_) => Err(RecvTimeoutError::Disconnected),
+                };
+                if let Some(Ok(mut observed)) = self.observer.as_ref().map(|o| o.lock()) {
+                    if let Some(since) = observed.host_key_prompt_since.take() {
+                        observed.host_key_prompt_time += since.elapsed();
+                    }
+                }
+                match decision {
+                    Ok(HostKeyDecision::TrustAndSave) => {
+                        keys::known_hosts::learn_known_hosts_path(
+                            &self.host,
+                            self.port,
+                            server_public_key,
+                            &known_hosts_path,
+                        )?;
+                        logger::log_line(
+                            log_path.as_str(),
+                            &format!(
+                                "Pinned host key for {}:{} to {}.",
+                                self.host,
+                                self.port,
+                                known_hosts_path.display()
+                            ),
+                        );
+                        Ok(true)
+                    }
+                    Ok(HostKeyDecision::ReplaceAndSave) => {
+                        let Some(line) = changed_line else {
+                            logger::log_line(
+                                log_path.as_str(),
+                                &format!(
+                                    "Ignoring replace-host-key decision for {}:{} because no existing entry was provided.",
+                                    self.host, self.port
+                                ),
+                            );
+                            return Ok(false);
+                        };
+                        remove_known_hosts_line(&known_hosts_path, line)?;
+                        keys::known_hosts::learn_known_hosts_path(
+                            &self.host,
+                            self.port,
+                            server_public_key,
+                            &known_hosts_path,
+                        )?;
+                        logger::log_line(
+                            log_path.as_str(),
+                            &format!(
+                                "Replaced pinned host key for {}:{} in {} (old line {}).",
+                                self.host,
+                                self.port,
+                                known_hosts_path.display(),
+                                line
+                            ),
+                        );
+                        Ok(true)
+                    }
+                    Ok(HostKeyDecision::Reject) => {
+                        logger::log_line(
+                            log_path.as_str(),
+                            &format!("User rejected host key for {}:{}.", self.host, self.port),
+                        );
+                        Ok(false)
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        logger::log_line(
+                            log_path.as_str(),
+                            &format!(
+                                "Timed out waiting for host key decision for {}:{}.",
+                                self.host, self.port
+                            ),
+                        );
+                        Ok(false)
+                    }
+                    Err(RecvTimeoutError::Disconnected) => Ok(false),
+                }
+            }
+            HostKeyVerificationMode::NonInteractive { log_path } => {
+                let fingerprint =
+                    format!("{}", server_public_key.fingerprint(keys::HashAlg::Sha256));
+                if let Some(line) = changed_line {
                     logger::log_line(
-                        log_path,
-                        "Private key auth failed: Username/PublicKey combination invalid",
+                        log_path.as_str(),
+                        &format!(
+                            "Detached transfer rejected changed host key for {}:{} at known_hosts line {} (fingerprint {}).",
+                            self.host, self.port, line, fingerprint
+                        ),
                     );
+                    return Err(keys::Error::KeyChanged { line }.into());
                 }
 
-                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
-                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
-                supports_pubkey = supports_method(&remaining_methods, MethodKind::PublicKey);
-            }
-        }
+                logger::log_line(
+                    log_path.as_str(),
+                    &format!(
+                        "Detached transfer rejected unknown host key for {}:{} (fingerprint {}).",
+                        self.host, self.port, fingerprint
+                    ),
+                );
+                Ok(false)
+            }
+        }
+    }
+}
+
+/// Handler for [`fetch_host_key`]: records the offered key and ends the handshake there.
+struct HostKeyProbe {
+    key: Arc<Mutex<Option<keys::PublicKey>>>,
+}
+
+impl client::Handler for HostKeyProbe {
+    type Error = russh::Error;
+
+    fn check_server_key(
+        &mut self,
+        server_public_key: &keys::PublicKey,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
+        if let Ok(mut slot) = self.key.lock() {
+            *slot = Some(server_public_key.clone());
+        }
+        async { Ok(false) }
+    }
+}
+
+/// Host key algorithms that make a server present the same type of key as a known_hosts
+/// entry for `pinned`. RSA pins also allow the SHA-2 signatures, which use the same key.
+fn host_key_algorithms_for(pinned: &str) -> String {
+    match pinned.trim() {
+        "ssh-rsa" => "rsa-sha2-512,rsa-sha2-256,ssh-rsa".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Connects to `host` just far enough to read its host key of type `algorithm` (as written in
+/// known_hosts; empty takes whatever the server prefers), through the global proxy if one is
+/// set. Blocks; call it off the UI thread.
+pub fn fetch_host_key(
+    host: &str,
+    port: u16,
+    algorithm: &str,
+    log_path: &str,
+) -> Result<keys::PublicKey> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| anyhow!("Failed to create async runtime: {err}"))?;
+    let settings = ConnectionSettings {
+        host: host.to_string(),
+        port,
+        algorithms: AlgorithmPreferences {
+            host_keys: host_key_algorithms_for(algorithm),
+            ..AlgorithmPreferences::default()
+        },
+        ..ConnectionSettings::default()
+    };
+    runtime.block_on(async {
+        let connect_timeout = settings.connect_timeout();
+        let transport = open_direct_transport(&settings, log_path).await?;
+        let slot = Arc::new(Mutex::new(None));
+        let probe = HostKeyProbe {
+            key: Arc::clone(&slot),
+        };
+        let target = format!("{host}:{port}");
+        // The probe refuses every key, so the handshake always ends in an error once the
+        // key has been seen; only a missing key means the fetch failed.
+        let handshake = within_connect_timeout(
+            connect_timeout,
+            &target,
+            client::connect_stream(Arc::new(client_config(&settings)?), transport, probe),
+        )
+        .await?;
+        let key = slot.lock().ok().and_then(|mut slot| slot.take());
+        match (key, handshake) {
+            (Some(key), _) => Ok(key),
+            (None, Err(err)) => {
+                Err(anyhow::Error::new(err).context(format!("SSH handshake with {target} failed")))
+            }
+            (None, Ok(_)) => Err(anyhow!("{target} did not present a host key")),
+        }
+    })
+}
+
+/// Replaces known_hosts line `line` of `path` with `key` for `host`:`port`.
+pub fn repin_known_host(
+    path: &Path,
+    line: usize,
+    host: &str,
+    port: u16,
+    key: &keys::PublicKey,
+) -> Result<()> {
+    remove_known_hosts_line(path, line)
+        .with_context(|| format!("Failed to update {}", path.display()))?;
+    keys::known_hosts::learn_known_hosts_path(host, port, key, path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+async fn run_detached_sftp_download(
+    request: DetachedTransferRequest,
+    resume_from_local: bool,
+) -> Result<()> {
+    let DetachedTransferRequest {
+        settings,
+        request_id,
+        remote_path,
+        local_path,
+        event_tx,
+        cancel_rx,
+        conflict_response_rx: _,
+        log_path,
+    } = request;
+
+    if transfer_cancel_requested(&cancel_rx) {
+        send_download_event(
+            &event_tx,
+            DownloadManagerEvent::Canceled {
+                request_id,
+                local_path,
+            },
+        );
+        return Ok(());
     }
 
-    if !authenticated && supports_pubkey {
-        logger::log_line(log_path, "Authenticating via ssh-agent.");
-        match authenticate_via_agent(&mut session, username, best_rsa_hash).await {
-            Ok(auth_result) => {
-                let partial =
-                    apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+    let AuthenticatedSession {
+        session,
+        jump_sessions: _jump_sessions,
+        ..
+    } = open_authenticated_session_for_transfer(&settings, &log_path).await?;
+    let sftp = open_sftp_channel(&session, &log_path).await?;
+    run_sftp_download_with_session(
+        sftp,
+        request_id,
+        remote_path,
+        local_path,
+        resume_from_local,
+        &event_tx,
+        &cancel_rx,
+    )
+    .await
+}
 
-                if authenticated {
-                    logger::log_line(log_path, "ssh-agent authentication succeeded.");
-                } else if partial {
-                    pubkey_partially_accepted = true;
-                    logger::log_line(
-                        log_path,
-                        "ssh-agent key accepted, but server requires additional authentication.",
-                    );
-                    send_ui_status(
-                        ui_tx,
-                        IssueKind::Authentication,
-                        "Additional authentication required (server policy).",
-                    );
-                } else {
-                    logger::log_line(log_path, "ssh-agent auth failed.");
-                }
+async fn run_detached_sftp_upload(
+    request: DetachedTransferRequest,
+    resume_from_remote_temp: bool,
+) -> Result<()> {
+    let DetachedTransferRequest {
+        settings,
+        request_id,
+        remote_path,
+        local_path,
+        event_tx,
+        cancel_rx,
+        conflict_response_rx,
+        log_path,
+    } = request;
 
-                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
-                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
-            }
-            Err(err) => {
-                logger::log_line(log_path, &format!("ssh-agent auth failed: {err}"));
-            }
-        }
+    if transfer_cancel_requested(&cancel_rx) {
+        send_download_event(
+            &event_tx,
+            DownloadManagerEvent::Canceled {
+                request_id,
+                local_path,
+            },
+        );
+        return Ok(());
     }
 
-    if !authenticated && supports_kbd {
-        logger::log_line(log_path, "Authenticating via keyboard-interactive.");
-        match authenticate_keyboard_interactive(
-            &mut session,
-            username,
-            ui_tx,
-            &worker_rx,
-            if settings.password.is_empty() {
-                None
-            } else {
-                Some(settings.password.as_str())
-            },
-            log_path,
-        )
-        .await
-        {
-            Ok(auth_result) => {
-                let partial =
-                    apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+    let AuthenticatedSession {
+        session,
+        jump_sessions: _jump_sessions,
+        ..
+    } = open_authenticated_session_for_transfer(&settings, &log_path).await?;
+    let sftp = open_sftp_channel(&session, &log_path).await?;
+    run_sftp_upload_with_session(
+        sftp,
+        request_id,
+        remote_path,
+        local_path,
+        resume_from_remote_temp,
+        UploadTransferControl {
+            request_id,
+            session: &session,
+            event_tx: &event_tx,
+            cancel_rx: &cancel_rx,
+            conflict_response_rx: &conflict_response_rx,
+            log_path: &log_path,
+        },
+    )
+    .await
+}
 
-                if partial && !authenticated {
-                    logger::log_line(
-                        log_path,
-                        "Keyboard-interactive accepted, but additional auth is required.",
-                    );
-                } else if !authenticated {
-                    logger::log_line(log_path, "Keyboard-interactive auth failed.");
-                }
+pub fn start_sftp_download_detached(
+    request: DetachedTransferRequest,
+    resume_from_local: bool,
+) -> thread::JoinHandle<()> {
+    let request_id = request.request_id;
+    let failure_tx = request.event_tx.clone();
+    thread::spawn(move || {
+        let result = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt.block_on(run_detached_sftp_download(request, resume_from_local)),
+            Err(err) => Err(anyhow!("Failed to create async runtime: {err}")),
+        };
 
-                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
-                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
-            }
-            Err(err) => {
-                logger::log_line(
-                    log_path,
-                    &format!("Keyboard-interactive auth failed: {err}"),
-                );
-            }
+        if let Err(err) = result {
+            send_download_event(
+                &failure_tx,
+                DownloadManagerEvent::Failed {
+                    request_id,
+                    issue: transfer_issue_from_error(&err),
+                },
+            );
         }
-    }
+    })
+}
 
-    if !authenticated && !settings.password.is_empty() && supports_pass {
-        if pubkey_partially_accepted {
-            logger::log_line(
-                log_path,
-                "Falling back to explicit password auth after pubkey partial authentication.",
+pub fn start_sftp_upload_detached(
+    request: DetachedTransferRequest,
+    resume_from_remote_temp: bool,
+) -> thread::JoinHandle<()> {
+    let request_id = request.request_id;
+    let failure_tx = request.event_tx.clone();
+    thread::spawn(move || {
+        let result = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt.block_on(run_detached_sftp_upload(request, resume_from_remote_temp)),
+            Err(err) => Err(anyhow!("Failed to create async runtime: {err}")),
+        };
+
+        if let Err(err) = result {
+            send_download_event(
+                &failure_tx,
+                DownloadManagerEvent::Failed {
+                    request_id,
+                    issue: transfer_issue_from_error(&err),
+                },
             );
-        } else {
-            logger::log_line(log_path, "Authenticating via password.");
         }
+    })
+}
 
-        match session
-            .authenticate_password(username, settings.password.clone())
-            .await
-        {
-            Ok(auth_result) => {
-                let partial =
-                    apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
+impl client::Handler for KnownHostsClient {
+    type Error = russh::Error;
 
-                if partial && !authenticated {
-                    logger::log_line(
-                        log_path,
-                        "Password accepted, but additional auth is required.",
-                    );
-                } else if !authenticated {
-                    logger::log_line(log_path, "Password auth failed.");
-                }
+    fn check_server_key(
+        &mut self,
+        server_public_key: &keys::PublicKey,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
+        let result = self.verify_server_key(server_public_key);
+        async move { result }
+    }
 
-                supports_kbd = supports_method(&remaining_methods, MethodKind::KeyboardInteractive);
-                supports_pass = supports_method(&remaining_methods, MethodKind::Password);
+    fn server_channel_open_forwarded_tcpip(
+        &mut self,
+        channel: russh::Channel<client::Msg>,
+        connected_address: &str,
+        connected_port: u32,
+        originator_address: &str,
+        originator_port: u32,
+        _session: &mut client::Session,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        // Without a worker to route it, the channel is dropped and the server sees it closed.
+        if let Some(tx) = &self.server_channel_tx {
+            let _ = tx.send(ServerChannel::ForwardedTcpip {
+                channel,
+                connected_address: connected_address.to_string(),
+                connected_port,
+                originator: format!("{originator_address}:{originator_port}"),
+            });
+        }
+        async { Ok(()) }
+    }
+
+    fn kex_done(
+        &mut self,
+        _shared_secret: Option<&[u8]>,
+        names: &russh::Names,
+        _session: &mut client::Session,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        if let Some(observer) = &self.observer {
+            if let Ok(mut observed) = observer.lock() {
+                observed.negotiated = Some(NegotiatedAlgorithms::from_names(names));
             }
-            Err(err) => {
-                logger::log_line(log_path, &format!("Password auth failed: {err}"));
+        }
+        async { Ok(()) }
+    }
+
+    fn disconnected(
+        &mut self,
+        reason: client::DisconnectReason<Self::Error>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        if let (Some(observer), client::DisconnectReason::Error(err)) = (&self.observer, &reason) {
+            if let Ok(mut observed) = observer.lock() {
+                observed.lost = Some(connection_lost_message(err));
+            }
+        }
+        async move {
+            match reason {
+                client::DisconnectReason::ReceivedDisconnect(_) => Ok(()),
+                client::DisconnectReason::Error(err) => Err(err),
             }
         }
     }
 
-    // Last resort: if the server supports password but not keyboard-interactive, we must
-    // ask the user explicitly for a password (the server cannot "prompt" in the shell).
-    if !authenticated && supports_pass && !supports_kbd {
-        logger::log_line(log_path, "Password needed; prompting user.");
-        let pw = loop {
-            let responses = request_auth_responses(
-                ui_tx,
-                &worker_rx,
-                username,
-                "Server requested password authentication.",
-                vec![AuthPromptItem {
-                    text: "Password:".to_string(),
-                    echo: false,
-                }],
-                log_path,
-            )?;
-            let pw = responses.first().cloned().unwrap_or_default();
-            if !pw.is_empty() {
-                break pw;
+    fn server_channel_open_agent_forward(
+        &mut self,
+        channel: russh::Channel<client::Msg>,
+        _session: &mut client::Session,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        if let Some(tx) = &self.server_channel_tx {
+            let _ = tx.send(ServerChannel::AgentForward(channel));
+        }
+        async { Ok(()) }
+    }
+}
+
+pub fn start_shell(
+    initial_client_id: u64,
+    settings: ConnectionSettings,
+    scrollback_len: usize,
+    ui_tx: Sender<UiMessage>,
+    worker_rx: Receiver<WorkerMessage>,
+    host_key_rx: Receiver<HostKeyDecision>,
+    log_path: String,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        logger::log_line(&log_path, "Starting SSH worker.");
+
+        let result = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => {
+                let local = tokio::task::LocalSet::new();
+                local.block_on(
+                    &rt,
+                    run_shell_async(
+                        initial_client_id,
+                        settings,
+                        scrollback_len,
+                        &ui_tx,
+                        worker_rx,
+                        host_key_rx,
+                        &log_path,
+                    ),
+                )
             }
-            logger::log_line(log_path, "Empty password submitted; reprompting.");
-            send_ui_status(ui_tx, IssueKind::Authentication, "Password required.");
+            Err(err) => Err(anyhow!("Failed to create async runtime: {err}")),
         };
 
-        let auth_result = session
-            .authenticate_password(username, pw)
-            .await
-            .context("SSH password authentication failed")?;
-        let _ = apply_auth_result(auth_result, &mut authenticated, &mut remaining_methods);
-    }
+        if let Err(err) = result {
+            logger::log_line(&log_path, &format!("Worker error: {err}"));
+            send_message(
+                &ui_tx,
+                UiMessage::Status(connection_status_from_error(&err)),
+            );
+            send_message(&ui_tx, UiMessage::Connected(false));
+        }
+    })
+}
 
-    if !authenticated {
-        return Err(ConnectionError::AuthenticationFailed.into());
+async fn run_shell_async(
+    initial_client_id: u64,
+    settings: ConnectionSettings,
+    scrollback_len: usize,
+    ui_tx: &Sender<UiMessage>,
+    worker_rx: Receiver<WorkerMessage>,
+    host_key_rx: Receiver<HostKeyDecision>,
+    log_path: &str,
+) -> Result<()> {
+    logger::log_line(log_path, "Validating settings.");
+    if settings.host.trim().is_empty() {
+        return Err(ConnectionError::MissingHost.into());
+    }
+    if settings.username.trim().is_empty() {
+        return Err(ConnectionError::MissingUsername.into());
     }
 
+    let config = Arc::new(client_config(&settings)?);
+
+    let host_key_rx = Arc::new(Mutex::new(host_key_rx));
+    let auth = SessionAuth::Interactive {
+        ui_tx,
+        worker_rx: &worker_rx,
+        host_key_rx: &host_key_rx,
+    };
+    let (server_channel_tx, server_channel_rx) = unbounded_channel();
+    let AuthenticatedSession {
+        mut session,
+        jump_sessions,
+        negotiated,
+        observer,
+    } = connect_authenticated_session(&settings, config, &auth, Some(server_channel_tx), log_path)
+        .await?;
+    if let Some(negotiated) = &negotiated {
+        send_message(ui_tx, UiMessage::Negotiated(negotiated.clone()));
+    }
+    let remote_forward_bindings = request_remote_forwards(&mut session, &settings, log_path).await;
+
     let session = Rc::new(session);
     let mut worker_rx = bridge_receiver_to_async(worker_rx);
     let mut active_terminal_clients: Vec<ActiveTerminalClient> = Vec::new();
     let mut active_sftp_clients: Vec<ActiveSftpClient> = Vec::new();
     let mut active_transfers: Vec<ActiveTransfer> = Vec::new();
     let mut worker_sender_disconnected = false;
+    let terminal_options = TerminalChannelOptions::from_settings(&settings, &observer);
     spawn_terminal_client(
         &mut active_terminal_clients,
         initial_client_id,
         Rc::clone(&session),
+        terminal_options.clone(),
         ui_tx.clone(),
         scrollback_len,
         log_path,
     );
+    let mut forwards = ActiveForwards::start(
+        &settings,
+        &session,
+        remote_forward_bindings,
+        server_channel_rx,
+        log_path,
+    );
 
     loop {
         active_terminal_clients.retain(|client| !client.abort_handle.is_finished());
         active_sftp_clients.retain(|client| !client.abort_handle.is_finished());
         active_transfers.retain(|transfer| !transfer.abort_handle.is_finished());
+        forwards.broadcast_changes(&active_terminal_clients);
+
+        if session.is_closed() {
+            let reason = connection_lost_reason(&observer)
+                .unwrap_or_else(|| "Transport disconnected. The SSH session closed.".to_string());
+            logger::log_line(log_path, &reason);
+            for client in &active_terminal_clients {
+                send_message(&client.ui_tx, UiMessage::ConnectionLost);
+            }
+            stop_active_terminal_clients(&mut active_terminal_clients, &reason);
+            stop_active_sftp_clients(&mut active_sftp_clients, &reason);
+            stop_active_transfers(
+                &mut active_transfers,
+                "Transfer paused because the SSH connection was lost. Reconnect the tab and click retry.",
+            );
+            forwards.stop();
+            disconnect_jump_sessions(&jump_sessions).await;
+            return Ok(());
+        }
 
         let mut pending_messages = VecDeque::new();
         if worker_sender_disconnected {
@@ -5521,18 +8404,37 @@ async fn run_shell_async(
                     client_id,
                     TerminalClientCommand::SetScrollback(rows),
                 ),
-                WorkerMessage::AttachTerminalClient {
-                    client_id,
-                    ui_tx,
-                    scrollback_len,
-                } => spawn_terminal_client(
+                WorkerMessage::StartRecording { client_id, request } => {
+                    send_terminal_client_command(
+                        &mut active_terminal_clients,
+                        client_id,
+                        TerminalClientCommand::StartRecording(request),
+                    )
+                }
+                WorkerMessage::StopRecording { client_id } => send_terminal_client_command(
                     &mut active_terminal_clients,
                     client_id,
-                    Rc::clone(&session),
+                    TerminalClientCommand::StopRecording,
+                ),
+                WorkerMessage::AttachTerminalClient {
+                    client_id,
                     ui_tx,
                     scrollback_len,
-                    log_path,
-                ),
+                } => {
+                    forwards.send_snapshot(&ui_tx);
+                    if let Some(negotiated) = &negotiated {
+                        send_message(&ui_tx, UiMessage::Negotiated(negotiated.clone()));
+                    }
+                    spawn_terminal_client(
+                        &mut active_terminal_clients,
+                        client_id,
+                        Rc::clone(&session),
+                        terminal_options.clone(),
+                        ui_tx,
+                        scrollback_len,
+                        log_path,
+                    )
+                }
                 WorkerMessage::DetachTerminalClient { client_id } => {
                     if let Some(index) = terminal_client_by_id(&active_terminal_clients, client_id)
                     {
@@ -5789,9 +8691,11 @@ async fn run_shell_async(
                 &mut active_transfers,
                 "Transfer paused because the SSH session disconnected. Reconnect the tab and click retry.",
             );
+            forwards.stop();
             let _ = session
                 .disconnect(Disconnect::ByApplication, "", "English")
                 .await;
+            disconnect_jump_sessions(&jump_sessions).await;
             logger::log_line(log_path, "Disconnected on request.");
             return Ok(());
         }
@@ -5805,9 +8709,11 @@ async fn run_shell_async(
                 &mut active_transfers,
                 "Transfer paused because the SSH session disconnected. Reconnect the tab and click retry.",
             );
+            forwards.stop();
             let _ = session
                 .disconnect(Disconnect::ByApplication, "", "English")
                 .await;
+            disconnect_jump_sessions(&jump_sessions).await;
             logger::log_line(
                 log_path,
                 "Session worker is idle; disconnecting SSH session.",
@@ -5823,6 +8729,26 @@ mod tests {
     use std::fs;
     use std::time::{SystemTime, UNIX_EPOCH};
 
+    #[test]
+    fn secret_prompts_redact_recorded_input() {
+        assert!(looks_like_secret_prompt("[sudo] password for alice: "));
+        assert!(looks_like_secret_prompt(
+            "Enter passphrase for key '/home/a/.ssh/id':"
+        ));
+        assert!(looks_like_secret_prompt("Verification PIN:"));
+        assert!(looks_like_secret_prompt("Verification code: "));
+        assert!(looks_like_secret_prompt("OTP?"));
+        assert!(looks_like_secret_prompt("Enter PIN "));
+        assert!(looks_like_secret_prompt("Password"));
+        assert!(!looks_like_secret_prompt("alice@web:~$ "));
+        assert!(!looks_like_secret_prompt("alice@token:~$ "));
+        assert!(!looks_like_secret_prompt("password reset complete"));
+        assert!(!looks_like_secret_prompt("Shipping:"));
+        assert!(!looks_like_secret_prompt("Spinner:"));
+        assert!(!looks_like_secret_prompt("Zip code area"));
+        assert_eq!(redact_input("hunter2\r"), "*******\r");
+    }
+
     #[test]
     fn blank_auth_prompt_gets_safe_label() {
         assert_eq!(auth_prompt_text("", false, 0), "Secret response:");
@@ -5839,6 +8765,116 @@ mod tests {
         assert!(!looks_like_password_prompt_text("Duo passcode or option:"));
     }
 
+    const HOST_KEY_A: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIJdD7y3aLq454yWBdwLWbieU1ebz9/cu7/QEXn9OIeZJ";
+    const HOST_KEY_B: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIA6rWI3G1sz07DnfFlrouTcysQlj2P+jpNSOEWD9OJ3X";
+
+    /// Runs one host key check of `HOST_KEY_A` for db.example against a known_hosts file
+    /// holding `contents`; returns the verdict, the recorded rejection and the file afterwards.
+    fn verify_against(
+        policy: HostKeyPolicy,
+        contents: &str,
+    ) -> (Result<bool, russh::Error>, Option<ConnectionError>, String) {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("rusty-host-key-policy-{unique}"));
+        fs::write(&path, contents).expect("write known_hosts");
+        let log_path = std::env::temp_dir().join(format!("rusty-host-key-policy-{unique}.log"));
+
+        let observer = SessionObserver::default();
+        let mut client = KnownHostsClient::non_interactive(
+            "db.example".to_string(),
+            22,
+            log_path.to_string_lossy().into_owned(),
+        )
+        .with_policy(policy)
+        .with_observer(observer.clone())
+        .with_known_hosts_files(vec![path.clone()], path.clone());
+        let key = keys::parse_public_key_base64(HOST_KEY_A).expect("valid key");
+        let verdict = client.verify_server_key(&key);
+
+        let rejection = observer.lock().unwrap().host_key_rejection.take();
+        let written = fs::read_to_string(&path).expect("read known_hosts");
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&log_path);
+        (verdict, rejection, written)
+    }
+
+    #[test]
+    fn host_key_policies_handle_trusted_unknown_changed_and_revoked_keys() {
+        let policies = [
+            HostKeyPolicy::Ask,
+            HostKeyPolicy::AcceptNew,
+            HostKeyPolicy::Strict,
+        ];
+        let trusted = format!("db.example ssh-ed25519 {HOST_KEY_A}\n");
+        let unknown = format!("other.example ssh-ed25519 {HOST_KEY_B}\n");
+        let changed = format!("db.example ssh-ed25519 {HOST_KEY_B}\n");
+        let revoked = format!("@revoked * ssh-ed25519 {HOST_KEY_A}\n{trusted}");
+
+        for policy in policies {
+            let (verdict, rejection, written) = verify_against(policy, &trusted);
+            assert!(verdict.unwrap(), "{policy:?}");
+            assert!(rejection.is_none(), "{policy:?}");
+            assert_eq!(written, trusted, "{policy:?}");
+
+            // Revocation wins over a pin for the same key, whatever the policy.
+            let (verdict, rejection, written) = verify_against(policy, &revoked);
+            assert!(!verdict.unwrap(), "{policy:?}");
+            assert!(
+                matches!(
+                    rejection,
+                    Some(ConnectionError::HostKeyRevoked { line: 1, .. })
+                ),
+                "{policy:?}"
+            );
+            assert_eq!(written, revoked, "{policy:?}");
+        }
+
+        // Ask without a UI (detached transfers) refuses unknown keys and errors on changed ones.
+        let (verdict, rejection, written) = verify_against(HostKeyPolicy::Ask, &unknown);
+        assert!(!verdict.unwrap());
+        assert!(rejection.is_none());
+        assert_eq!(written, unknown);
+        let (verdict, rejection, written) = verify_against(HostKeyPolicy::Ask, &changed);
+        assert!(matches!(
+            verdict,
+            Err(russh::Error::Keys(keys::Error::KeyChanged { line: 1 }))
+        ));
+        assert!(rejection.is_none());
+        assert_eq!(written, changed);
+
+        let (verdict, rejection, written) = verify_against(HostKeyPolicy::AcceptNew, &unknown);
+        assert!(verdict.unwrap());
+        assert!(rejection.is_none());
+        assert_eq!(
+            written,
+            format!("{unknown}db.example ssh-ed25519 {HOST_KEY_A}\n")
+        );
+
+        let (verdict, rejection, written) = verify_against(HostKeyPolicy::Strict, &unknown);
+        assert!(!verdict.unwrap());
+        assert!(matches!(
+            rejection,
+            Some(ConnectionError::UnknownHostKey { ref host, .. }) if host == "db.example"
+        ));
+        assert_eq!(written, unknown);
+
+        for policy in [HostKeyPolicy::AcceptNew, HostKeyPolicy::Strict] {
+            let (verdict, rejection, written) = verify_against(policy, &changed);
+            assert!(!verdict.unwrap(), "{policy:?}");
+            assert!(
+                matches!(
+                    rejection,
+                    Some(ConnectionError::HostKeyChanged { line: 1, .. })
+                ),
+                "{policy:?}"
+            );
+            assert_eq!(written, changed, "{policy:?}");
+        }
+    }
+
     #[test]
     fn remove_known_hosts_line_rewrites_target_entry() {
         let unique = SystemTime::now()
@@ -6031,4 +9067,429 @@ mod tests {
             .to_string()
             .contains("Remote path exists as a file where a folder is required"));
     }
+
+    #[test]
+    fn expand_proxy_command_substitutes_tokens() {
+        assert_eq!(
+            expand_proxy_command("ncat --proxy p:8080 %h %p", "db.internal", 2222, "ops").unwrap(),
+            "ncat --proxy p:8080 db.internal 2222"
+        );
+        assert_eq!(
+            expand_proxy_command("connect %r@%h 100%% %x%", "h", 22, "me").unwrap(),
+            "connect me@h 100% %x%"
+        );
+        assert_eq!(
+            expand_proxy_command("nc %h %p", "fe80::1", 22, "").unwrap(),
+            "nc fe80::1 22"
+        );
+    }
+
+    #[test]
+    fn expand_proxy_command_refuses_shell_metacharacters() {
+        for host in [
+            "x&calc",
+            "host;rm -rf ~",
+            "a|b",
+            "$(id)",
+            "`id`",
+            "%TEMP%",
+            "-oProxy",
+        ] {
+            let err = expand_proxy_command("nc %h %p", host, 22, "ops").unwrap_err();
+            assert!(
+                matches!(&err, ConnectionError::ProxyCommandUnsafeToken { token: "host", value } if value == host),
+                "{host}"
+            );
+            assert_eq!(
+                issue_kind_from_anyhow(&anyhow::Error::new(err)),
+                IssueKind::Configuration
+            );
+        }
+        assert!(matches!(
+            expand_proxy_command("connect %r@%h", "db", 22, "x&calc"),
+            Err(ConnectionError::ProxyCommandUnsafeToken { token: "user", .. })
+        ));
+        // Values the template never uses aren't checked.
+        assert!(expand_proxy_command("nc %h %p", "db", 22, "x&calc").is_ok());
+    }
+
+    #[test]
+    fn proxy_command_errors_map_to_issue_kinds() {
+        let spawn: anyhow::Error = ConnectionError::ProxyCommandSpawn {
+            command: "missing-helper".to_string(),
+            reason: "not found".to_string(),
+        }
+        .into();
+        assert_eq!(issue_kind_from_anyhow(&spawn), IssueKind::Configuration);
+
+        let exited = anyhow::Error::new(ConnectionError::ProxyCommandExited {
+            status: "exit code: 1".to_string(),
+            stderr: "connection refused".to_string(),
+        })
+        .context("SSH handshake failed");
+        assert_eq!(issue_kind_from_anyhow(&exited), IssueKind::Transport);
+    }
+
+    #[test]
+    fn http_connect_response_requires_success_status() {
+        assert!(check_http_connect_response("HTTP/1.1 200 Connection established\r\n\r\n").is_ok());
+
+        let denied = check_http_connect_response("HTTP/1.1 403 Forbidden\r\n\r\n").unwrap_err();
+        assert_eq!(issue_kind_from_anyhow(&denied), IssueKind::Transport);
+        assert!(denied.to_string().contains("403 Forbidden"));
+
+        let auth = check_http_connect_response("HTTP/1.0 407 Proxy Auth\r\n\r\n").unwrap_err();
+        assert!(auth.to_string().contains("requires authentication"));
+    }
+
+    #[test]
+    fn socks5_connect_authenticates_and_requests_domain_target() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let (mut client, mut server) = tokio::io::duplex(256);
+            let proxy = ProxySettings {
+                kind: ProxyKind::Socks5,
+                host: "proxy".to_string(),
+                username: "u".to_string(),
+                password: "pw".to_string(),
+                ..ProxySettings::default()
+            };
+            let server_task = tokio::spawn(async move {
+                let mut greeting = [0u8; 4];
+                server.read_exact(&mut greeting).await.unwrap();
+                assert_eq!(greeting, [0x05, 0x02, 0x00, 0x02]);
+                server.write_all(&[0x05, 0x02]).await.unwrap();
+
+                let mut auth = [0u8; 6];
+                server.read_exact(&mut auth).await.unwrap();
+                assert_eq!(&auth, b"\x01\x01u\x02pw");
+                server.write_all(&[0x01, 0x00]).await.unwrap();
+
+                let mut request = [0u8; 13];
+                server.read_exact(&mut request).await.unwrap();
+                assert_eq!(&request[..5], &[0x05, 0x01, 0x00, 0x03, 6]);
+                assert_eq!(&request[5..11], b"db.lan");
+                assert_eq!(&request[11..], &22u16.to_be_bytes());
+                server
+                    .write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+                    .await
+                    .unwrap();
+            });
+            socks5_connect(&mut client, &proxy, "db.lan", 22)
+                .await
+                .unwrap();
+            server_task.await.unwrap();
+        });
+    }
+
+    #[test]
+    fn forward_board_reports_each_change_once() {
+        let mut board = ForwardBoard::default();
+        let forward = crate::model::LocalForward {
+            bind_address: "::1".to_string(),
+            local_port: 15432,
+            remote_host: "db".to_string(),
+            remote_port: 5432,
+        };
+        let id = board.register(forward.describe());
+        assert_eq!(
+            board.take_changes().unwrap()[0].description,
+            "L [::1]:15432 -> db:5432"
+        );
+        assert!(board.take_changes().is_none());
+
+        board.update(id, |status| status.state = ForwardState::Active);
+        let statuses = board.take_changes().unwrap();
+        assert_eq!(statuses[0].state, ForwardState::Active);
+        assert!(board.take_changes().is_none());
+    }
+
+    #[test]
+    fn remote_forward_description_uses_server_allocated_port() {
+        let forward = crate::model::RemoteForward {
+            bind_address: String::new(),
+            remote_port: 0,
+            local_host: "127.0.0.1".to_string(),
+            local_port: 3000,
+        };
+        assert_eq!(
+            forward.describe(Some(43121)),
+            "R localhost:43121 -> 127.0.0.1:3000"
+        );
+        assert_eq!(forward.describe(None), "R localhost:0 -> 127.0.0.1:3000");
+    }
+
+    #[test]
+    fn socks_server_handshake_parses_v5_domain_and_v4a_requests() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let (mut client, mut server) = tokio::io::duplex(256);
+            client.write_all(&[0x05, 0x01, 0x00]).await.unwrap();
+            client
+                .write_all(&[0x05, 0x01, 0x00, 0x03, 8])
+                .await
+                .unwrap();
+            client.write_all(b"wiki.lan").await.unwrap();
+            client.write_all(&443u16.to_be_bytes()).await.unwrap();
+            let request = socks_server_handshake(&mut server).await.unwrap();
+            assert_eq!(
+                request,
+                SocksConnectRequest {
+                    version: SocksVersion::V5,
+                    host: "wiki.lan".to_string(),
+                    port: 443,
+                }
+            );
+            let mut method_reply = [0u8; 2];
+            client.read_exact(&mut method_reply).await.unwrap();
+            assert_eq!(method_reply, [0x05, 0x00]);
+
+            let (mut client, mut server) = tokio::io::duplex(256);
+            client.write_all(&[0x04, 0x01]).await.unwrap();
+            client.write_all(&8080u16.to_be_bytes()).await.unwrap();
+            client.write_all(&[0, 0, 0, 1]).await.unwrap();
+            client.write_all(b"me\0intranet\0").await.unwrap();
+            let request = socks_server_handshake(&mut server).await.unwrap();
+            assert_eq!(request.version, SocksVersion::V4);
+            assert_eq!(request.host, "intranet");
+            assert_eq!(request.port, 8080);
+        });
+    }
+
+    #[test]
+    fn algorithm_preferences_map_to_russh_lists() {
+        let prefs = AlgorithmPreferences {
+            kex: "diffie-hellman-group14-sha1, curve25519-sha256".to_string(),
+            host_keys: "ssh-rsa,rsa-sha2-256".to_string(),
+            ..AlgorithmPreferences::default()
+        };
+        let preferred = preferred_algorithms(&prefs).unwrap();
+        let kex: Vec<&str> = preferred.kex.iter().map(|name| name.as_ref()).collect();
+        assert_eq!(
+            kex,
+            [
+                "diffie-hellman-group14-sha1",
+                "curve25519-sha256",
+                "ext-info-c",
+                "kex-strict-c-v00@openssh.com"
+            ]
+        );
+        let keys: Vec<&str> = preferred.key.iter().map(|key| key.as_str()).collect();
+        assert_eq!(keys, ["ssh-rsa", "rsa-sha2-256"]);
+        assert_eq!(
+            preferred.cipher.as_ref(),
+            russh::Preferred::default().cipher.as_ref()
+        );
+
+        let err = preferred_algorithms(&AlgorithmPreferences {
+            ciphers: "aes256-ctr,blowfish-cbc".to_string(),
+            ..AlgorithmPreferences::default()
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unsupported cipher algorithm `blowfish-cbc`"
+        );
+        assert_eq!(
+            issue_kind_from_anyhow(&anyhow::Error::new(err)),
+            IssueKind::Configuration
+        );
+    }
+
+    #[test]
+    fn connect_timeout_and_dead_peer_are_transport_issues() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let err = runtime
+            .block_on(within_connect_timeout(
+                Some(Duration::from_millis(10)),
+                "db.example.com:22",
+                std::future::pending::<()>(),
+            ))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Timed out connecting to db.example.com:22 after 10ms"
+        );
+        let whole_seconds = ConnectionError::ConnectTimeout {
+            target: "db.example.com:22".to_string(),
+            limit: Duration::from_secs(15),
+        };
+        assert_eq!(
+            whole_seconds.to_string(),
+            "Timed out connecting to db.example.com:22 after 15s"
+        );
+        assert_eq!(
+            issue_kind_from_anyhow(&anyhow::Error::new(err)),
+            IssueKind::Transport
+        );
+
+        let observer = SessionObserver::default();
+        let options =
+            TerminalChannelOptions::from_settings(&ConnectionSettings::default(), &observer);
+        assert_eq!(
+            options.channel_closed_status(),
+            "Transport disconnected. The SSH channel closed."
+        );
+        observer.lock().unwrap().lost =
+            Some(connection_lost_message(&russh::Error::KeepaliveTimeout));
+        assert_eq!(
+            options.channel_closed_status(),
+            "Connection lost: the server stopped answering keepalives."
+        );
+    }
+
+    #[test]
+    fn host_key_prompt_time_does_not_count_against_connect_timeout() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let observer = SessionObserver::default();
+        // Stands in for a handshake whose host key check blocks on the user for longer than
+        // the whole connect timeout, then still needs the network to finish.
+        let handshake = async {
+            observer.lock().unwrap().host_key_prompt_since = Some(Instant::now());
+            std::thread::sleep(Duration::from_millis(300));
+            {
+                let mut observed = observer.lock().unwrap();
+                let since = observed.host_key_prompt_since.take().unwrap();
+                observed.host_key_prompt_time += since.elapsed();
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            "session"
+        };
+        let result = runtime.block_on(within_handshake_timeout(
+            Some(Duration::from_millis(200)),
+            "db.example.com:22",
+            &observer,
+            handshake,
+        ));
+        assert_eq!(result.unwrap(), "session");
+
+        let err = runtime
+            .block_on(within_handshake_timeout(
+                Some(Duration::from_millis(20)),
+                "db.example.com:22",
+                &SessionObserver::default(),
+                std::future::pending::<()>(),
+            ))
+            .unwrap_err();
+        assert!(matches!(err, ConnectionError::ConnectTimeout { .. }));
+    }
+
+    #[test]
+    fn user_certificate_path_prefers_explicit_then_sidecar() {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("rusty-cert-{stamp}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key = dir.join("id_ed25519");
+
+        let mut settings = ConnectionSettings {
+            private_key_path: key.display().to_string(),
+            ..ConnectionSettings::default()
+        };
+        assert_eq!(user_certificate_path(&settings), None);
+
+        let sidecar = dir.join("id_ed25519-cert.pub");
+        std::fs::write(&sidecar, b"not a certificate").unwrap();
+        assert_eq!(user_certificate_path(&settings), Some(sidecar.clone()));
+        assert!(CertificateSummary::read(&sidecar).is_err());
+
+        settings.certificate_path = " C:\\certs\\ops-cert.pub ".to_string();
+        assert_eq!(
+            user_certificate_path(&settings),
+            Some(PathBuf::from("C:\\certs\\ops-cert.pub"))
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn startup_script_waits_for_prompt_between_commands() {
+        let commands = vec!["sudo -i".to_string(), "cd /srv/app".to_string()];
+        assert!(StartupScript::new(commands.clone(), "[").is_err());
+
+        let mut immediate = StartupScript::new(commands.clone(), "").unwrap();
+        assert_eq!(immediate.next_command(None).as_deref(), Some("sudo -i"));
+        assert_eq!(immediate.next_command(None).as_deref(), Some("cd /srv/app"));
+        assert!(immediate.is_done());
+
+        let mut parser = Parser::new(4, 40, 32);
+        let mut script = StartupScript::new(commands, r"[$#] $").unwrap();
+        parser.process(b"Last login: today\r\nalice@web:~$ ");
+        let line = parser.screen().text_before_cursor();
+        assert_eq!(line.as_deref(), Some("alice@web:~$ "));
+        assert_eq!(
+            script.next_command(line.as_deref()).as_deref(),
+            Some("sudo -i")
+        );
+        // Same prompt still on screen: nothing more until output arrives.
+        assert_eq!(script.next_command(line.as_deref()), None);
+
+        script.saw_output();
+        parser.process(b"sudo -i\r\n[sudo] password for alice: ");
+        let line = parser.screen().text_before_cursor();
+        assert_eq!(script.next_command(line.as_deref()), None);
+        parser.process(b"\r\nroot@web:~# ");
+        let line = parser.screen().text_before_cursor();
+        assert_eq!(
+            script.next_command(line.as_deref()).as_deref(),
+            Some("cd /srv/app")
+        );
+        assert!(script.is_done());
+
+        let mut stalled =
+            StartupScript::new(vec!["make".to_string()], r"\$ $").expect("valid pattern");
+        assert!(stalled.deadline.is_some());
+        assert_eq!(stalled.expire(), 1);
+        assert!(stalled.is_done());
+    }
+
+    #[test]
+    fn remote_command_exit_status_reports_code_or_signal() {
+        assert_eq!(
+            remote_command_exit_status(Some(0), None),
+            (IssueKind::Info, "Command exited with status 0.".to_string())
+        );
+        assert_eq!(
+            remote_command_exit_status(Some(3), None).1,
+            "Command exited with status 3."
+        );
+        assert_eq!(
+            remote_command_exit_status(None, Some("TERM")).1,
+            "Command terminated by signal TERM."
+        );
+    }
+
+    #[test]
+    fn host_key_probe_asks_for_the_pinned_key_type() {
+        assert_eq!(
+            host_key_algorithms_for("ssh-rsa"),
+            "rsa-sha2-512,rsa-sha2-256,ssh-rsa"
+        );
+        assert_eq!(host_key_algorithms_for("ssh-ed25519"), "ssh-ed25519");
+        assert_eq!(host_key_algorithms_for(""), "");
+        for pinned in ["ssh-rsa", "ssh-ed25519", "ecdsa-sha2-nistp256"] {
+            let prefs = AlgorithmPreferences {
+                host_keys: host_key_algorithms_for(pinned),
+                ..AlgorithmPreferences::default()
+            };
+            let preferred = preferred_algorithms(&prefs).expect("supported key type");
+            assert!(preferred.key.iter().all(|algorithm| {
+                let name = algorithm.as_str();
+                name == pinned || (pinned == "ssh-rsa" && name.starts_with("rsa-sha2-"))
+            }));
+        }
+    }
 }
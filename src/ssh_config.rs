@@ -0,0 +1,1101 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{self, AppConfig, ConnectionProfile};
+use crate::model::{
+    AlgorithmPreferences, ConnectionProtocol, ConnectionSettings, DynamicForward, EnvVar,
+    HostKeyPolicy, IdentityFile, JumpHostSettings, LocalForward, RemoteForward,
+};
+
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// A concrete `Host` alias resolved against every block that applies to it.
+#[derive(Clone, Debug)]
+pub struct ImportedHost {
+    pub alias: String,
+    pub settings: ConnectionSettings,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImportReport {
+    pub hosts: Vec<ImportedHost>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+pub fn default_config_path(home: &Path) -> PathBuf {
+    home.join(".ssh").join("config")
+}
+
+/// Parses an OpenSSH client config, following `Include` directives.
+/// `home` expands `~` and anchors relative includes at `~/.ssh`.
+pub fn import_from_path(path: &Path, home: Option<&Path>) -> ImportReport {
+    let mut parser = Parser::new(home);
+    match fs::read_to_string(path) {
+        Ok(text) => parser.parse_text(&text, 0),
+        Err(err) => parser
+            .warnings
+            .push(format!("Failed to read {}: {err}", path.display())),
+    }
+    parser.finish()
+}
+
+/// Adds imported hosts as profiles. Existing profiles with the same name are replaced when
+/// `overwrite` is set (keeping their stored secrets), otherwise left alone.
+pub fn merge_into_config(
+    cfg: &mut AppConfig,
+    hosts: &[ImportedHost],
+    overwrite: bool,
+) -> MergeSummary {
+    let mut summary = MergeSummary::default();
+    for host in hosts {
+        let name = config::sanitized_profile_name(&host.alias);
+        if name.is_empty() {
+            summary.skipped += 1;
+            continue;
+        }
+        match config::find_profile_index(cfg, &name) {
+            Some(i) if overwrite => {
+                let existing = &mut cfg.profiles[i];
+                let mut settings = host.settings.clone();
+                settings.password = std::mem::take(&mut existing.settings.password);
+                let key_paths: Vec<String> = settings
+                    .identity_files()
+                    .into_iter()
+                    .map(|identity| identity.private_key_path)
+                    .collect();
+                for identity in existing.settings.identity_files() {
+                    if !identity.key_passphrase.is_empty()
+                        && key_paths.contains(&identity.private_key_path)
+                    {
+                        settings.set_key_passphrase(
+                            Some(&identity.private_key_path),
+                            identity.key_passphrase,
+                        );
+                    }
+                }
+                existing.settings = settings;
+                summary.updated += 1;
+            }
+            Some(_) => summary.skipped += 1,
+            None => {
+                cfg.profiles.push(ConnectionProfile {
+                    name,
+                    settings: host.settings.clone(),
+                    ..ConnectionProfile::default()
+                });
+                summary.added += 1;
+            }
+        }
+    }
+    summary
+}
+
+/// Renders profiles as an `ssh_config` file and counts the `Host` blocks written. Passwords and
+/// key passphrases are never written, and Telnet or raw TCP profiles are left out since
+/// OpenSSH cannot open them.
+pub fn export_profiles(profiles: &[ConnectionProfile]) -> (String, usize) {
+    let mut out =
+        String::from("# Generated by Rusty. Passwords and key passphrases are not exported.\n");
+    let mut exported = 0;
+    for profile in profiles {
+        let settings = &profile.settings;
+        if settings.host.trim().is_empty() || settings.protocol != ConnectionProtocol::Ssh {
+            continue;
+        }
+        exported += 1;
+        out.push('\n');
+        out.push_str(&format!("Host {}\n", host_alias(&profile.name)));
+        push_option(&mut out, "HostName", settings.host.trim());
+        push_option(&mut out, "User", settings.username.trim());
+        if settings.port != 22 {
+            push_option(&mut out, "Port", &settings.port.to_string());
+        }
+        for identity in settings.identity_files() {
+            push_option(
+                &mut out,
+                "IdentityFile",
+                &quoted(identity.private_key_path.trim()),
+            );
+        }
+        push_option(
+            &mut out,
+            "CertificateFile",
+            &quoted(settings.certificate_path.trim()),
+        );
+        if !settings.jump_hosts.is_empty() {
+            let hops: Vec<String> = settings
+                .jump_hosts
+                .iter()
+                .map(|hop| {
+                    let target = host_port(hop.host.trim(), hop.port);
+                    if hop.username.trim().is_empty() {
+                        target
+                    } else {
+                        format!("{}@{target}", hop.username.trim())
+                    }
+                })
+                .collect();
+            push_option(&mut out, "ProxyJump", &hops.join(","));
+        }
+        push_option(&mut out, "ProxyCommand", settings.proxy_command.trim());
+        if let Some(proxy) = settings.proxy_override.as_ref().filter(|p| p.is_enabled()) {
+            out.push_str(&format!(
+                "    # Proxy: {} {} (no ssh_config equivalent)\n",
+                proxy.kind.label(),
+                host_port(proxy.host.trim(), proxy.port)
+            ));
+        }
+        for forward in settings.local_forwards.iter().filter(|f| f.is_complete()) {
+            let target = host_port(forward.remote_host.trim(), forward.remote_port);
+            push_option(
+                &mut out,
+                "LocalForward",
+                &format!("{} {target}", forward.bind_addr()),
+            );
+        }
+        for forward in settings.remote_forwards.iter().filter(|f| f.is_complete()) {
+            let bind = forward.bind_address.trim();
+            let listen = if bind.is_empty() {
+                forward.remote_port.to_string()
+            } else {
+                host_port(bind, forward.remote_port)
+            };
+            push_option(
+                &mut out,
+                "RemoteForward",
+                &format!("{listen} {}", forward.local_target()),
+            );
+        }
+        for forward in settings.dynamic_forwards.iter().filter(|f| f.is_complete()) {
+            push_option(&mut out, "DynamicForward", &forward.bind_addr());
+        }
+        if settings.forward_agent {
+            push_option(&mut out, "ForwardAgent", "yes");
+        }
+        match settings.host_key_policy {
+            HostKeyPolicy::Ask => {}
+            HostKeyPolicy::AcceptNew => {
+                push_option(&mut out, "StrictHostKeyChecking", "accept-new")
+            }
+            HostKeyPolicy::Strict => push_option(&mut out, "StrictHostKeyChecking", "yes"),
+        }
+        let remote_command = settings.remote_command.trim();
+        if !remote_command.is_empty() {
+            push_option(&mut out, "RemoteCommand", remote_command);
+            push_option(&mut out, "RequestTTY", "yes");
+        }
+        let mut environment: Vec<String> = settings
+            .environment
+            .iter()
+            .filter(|var| var.is_complete())
+            .map(|var| quoted(&format!("{}={}", var.name.trim(), var.value)))
+            .collect();
+        let term = settings.terminal_type.trim();
+        if !term.is_empty() {
+            environment.insert(0, quoted(&format!("TERM={term}")));
+        }
+        push_option(&mut out, "SetEnv", &environment.join(" "));
+        let startup_lines = settings.startup_command_lines().count();
+        if startup_lines > 0 {
+            out.push_str(&format!(
+                "    # Startup commands: {startup_lines} line(s) (no ssh_config equivalent)\n"
+            ));
+        }
+        push_option(
+            &mut out,
+            "ConnectTimeout",
+            &settings.connect_timeout_secs.to_string(),
+        );
+        push_option(
+            &mut out,
+            "ServerAliveInterval",
+            &settings.keepalive_interval_secs.to_string(),
+        );
+        push_option(
+            &mut out,
+            "ServerAliveCountMax",
+            &settings.keepalive_max_missed.to_string(),
+        );
+        let algorithms = &settings.algorithms;
+        for (keyword, list) in [
+            ("KexAlgorithms", &algorithms.kex),
+            ("Ciphers", &algorithms.ciphers),
+            ("MACs", &algorithms.macs),
+            ("HostKeyAlgorithms", &algorithms.host_keys),
+        ] {
+            let names: Vec<&str> = AlgorithmPreferences::names(list).collect();
+            push_option(&mut out, keyword, &names.join(","));
+        }
+    }
+    (out, exported)
+}
+
+/// `Host` patterns are whitespace-separated and treat `*?!` specially.
+fn host_alias(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || matches!(c, '*' | '?' | '!' | '"') {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn host_port(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+fn push_option(out: &mut String, keyword: &str, value: &str) {
+    if !value.is_empty() {
+        out.push_str(&format!("    {keyword} {value}\n"));
+    }
+}
+
+/// Single-argument values such as paths need quoting when they contain spaces.
+fn quoted(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("\"{value}\"")
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Debug)]
+struct Block {
+    /// `Host` patterns; empty for `Match` blocks, which never apply.
+    patterns: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Block {
+    fn matches(&self, alias: &str) -> bool {
+        let mut matched = false;
+        for pattern in &self.patterns {
+            if let Some(negated) = pattern.strip_prefix('!') {
+                if wildcard_match(negated, alias) {
+                    return false;
+                }
+            } else if wildcard_match(pattern, alias) {
+                matched = true;
+            }
+        }
+        matched
+    }
+}
+
+struct Parser<'a> {
+    home: Option<&'a Path>,
+    blocks: Vec<Block>,
+    aliases: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(home: Option<&'a Path>) -> Self {
+        Self {
+            home,
+            blocks: vec![Block {
+                patterns: vec!["*".to_string()],
+                options: Vec::new(),
+            }],
+            aliases: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    fn parse_text(&mut self, text: &str, depth: usize) {
+        for line in text.lines() {
+            let Some((keyword, args)) = split_directive(line) else {
+                continue;
+            };
+            match keyword.as_str() {
+                "host" => {
+                    let patterns = tokenize(&args);
+                    for pattern in &patterns {
+                        let concrete = !pattern.contains(['*', '?', '!']);
+                        if concrete && !self.aliases.iter().any(|a| a.eq_ignore_ascii_case(pattern))
+                        {
+                            self.aliases.push(pattern.clone());
+                        }
+                    }
+                    self.blocks.push(Block {
+                        patterns,
+                        options: Vec::new(),
+                    });
+                }
+                "match" => {
+                    self.warnings
+                        .push(format!("Ignored unsupported block: Match {args}"));
+                    self.blocks.push(Block {
+                        patterns: Vec::new(),
+                        options: Vec::new(),
+                    });
+                }
+                "include" => self.include(&args, depth),
+                _ => {
+                    if let Some(block) = self.blocks.last_mut() {
+                        block.options.push((keyword, args));
+                    }
+                }
+            }
+        }
+    }
+
+    fn include(&mut self, args: &str, depth: usize) {
+        if depth >= MAX_INCLUDE_DEPTH {
+            self.warnings
+                .push(format!("Include nesting too deep, skipped: {args}"));
+            return;
+        }
+        let enclosing = self.blocks.last().map(|b| b.patterns.clone());
+        let block_count = self.blocks.len();
+        for pattern in tokenize(args) {
+            let path = self.include_path(&pattern);
+            let files = expand_include_glob(&path);
+            if files.is_empty() && !path.to_string_lossy().contains(['*', '?']) {
+                self.warnings
+                    .push(format!("Included file not found: {}", path.display()));
+            }
+            for file in files {
+                match fs::read_to_string(&file) {
+                    Ok(text) => self.parse_text(&text, depth + 1),
+                    Err(err) => self
+                        .warnings
+                        .push(format!("Failed to read {}: {err}", file.display())),
+                }
+            }
+        }
+        // An Include inside a Host block must not leak the included file's last block
+        // into the directives that follow it.
+        if self.blocks.len() != block_count {
+            if let Some(patterns) = enclosing {
+                self.blocks.push(Block {
+                    patterns,
+                    options: Vec::new(),
+                });
+            }
+        }
+    }
+
+    fn include_path(&self, raw: &str) -> PathBuf {
+        let expanded = expand_home(raw, self.home);
+        let path = PathBuf::from(&expanded);
+        if path.is_absolute() {
+            return path;
+        }
+        match self.home {
+            Some(home) => home.join(".ssh").join(path),
+            None => path,
+        }
+    }
+
+    fn finish(mut self) -> ImportReport {
+        let mut hosts = Vec::new();
+        for alias in std::mem::take(&mut self.aliases) {
+            let resolved = self.resolve(&alias);
+            let settings = self.build_settings(&alias, &resolved);
+            hosts.push(ImportedHost { alias, settings });
+        }
+        ImportReport {
+            hosts,
+            warnings: std::mem::take(&mut self.warnings),
+        }
+    }
+
+    /// Collects the options applying to `alias`, first value winning as in OpenSSH
+    /// (forwards and identity files accumulate).
+    fn resolve(&self, alias: &str) -> Vec<(String, String)> {
+        let mut resolved: Vec<(String, String)> = Vec::new();
+        for block in self.blocks.iter().filter(|b| b.matches(alias)) {
+            for (key, value) in &block.options {
+                let repeatable = matches!(
+                    key.as_str(),
+                    "identityfile" | "localforward" | "remoteforward" | "dynamicforward" | "setenv"
+                );
+                if repeatable || !resolved.iter().any(|(k, _)| k == key) {
+                    resolved.push((key.clone(), value.clone()));
+                }
+            }
+        }
+        resolved
+    }
+
+    fn build_settings(&mut self, alias: &str, resolved: &[(String, String)]) -> ConnectionSettings {
+        let first = |key: &str| {
+            resolved
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| unquote(v))
+        };
+        let all = |key: &str| {
+            resolved
+                .iter()
+                .filter(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        let mut settings = ConnectionSettings {
+            host: first("hostname")
+                .map(|h| h.replace("%h", alias))
+                .unwrap_or_else(|| alias.to_string()),
+            username: first("user").unwrap_or_default(),
+            ..ConnectionSettings::default()
+        };
+        if let Some(port) = first("port") {
+            match port.parse::<u16>() {
+                Ok(port) => settings.port = port,
+                Err(_) => self
+                    .warnings
+                    .push(format!("{alias}: invalid Port {port:?}")),
+            }
+        }
+        let mut identities = all("identityfile")
+            .into_iter()
+            .map(|identity| expand_home(identity, self.home));
+        if let Some(primary) = identities.next() {
+            settings.private_key_path = primary;
+        }
+        settings.identities = identities
+            .map(|private_key_path| IdentityFile {
+                private_key_path,
+                ..IdentityFile::default()
+            })
+            .collect();
+        if let Some(certificate) = first("certificatefile") {
+            settings.certificate_path = expand_home(&certificate, self.home);
+        }
+        if let Some(command) = first("remotecommand") {
+            if !command.eq_ignore_ascii_case("none") {
+                settings.remote_command = command;
+            }
+        }
+        for value in all("setenv") {
+            for token in tokenize(value) {
+                let Some((name, value)) = token.split_once('=').filter(|(n, _)| !n.is_empty())
+                else {
+                    self.warnings
+                        .push(format!("{alias}: invalid SetEnv {token:?}"));
+                    continue;
+                };
+                if name == "TERM" {
+                    if settings.terminal_type.is_empty() {
+                        settings.terminal_type = value.to_string();
+                    }
+                } else if !settings.environment.iter().any(|var| var.name == name) {
+                    settings.environment.push(EnvVar {
+                        name: name.to_string(),
+                        value: value.to_string(),
+                    });
+                }
+            }
+        }
+        if let Some(command) = first("proxycommand") {
+            if !command.eq_ignore_ascii_case("none") {
+                settings.proxy_command = command;
+            }
+        }
+        if let Some(jumps) = first("proxyjump") {
+            if !jumps.eq_ignore_ascii_case("none") {
+                for spec in jumps.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                    let hop = self.jump_host(spec);
+                    settings.jump_hosts.push(hop);
+                }
+            }
+        }
+        if let Some(checking) = first("stricthostkeychecking") {
+            settings.host_key_policy = match checking.to_ascii_lowercase().as_str() {
+                "yes" => HostKeyPolicy::Strict,
+                "accept-new" => HostKeyPolicy::AcceptNew,
+                "ask" => HostKeyPolicy::Ask,
+                "no" | "off" => {
+                    // Rusty never silently accepts a changed key.
+                    self.warnings.push(format!(
+                        "{alias}: StrictHostKeyChecking {checking} imported as accept-new"
+                    ));
+                    HostKeyPolicy::AcceptNew
+                }
+                _ => {
+                    self.warnings.push(format!(
+                        "{alias}: invalid StrictHostKeyChecking {checking:?}"
+                    ));
+                    HostKeyPolicy::Ask
+                }
+            };
+        }
+        if let Some(forward) = first("forwardagent") {
+            settings.forward_agent = forward.eq_ignore_ascii_case("yes");
+        }
+        for (keyword, target) in [
+            ("connecttimeout", &mut settings.connect_timeout_secs),
+            ("serveraliveinterval", &mut settings.keepalive_interval_secs),
+            ("serveralivecountmax", &mut settings.keepalive_max_missed),
+        ] {
+            let Some(value) = first(keyword) else {
+                continue;
+            };
+            match value.parse::<u32>() {
+                Ok(value) => *target = value,
+                Err(_) => self
+                    .warnings
+                    .push(format!("{alias}: invalid {keyword} {value:?}")),
+            }
+        }
+        let algorithms = &mut settings.algorithms;
+        for (keyword, target) in [
+            ("kexalgorithms", &mut algorithms.kex),
+            ("ciphers", &mut algorithms.ciphers),
+            ("macs", &mut algorithms.macs),
+            ("hostkeyalgorithms", &mut algorithms.host_keys),
+        ] {
+            let Some(list) = first(keyword) else {
+                continue;
+            };
+            if list.starts_with(['+', '-', '^']) {
+                // Lists relative to the defaults have no fixed equivalent.
+                self.warnings
+                    .push(format!("{alias}: ignored relative algorithm list {list:?}"));
+            } else {
+                *target = list;
+            }
+        }
+        for value in all("localforward") {
+            match parse_local_forward(value) {
+                Some(forward) => settings.local_forwards.push(forward),
+                None => self
+                    .warnings
+                    .push(format!("{alias}: unsupported LocalForward {value:?}")),
+            }
+        }
+        for value in all("remoteforward") {
+            match parse_remote_forward(value) {
+                Some(forward) => settings.remote_forwards.push(forward),
+                None => self
+                    .warnings
+                    .push(format!("{alias}: unsupported RemoteForward {value:?}")),
+            }
+        }
+        for value in all("dynamicforward") {
+            match parse_dynamic_forward(value) {
+                Some(forward) => settings.dynamic_forwards.push(forward),
+                None => self
+                    .warnings
+                    .push(format!("{alias}: unsupported DynamicForward {value:?}")),
+            }
+        }
+        settings
+    }
+
+    /// A `ProxyJump` hop; when it names another `Host` alias, that alias's own
+    /// HostName/User/Port/IdentityFile are used as defaults.
+    fn jump_host(&self, spec: &str) -> JumpHostSettings {
+        let spec = spec.strip_prefix("ssh://").unwrap_or(spec);
+        let (user, host_port) = match spec.rsplit_once('@') {
+            Some((user, rest)) => (Some(user.to_string()), rest),
+            None => (None, spec),
+        };
+        let (host, port) = split_host_port(host_port);
+
+        let resolved = self.resolve(&host);
+        let lookup = |key: &str| {
+            resolved
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| unquote(v))
+        };
+        let mut hop = JumpHostSettings {
+            host: lookup("hostname")
+                .map(|h| h.replace("%h", &host))
+                .unwrap_or_else(|| host.clone()),
+            username: user.or_else(|| lookup("user")).unwrap_or_default(),
+            private_key_path: lookup("identityfile")
+                .map(|p| expand_home(&p, self.home))
+                .unwrap_or_default(),
+            ..JumpHostSettings::default()
+        };
+        hop.port = port
+            .or_else(|| lookup("port").and_then(|p| p.parse().ok()))
+            .unwrap_or(22);
+        hop
+    }
+}
+
+/// Splits a config line into a lowercased keyword and its raw arguments.
+fn split_directive(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let keyword = line[..end].to_ascii_lowercase();
+    let rest = line[end..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim();
+    Some((keyword, rest.to_string()))
+}
+
+/// Whitespace-separated arguments, honouring double quotes.
+fn tokenize(args: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in args.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+fn expand_home(path: &str, home: Option<&Path>) -> String {
+    let path = unquote(path);
+    let Some(home) = home else {
+        return path;
+    };
+    let home = home.display().to_string();
+    let path = path.replace("%d", &home);
+    if path == "~" {
+        return home;
+    }
+    match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some(rest) => Path::new(&home).join(rest).display().to_string(),
+        None => path,
+    }
+}
+
+/// Case-insensitive `*`/`?` matching as used by `Host` patterns.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Resolves wildcards in the file name of an include path, sorted like OpenSSH's glob.
+fn expand_include_glob(path: &Path) -> Vec<PathBuf> {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return Vec::new();
+    };
+    if !name.contains(['*', '?']) {
+        return if path.is_file() {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        };
+    }
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| wildcard_match(&name, &n.to_string_lossy()))
+                .unwrap_or(false)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// `host:port`, `[v6]:port` or a bare host.
+fn split_host_port(value: &str) -> (String, Option<u16>) {
+    if let Some(rest) = value.strip_prefix('[') {
+        if let Some((host, tail)) = rest.split_once(']') {
+            let port = tail.strip_prefix(':').and_then(|p| p.parse().ok());
+            return (host.to_string(), port);
+        }
+    }
+    match value.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host.to_string(), Some(port)),
+            Err(_) => (value.to_string(), None),
+        },
+        _ => (value.to_string(), None),
+    }
+}
+
+/// `[bind_address:]port`, with the bind address optionally bracketed.
+fn split_listen_spec(value: &str) -> Option<(Option<String>, u16)> {
+    if let Ok(port) = value.parse() {
+        return Some((None, port));
+    }
+    let (host, port) = split_host_port(value);
+    let host = if host == "*" {
+        "0.0.0.0".to_string()
+    } else {
+        host
+    };
+    Some((Some(host), port?))
+}
+
+fn forward_specs(value: &str) -> Option<(String, String)> {
+    let tokens = tokenize(value);
+    match tokens.as_slice() {
+        [listen, target] => Some((listen.clone(), target.clone())),
+        _ => None,
+    }
+}
+
+fn parse_local_forward(value: &str) -> Option<LocalForward> {
+    let (listen, target) = forward_specs(value)?;
+    let (bind, local_port) = split_listen_spec(&listen)?;
+    let (remote_host, remote_port) = split_host_port(&target);
+    let forward = LocalForward {
+        bind_address: bind.unwrap_or_else(|| LocalForward::default().bind_address),
+        local_port,
+        remote_host,
+        remote_port: remote_port?,
+    };
+    forward.is_complete().then_some(forward)
+}
+
+fn parse_remote_forward(value: &str) -> Option<RemoteForward> {
+    let (listen, target) = forward_specs(value)?;
+    let (bind, remote_port) = split_listen_spec(&listen)?;
+    let (local_host, local_port) = split_host_port(&target);
+    let forward = RemoteForward {
+        bind_address: bind.unwrap_or_default(),
+        remote_port,
+        local_host,
+        local_port: local_port?,
+    };
+    forward.is_complete().then_some(forward)
+}
+
+fn parse_dynamic_forward(value: &str) -> Option<DynamicForward> {
+    let (bind, local_port) = split_listen_spec(value.trim())?;
+    let forward = DynamicForward {
+        bind_address: bind.unwrap_or_else(|| DynamicForward::default().bind_address),
+        local_port,
+    };
+    forward.is_complete().then_some(forward)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_home(tag: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let home = std::env::temp_dir().join(format!("rusty-ssh-config-{tag}-{nanos}"));
+        fs::create_dir_all(home.join(".ssh").join("conf.d")).unwrap();
+        home
+    }
+
+    #[test]
+    fn imports_hosts_with_wildcards_includes_and_jumps() {
+        let home = temp_home("import");
+        fs::write(
+            home.join(".ssh").join("conf.d").join("work.conf"),
+            "Host bastion\n  HostName bastion.example.com\n  User jump\n  Port 2200\n",
+        )
+        .unwrap();
+        fs::write(
+            default_config_path(&home),
+            "Include conf.d/*.conf\n\
+             \n\
+             Host web db\n\
+             \tUser alice\n\
+             Host web\n\
+             \tHostName web.internal\n\
+             \tProxyJump bastion,ops@10.0.0.5:2022\n\
+             \tLocalForward 8080 localhost:80\n\
+             \tLocalForward 127.0.0.1:5433 [::1]:5432\n\
+             Host *.internal !skip.internal\n\
+             \tIdentityFile ~/.ssh/id_internal\n\
+             Host db\n\
+             \tUser ignored\n\
+             Host *\n\
+             \tPort 2222\n\
+             \tIdentityFile=\"~/.ssh/id_default\"\n",
+        )
+        .unwrap();
+
+        let report = import_from_path(&default_config_path(&home), Some(&home));
+        let _ = fs::remove_dir_all(&home);
+
+        let aliases: Vec<_> = report.hosts.iter().map(|h| h.alias.as_str()).collect();
+        assert_eq!(aliases, ["bastion", "web", "db"]);
+
+        let bastion = &report.hosts[0].settings;
+        assert_eq!(bastion.host, "bastion.example.com");
+        assert_eq!(bastion.port, 2200);
+        assert_eq!(bastion.username, "jump");
+
+        let web = &report.hosts[1].settings;
+        assert_eq!(web.host, "web.internal");
+        assert_eq!(web.username, "alice");
+        assert_eq!(web.port, 2222);
+        assert!(web.private_key_path.ends_with("id_default"));
+        assert_eq!(web.jump_hosts.len(), 2);
+        assert_eq!(web.jump_hosts[0].host, "bastion.example.com");
+        assert_eq!(web.jump_hosts[0].port, 2200);
+        assert_eq!(web.jump_hosts[0].username, "jump");
+        assert_eq!(web.jump_hosts[1].host, "10.0.0.5");
+        assert_eq!(web.jump_hosts[1].port, 2022);
+        assert_eq!(web.jump_hosts[1].username, "ops");
+        assert_eq!(web.local_forwards.len(), 2);
+        assert_eq!(
+            web.local_forwards[0].describe(),
+            "L 127.0.0.1:8080 -> localhost:80"
+        );
+        assert_eq!(web.local_forwards[1].remote_host, "::1");
+
+        let db = &report.hosts[2].settings;
+        assert_eq!(db.host, "db");
+        assert_eq!(db.username, "alice");
+        assert!(db.jump_hosts.is_empty());
+    }
+
+    #[test]
+    fn host_patterns_honour_negation_and_wildcards() {
+        let block = Block {
+            patterns: vec![
+                "*.example.com".to_string(),
+                "!secret.example.com".to_string(),
+            ],
+            options: Vec::new(),
+        };
+        assert!(block.matches("web.EXAMPLE.com"));
+        assert!(!block.matches("secret.example.com"));
+        assert!(!block.matches("example.org"));
+        assert!(wildcard_match("db-?", "db-1"));
+        assert!(!wildcard_match("db-?", "db-12"));
+    }
+
+    #[test]
+    fn merge_skips_or_overwrites_existing_profiles() {
+        let mut cfg = AppConfig::default();
+        cfg.profiles.push(ConnectionProfile {
+            name: "Web".to_string(),
+            settings: ConnectionSettings {
+                host: "old.example.com".to_string(),
+                password: "kept".to_string(),
+                private_key_path: "id_deploy".to_string(),
+                key_passphrase: "deploy-pw".to_string(),
+                identities: vec![IdentityFile {
+                    private_key_path: "id_retired".to_string(),
+                    key_passphrase: "retired-pw".to_string(),
+                }],
+                ..ConnectionSettings::default()
+            },
+            remember_password: true,
+            ..ConnectionProfile::default()
+        });
+        let hosts = vec![
+            ImportedHost {
+                alias: " web ".to_string(),
+                settings: ConnectionSettings {
+                    host: "new.example.com".to_string(),
+                    private_key_path: "id_personal".to_string(),
+                    identities: vec![IdentityFile {
+                        private_key_path: "id_deploy".to_string(),
+                        ..IdentityFile::default()
+                    }],
+                    ..ConnectionSettings::default()
+                },
+            },
+            ImportedHost {
+                alias: "db".to_string(),
+                settings: ConnectionSettings::default(),
+            },
+        ];
+
+        let summary = merge_into_config(&mut cfg, &hosts, false);
+        assert_eq!(
+            summary,
+            MergeSummary {
+                added: 1,
+                updated: 0,
+                skipped: 1
+            }
+        );
+        assert_eq!(cfg.profiles[0].settings.host, "old.example.com");
+
+        let summary = merge_into_config(&mut cfg, &hosts[..1], true);
+        assert_eq!(summary.updated, 1);
+        assert_eq!(cfg.profiles[0].settings.host, "new.example.com");
+        assert_eq!(cfg.profiles[0].settings.password, "kept");
+        let identities = cfg.profiles[0].settings.identity_files();
+        assert_eq!(identities.len(), 2);
+        assert_eq!(identities[0].private_key_path, "id_personal");
+        assert_eq!(identities[0].key_passphrase, "");
+        assert_eq!(identities[1].private_key_path, "id_deploy");
+        assert_eq!(identities[1].key_passphrase, "deploy-pw");
+        assert_eq!(cfg.profiles.len(), 2);
+    }
+
+    #[test]
+    fn export_writes_options_but_never_secrets() {
+        let profile = ConnectionProfile {
+            name: "prod web".to_string(),
+            settings: ConnectionSettings {
+                host: "web.example.com".to_string(),
+                port: 2222,
+                username: "alice".to_string(),
+                password: "hunter2".to_string(),
+                private_key_path: "C:\\Users\\Alice Smith\\.ssh\\id_ed25519".to_string(),
+                key_passphrase: "keypw".to_string(),
+                certificate_path: "C:\\certs\\id_ed25519-cert.pub".to_string(),
+                identities: vec![IdentityFile {
+                    private_key_path: "~/.ssh/id_deploy".to_string(),
+                    key_passphrase: "deploy-pw".to_string(),
+                }],
+                jump_hosts: vec![JumpHostSettings {
+                    host: "bastion".to_string(),
+                    username: "ops".to_string(),
+                    password: "hop-pw".to_string(),
+                    ..JumpHostSettings::default()
+                }],
+                local_forwards: vec![LocalForward {
+                    local_port: 8080,
+                    remote_host: "localhost".to_string(),
+                    remote_port: 80,
+                    ..LocalForward::default()
+                }],
+                forward_agent: true,
+                remote_command: "journalctl -f".to_string(),
+                startup_commands: "cd /srv/app\n\nsudo -i\n".to_string(),
+                terminal_type: "screen-256color".to_string(),
+                host_key_policy: HostKeyPolicy::AcceptNew,
+                environment: vec![
+                    EnvVar {
+                        name: "LANG".to_string(),
+                        value: "en_US.UTF-8".to_string(),
+                    },
+                    EnvVar {
+                        name: "DEPLOY_NOTE".to_string(),
+                        value: "blue green".to_string(),
+                    },
+                ],
+                algorithms: AlgorithmPreferences {
+                    kex: "diffie-hellman-group14-sha1, curve25519-sha256".to_string(),
+                    ..AlgorithmPreferences::default()
+                },
+                ..ConnectionSettings::default()
+            },
+            remember_password: true,
+            remember_key_passphrase: true,
+        };
+
+        let telnet = ConnectionProfile {
+            name: "switch".to_string(),
+            settings: ConnectionSettings {
+                host: "switch.example.com".to_string(),
+                protocol: ConnectionProtocol::Telnet,
+                ..ConnectionSettings::default()
+            },
+            ..ConnectionProfile::default()
+        };
+        let blank = ConnectionProfile::default();
+
+        let (text, exported) = export_profiles(&[profile, telnet, blank]);
+        assert_eq!(exported, 1);
+        assert!(!text.contains("switch"));
+        for secret in ["hunter2", "keypw", "hop-pw", "deploy-pw"] {
+            assert!(!text.contains(secret), "{secret} leaked into export");
+        }
+        assert!(text.contains("Host prod-web\n"));
+        assert!(text.contains("    Port 2222\n"));
+        assert!(text.contains("    IdentityFile \"C:\\Users\\Alice Smith\\.ssh\\id_ed25519\"\n"));
+        assert!(text.contains("    CertificateFile C:\\certs\\id_ed25519-cert.pub\n"));
+        assert!(text.contains("    ProxyJump ops@bastion:22\n"));
+        assert!(text.contains("    LocalForward 127.0.0.1:8080 localhost:80\n"));
+        assert!(text.contains("    KexAlgorithms diffie-hellman-group14-sha1,curve25519-sha256\n"));
+        assert!(text.contains("    RemoteCommand journalctl -f\n    RequestTTY yes\n"));
+        assert!(text.contains("    # Startup commands: 2 line(s)"));
+        assert!(text.contains(
+            "    SetEnv TERM=screen-256color LANG=en_US.UTF-8 \"DEPLOY_NOTE=blue green\"\n"
+        ));
+        assert!(!text.contains("Ciphers"));
+        assert!(text.contains("    ServerAliveInterval 20\n"));
+        assert!(text.contains("    StrictHostKeyChecking accept-new\n"));
+
+        let home = temp_home("export");
+        let path = default_config_path(&home);
+        fs::write(&path, &text).unwrap();
+        let report = import_from_path(&path, Some(&home));
+        let _ = fs::remove_dir_all(&home);
+        let imported = &report.hosts[0].settings;
+        assert_eq!(imported.host, "web.example.com");
+        assert_eq!(imported.port, 2222);
+        assert_eq!(
+            imported.private_key_path,
+            "C:\\Users\\Alice Smith\\.ssh\\id_ed25519"
+        );
+        assert_eq!(imported.identities.len(), 1);
+        assert!(imported.identities[0]
+            .private_key_path
+            .ends_with("id_deploy"));
+        assert_eq!(imported.certificate_path, "C:\\certs\\id_ed25519-cert.pub");
+        assert_eq!(imported.local_forwards.len(), 1);
+        assert!(imported.forward_agent);
+        assert_eq!(imported.remote_command, "journalctl -f");
+        assert_eq!(imported.terminal_type, "screen-256color");
+        assert_eq!(imported.host_key_policy, HostKeyPolicy::AcceptNew);
+        assert_eq!(imported.environment.len(), 2);
+        assert_eq!(imported.environment[1].name, "DEPLOY_NOTE");
+        assert_eq!(imported.environment[1].value, "blue green");
+        assert_eq!(
+            imported.algorithms.kex,
+            "diffie-hellman-group14-sha1,curve25519-sha256"
+        );
+    }
+}
@@ -0,0 +1,674 @@
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread;
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::logger;
+use crate::model::{ConnectionProtocol, ConnectionSettings};
+use crate::ssh::{self, CsiQueryScanner, IssueKind, SessionRecording, UiMessage, WorkerMessage};
+use crate::terminal_emulator::Parser;
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+const OPT_BINARY: u8 = 0;
+const OPT_ECHO: u8 = 1;
+const OPT_SGA: u8 = 3;
+const OPT_TTYPE: u8 = 24;
+const OPT_NAWS: u8 = 31;
+
+const TTYPE_IS: u8 = 0;
+const TTYPE_SEND: u8 = 1;
+
+/// Subnegotiations we act on are a few bytes; anything longer is dropped, not buffered.
+const MAX_SUBNEGOTIATION_LEN: usize = 512;
+
+/// One side of an option, following the RFC 1143 "Q method" closely enough to never answer
+/// an acknowledgement with another request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum OptionState {
+    #[default]
+    No,
+    Yes,
+    /// We asked and are waiting for the answer.
+    WantYes,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+enum ReadState {
+    #[default]
+    Data,
+    Iac,
+    Command(u8),
+    Sub,
+    SubIac,
+}
+
+/// Client side of the Telnet protocol: separates server data from commands and produces the
+/// replies for the options we support (BINARY, ECHO, SGA, TTYPE and NAWS).
+pub struct TelnetCodec {
+    /// Options this client performs (our WILL side).
+    local: [OptionState; 256],
+    /// Options the server performs (its WILL side).
+    remote: [OptionState; 256],
+    state: ReadState,
+    sub: Vec<u8>,
+    after_cr: bool,
+    terminal_type: String,
+    cols: u16,
+    rows: u16,
+}
+
+impl TelnetCodec {
+    pub fn new(terminal_type: &str, cols: u16, rows: u16) -> Self {
+        Self {
+            local: [OptionState::No; 256],
+            remote: [OptionState::No; 256],
+            state: ReadState::Data,
+            sub: Vec::new(),
+            after_cr: false,
+            terminal_type: terminal_type.to_string(),
+            cols,
+            rows,
+        }
+    }
+
+    /// Offers sent right after connecting. Servers that wait for the client to speak first
+    /// otherwise start in line mode with local echo.
+    pub fn opening(&mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for option in [OPT_TTYPE, OPT_NAWS] {
+            self.local[option as usize] = OptionState::WantYes;
+            out.extend_from_slice(&[IAC, WILL, option]);
+        }
+        for option in [OPT_SGA, OPT_ECHO] {
+            self.remote[option as usize] = OptionState::WantYes;
+            out.extend_from_slice(&[IAC, DO, option]);
+        }
+        out
+    }
+
+    /// Splits `input` into terminal data (appended to `data`) and protocol replies (appended
+    /// to `replies`). State carries over, so commands may straddle reads.
+    pub fn receive(&mut self, input: &[u8], data: &mut Vec<u8>, replies: &mut Vec<u8>) {
+        for &byte in input {
+            self.state = match self.state {
+                ReadState::Data => {
+                    if byte == IAC {
+                        ReadState::Iac
+                    } else {
+                        self.push_data(byte, data);
+                        ReadState::Data
+                    }
+                }
+                ReadState::Iac => match byte {
+                    IAC => {
+                        self.push_data(IAC, data);
+                        ReadState::Data
+                    }
+                    WILL | WONT | DO | DONT => ReadState::Command(byte),
+                    SB => {
+                        self.sub.clear();
+                        ReadState::Sub
+                    }
+                    // NOP, data mark, go ahead and the rest carry nothing a terminal shows.
+                    _ => ReadState::Data,
+                },
+                ReadState::Command(command) => {
+                    self.negotiate(command, byte, replies);
+                    ReadState::Data
+                }
+                ReadState::Sub => {
+                    if byte == IAC {
+                        ReadState::SubIac
+                    } else {
+                        if self.sub.len() < MAX_SUBNEGOTIATION_LEN {
+                            self.sub.push(byte);
+                        }
+                        ReadState::Sub
+                    }
+                }
+                ReadState::SubIac => match byte {
+                    SE => {
+                        self.subnegotiation(replies);
+                        ReadState::Data
+                    }
+                    IAC => {
+                        if self.sub.len() < MAX_SUBNEGOTIATION_LEN {
+                            self.sub.push(IAC);
+                        }
+                        ReadState::Sub
+                    }
+                    _ => ReadState::Sub,
+                },
+            };
+        }
+    }
+
+    /// Escapes `input` for the wire: IAC is doubled and, outside binary mode, a bare CR
+    /// becomes CR NUL as the NVT requires.
+    pub fn encode(&self, input: &[u8], out: &mut Vec<u8>) {
+        let binary = self.local[OPT_BINARY as usize] == OptionState::Yes;
+        for (i, &byte) in input.iter().enumerate() {
+            out.push(byte);
+            if byte == IAC {
+                out.push(IAC);
+            } else if byte == b'\r' && !binary && input.get(i + 1) != Some(&b'\n') {
+                out.push(0);
+            }
+        }
+    }
+
+    /// Records the new window size and returns the NAWS update when the server asked for one.
+    pub fn resize(&mut self, cols: u16, rows: u16) -> Vec<u8> {
+        self.cols = cols;
+        self.rows = rows;
+        let mut out = Vec::new();
+        if self.local[OPT_NAWS as usize] == OptionState::Yes {
+            self.push_window_size(&mut out);
+        }
+        out
+    }
+
+    fn push_data(&mut self, byte: u8, data: &mut Vec<u8>) {
+        let binary = self.remote[OPT_BINARY as usize] == OptionState::Yes;
+        // NVT sends CR as CR NUL; the NUL is padding, not a character.
+        if !(byte == 0 && self.after_cr && !binary) {
+            data.push(byte);
+        }
+        self.after_cr = byte == b'\r';
+    }
+
+    fn negotiate(&mut self, command: u8, option: u8, replies: &mut Vec<u8>) {
+        let supported_local = matches!(option, OPT_BINARY | OPT_SGA | OPT_TTYPE | OPT_NAWS);
+        let supported_remote = matches!(option, OPT_BINARY | OPT_ECHO | OPT_SGA);
+        match command {
+            DO => match self.local[option as usize] {
+                OptionState::Yes => {}
+                OptionState::WantYes => {
+                    self.local[option as usize] = OptionState::Yes;
+                    self.option_enabled(option, replies);
+                }
+                OptionState::No if supported_local => {
+                    self.local[option as usize] = OptionState::Yes;
+                    replies.extend_from_slice(&[IAC, WILL, option]);
+                    self.option_enabled(option, replies);
+                }
+                OptionState::No => replies.extend_from_slice(&[IAC, WONT, option]),
+            },
+            DONT => {
+                if self.local[option as usize] == OptionState::Yes {
+                    replies.extend_from_slice(&[IAC, WONT, option]);
+                }
+                self.local[option as usize] = OptionState::No;
+            }
+            WILL => match self.remote[option as usize] {
+                OptionState::Yes => {}
+                OptionState::WantYes => self.remote[option as usize] = OptionState::Yes,
+                OptionState::No if supported_remote => {
+                    self.remote[option as usize] = OptionState::Yes;
+                    replies.extend_from_slice(&[IAC, DO, option]);
+                }
+                OptionState::No => replies.extend_from_slice(&[IAC, DONT, option]),
+            },
+            WONT => {
+                if self.remote[option as usize] == OptionState::Yes {
+                    replies.extend_from_slice(&[IAC, DONT, option]);
+                }
+                self.remote[option as usize] = OptionState::No;
+            }
+            _ => {}
+        }
+    }
+
+    fn option_enabled(&mut self, option: u8, replies: &mut Vec<u8>) {
+        if option == OPT_NAWS {
+            self.push_window_size(replies);
+        }
+    }
+
+    fn subnegotiation(&mut self, replies: &mut Vec<u8>) {
+        if self.sub.as_slice() == [OPT_TTYPE, TTYPE_SEND]
+            && self.local[OPT_TTYPE as usize] == OptionState::Yes
+        {
+            replies.extend_from_slice(&[IAC, SB, OPT_TTYPE, TTYPE_IS]);
+            replies.extend_from_slice(self.terminal_type.as_bytes());
+            replies.extend_from_slice(&[IAC, SE]);
+        }
+        self.sub.clear();
+    }
+
+    fn push_window_size(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[IAC, SB, OPT_NAWS]);
+        for byte in self
+            .cols
+            .to_be_bytes()
+            .into_iter()
+            .chain(self.rows.to_be_bytes())
+        {
+            out.push(byte);
+            if byte == IAC {
+                out.push(IAC);
+            }
+        }
+        out.extend_from_slice(&[IAC, SE]);
+    }
+}
+
+/// Opens a Telnet or raw TCP connection to the profile's host and speaks the same
+/// [`WorkerMessage`]/[`UiMessage`] protocol as an SSH terminal, so the pane renders and takes
+/// input exactly like one. Nothing on the wire is encrypted.
+pub fn start_telnet(
+    client_id: u64,
+    settings: ConnectionSettings,
+    scrollback_len: usize,
+    ui_tx: Sender<UiMessage>,
+    worker_rx: Receiver<WorkerMessage>,
+    log_path: String,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        logger::log_line(
+            &log_path,
+            &format!("Starting {} worker.", settings.protocol.label()),
+        );
+
+        let result = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt.block_on(run_telnet_async(
+                client_id,
+                &settings,
+                scrollback_len,
+                &ui_tx,
+                worker_rx,
+                &log_path,
+            )),
+            Err(err) => Err(anyhow!("Failed to create async runtime: {err}")),
+        };
+
+        if let Err(err) = result {
+            logger::log_line(&log_path, &format!("Worker error: {err:#}"));
+            ssh::send_message(
+                &ui_tx,
+                UiMessage::Status(ssh::connection_status_from_error(&err)),
+            );
+            ssh::send_message(&ui_tx, UiMessage::Connected(false));
+        }
+    })
+}
+
+async fn run_telnet_async(
+    client_id: u64,
+    settings: &ConnectionSettings,
+    scrollback_len: usize,
+    ui_tx: &Sender<UiMessage>,
+    worker_rx: Receiver<WorkerMessage>,
+    log_path: &str,
+) -> Result<()> {
+    let host = settings.host.trim();
+    if host.is_empty() {
+        return Err(anyhow!("Host is required"));
+    }
+    let protocol = settings.protocol.label();
+    let target = format!("{host}:{}", settings.port);
+    ssh::send_ui_status(ui_tx, IssueKind::Info, format!("Connecting to {target}..."));
+    let transport = ssh::open_direct_transport(settings, log_path).await?;
+    let (mut reader, mut writer) = tokio::io::split(transport);
+
+    let mut codec = (settings.protocol == ConnectionProtocol::Telnet)
+        .then(|| TelnetCodec::new(settings.terminal_type(), 80, 24));
+    if let Some(codec) = codec.as_mut() {
+        writer
+            .write_all(&codec.opening())
+            .await
+            .context("Failed to send Telnet options")?;
+    }
+
+    logger::log_line(
+        log_path,
+        &format!("{protocol} session {client_id} connected to {target}."),
+    );
+    ssh::send_ui_status(
+        ui_tx,
+        IssueKind::Info,
+        format!("Connected to {target} over {protocol}. Traffic is not encrypted."),
+    );
+    ssh::send_message(ui_tx, UiMessage::Connected(true));
+
+    let len = scrollback_len.clamp(0, 200_000);
+    let len = if len == 0 {
+        ssh::TERM_SCROLLBACK_LEN
+    } else {
+        len
+    };
+    let mut parser = Parser::new(24, 80, len);
+    let mut scanner = CsiQueryScanner::default();
+    let mut recording: Option<SessionRecording> = None;
+    let mut buf = vec![0u8; 16 * 1024];
+    // Terminal data, query replies and outgoing bytes for the current chunk.
+    let mut data: Vec<u8> = Vec::new();
+    let mut replies: Vec<u8> = Vec::new();
+    let mut outgoing: Vec<u8> = Vec::new();
+    let mut screen_dirty = true;
+    let mut scrollback_dirty = true;
+    let mut last_scrollback_max: Option<usize> = None;
+    let mut screen_emit_interval = ssh::TERM_SCREEN_EMIT_INTERVAL_BASE;
+    let mut last_screen_emit = Instant::now()
+        .checked_sub(screen_emit_interval)
+        .unwrap_or_else(Instant::now);
+    let mut screen_rate_window_started = Instant::now();
+    let mut screen_rate_window_bytes: u64 = 0;
+    let mut worker_rx = ssh::bridge_receiver_to_async(worker_rx);
+
+    loop {
+        let rate_window_elapsed = screen_rate_window_started.elapsed();
+        if rate_window_elapsed >= ssh::TERM_SCREEN_RATE_WINDOW {
+            screen_emit_interval =
+                ssh::adaptive_screen_emit_interval(screen_rate_window_bytes, rate_window_elapsed);
+            screen_rate_window_started = Instant::now();
+            screen_rate_window_bytes = 0;
+        }
+
+        if scrollback_dirty {
+            let scrollback_max = parser.screen().scrollback_max();
+            if last_scrollback_max != Some(scrollback_max) {
+                ssh::send_scrollback_max(ui_tx, &mut parser);
+                last_scrollback_max = Some(scrollback_max);
+            }
+            scrollback_dirty = false;
+        }
+
+        if screen_dirty && last_screen_emit.elapsed() >= screen_emit_interval {
+            ssh::send_screen(ui_tx, &mut parser);
+            screen_dirty = false;
+            last_screen_emit = Instant::now();
+            continue;
+        }
+
+        // `Some(reason)` ends the session; `lost` marks an unexpected drop.
+        let mut closed: Option<(IssueKind, String, bool)> = None;
+        outgoing.clear();
+        tokio::select! {
+            message = worker_rx.recv() => {
+                match message {
+                    Some(WorkerMessage::Input { data: input, .. }) => {
+                        match codec.as_ref() {
+                            Some(codec) => codec.encode(&input, &mut outgoing),
+                            None => outgoing.extend_from_slice(&input),
+                        }
+                        if let Some(active) = recording.as_mut() {
+                            let before_cursor = parser.screen().text_before_cursor();
+                            let result = active.input(&input, before_cursor.as_deref());
+                            SessionRecording::check(&mut recording, result, ui_tx, log_path);
+                        }
+                    }
+                    Some(WorkerMessage::Resize { rows, cols, .. }) => {
+                        if let Some(codec) = codec.as_mut() {
+                            outgoing.extend_from_slice(&codec.resize(cols, rows));
+                        }
+                        parser.set_size(rows, cols);
+                        screen_dirty = true;
+                        scrollback_dirty = true;
+                        if let Some(active) = recording.as_mut() {
+                            let result = active.writer.resize(cols, rows);
+                            SessionRecording::check(&mut recording, result, ui_tx, log_path);
+                        }
+                    }
+                    Some(WorkerMessage::SetScrollback { rows, .. }) => {
+                        parser.set_scrollback(rows);
+                        screen_dirty = true;
+                        scrollback_dirty = true;
+                    }
+                    Some(WorkerMessage::StartRecording { request, .. }) => {
+                        SessionRecording::stop(&mut recording, ui_tx, log_path);
+                        let (rows, cols) = parser.screen().size();
+                        recording = SessionRecording::start(
+                            request,
+                            cols,
+                            rows,
+                            settings.terminal_type(),
+                            ui_tx,
+                            log_path,
+                        );
+                    }
+                    Some(WorkerMessage::StopRecording { .. }) => {
+                        SessionRecording::stop(&mut recording, ui_tx, log_path);
+                    }
+                    Some(WorkerMessage::Disconnect)
+                    | Some(WorkerMessage::DetachTerminalClient { .. })
+                    | None => {
+                        closed = Some((IssueKind::Info, "Disconnected.".to_string(), false));
+                    }
+                    Some(_) => {}
+                }
+            }
+            read = reader.read(&mut buf) => {
+                match read {
+                    Ok(0) => {
+                        closed = Some((
+                            IssueKind::Info,
+                            format!("Connection closed by {target}."),
+                            false,
+                        ));
+                    }
+                    Ok(n) => {
+                        let received = &buf[..n];
+                        data.clear();
+                        let received = match codec.as_mut() {
+                            Some(codec) => {
+                                codec.receive(received, &mut data, &mut outgoing);
+                                data.as_slice()
+                            }
+                            None => received,
+                        };
+                        if let Some(active) = recording.as_mut() {
+                            let result = active.writer.output(received);
+                            SessionRecording::check(&mut recording, result, ui_tx, log_path);
+                        }
+                        ssh::process_with_query_responses(
+                            &mut parser,
+                            &mut scanner,
+                            &mut replies,
+                            received,
+                        )
+                        .await?;
+                        if !replies.is_empty() {
+                            match codec.as_ref() {
+                                Some(codec) => codec.encode(&replies, &mut outgoing),
+                                None => outgoing.extend_from_slice(&replies),
+                            }
+                            replies.clear();
+                        }
+                        ssh::send_clipboard_writes(ui_tx, &mut parser);
+                        screen_rate_window_bytes =
+                            screen_rate_window_bytes.saturating_add(n as u64);
+                        screen_dirty = true;
+                        scrollback_dirty = true;
+                    }
+                    Err(err) => {
+                        closed = Some((
+                            IssueKind::Transport,
+                            format!("Transport error. Connection to {target} lost: {err}"),
+                            true,
+                        ));
+                    }
+                }
+            }
+            _ = tokio::time::sleep(screen_emit_interval.saturating_sub(last_screen_emit.elapsed())), if screen_dirty => {
+                ssh::send_screen(ui_tx, &mut parser);
+                screen_dirty = false;
+                last_screen_emit = Instant::now();
+            }
+        }
+
+        if closed.is_none() && !outgoing.is_empty() {
+            let written = async {
+                writer.write_all(&outgoing).await?;
+                writer.flush().await
+            }
+            .await;
+            if let Err(err) = written {
+                closed = Some((
+                    IssueKind::Transport,
+                    format!("Transport error. Connection to {target} lost: {err}"),
+                    true,
+                ));
+            }
+        }
+
+        if let Some((kind, status, lost)) = closed {
+            logger::log_line(
+                log_path,
+                &format!("{protocol} session {client_id} ended: {status}"),
+            );
+            SessionRecording::stop(&mut recording, ui_tx, log_path);
+            let _ = writer.shutdown().await;
+            ssh::send_screen(ui_tx, &mut parser);
+            ssh::send_ui_status(ui_tx, kind, status);
+            if lost {
+                ssh::send_message(ui_tx, UiMessage::ConnectionLost);
+            }
+            ssh::send_message(ui_tx, UiMessage::Connected(false));
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receive(codec: &mut TelnetCodec, input: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let mut data = Vec::new();
+        let mut replies = Vec::new();
+        codec.receive(input, &mut data, &mut replies);
+        (data, replies)
+    }
+
+    #[test]
+    fn negotiation_accepts_supported_options_and_refuses_the_rest() {
+        let mut codec = TelnetCodec::new("xterm-256color", 120, 40);
+        let opening = codec.opening();
+        assert_eq!(
+            opening,
+            [IAC, WILL, OPT_TTYPE, IAC, WILL, OPT_NAWS, IAC, DO, OPT_SGA, IAC, DO, OPT_ECHO]
+        );
+
+        // Answers to our own offers are not acknowledged again; NAWS reports the size at once.
+        let (data, replies) = receive(
+            &mut codec,
+            &[
+                IAC, DO, OPT_TTYPE, IAC, DO, OPT_NAWS, IAC, WILL, OPT_ECHO, IAC, WILL, OPT_SGA,
+            ],
+        );
+        assert!(data.is_empty());
+        assert_eq!(replies, [IAC, SB, OPT_NAWS, 0, 120, 0, 40, IAC, SE]);
+
+        let (_, replies) = receive(
+            &mut codec,
+            &[IAC, DO, OPT_BINARY, IAC, DO, 5, IAC, WILL, 39],
+        );
+        assert_eq!(
+            replies,
+            [IAC, WILL, OPT_BINARY, IAC, WONT, 5, IAC, DONT, 39]
+        );
+
+        let (_, replies) = receive(&mut codec, &[IAC, SB, OPT_TTYPE, TTYPE_SEND, IAC, SE]);
+        let mut expected = vec![IAC, SB, OPT_TTYPE, TTYPE_IS];
+        expected.extend_from_slice(b"xterm-256color");
+        expected.extend_from_slice(&[IAC, SE]);
+        assert_eq!(replies, expected);
+
+        assert_eq!(
+            codec.resize(255, 50),
+            [IAC, SB, OPT_NAWS, 0, 255, 255, 0, 50, IAC, SE]
+        );
+        let (_, replies) = receive(&mut codec, &[IAC, DONT, OPT_NAWS]);
+        assert_eq!(replies, [IAC, WONT, OPT_NAWS]);
+        assert!(codec.resize(80, 24).is_empty());
+    }
+
+    #[test]
+    fn data_is_unescaped_across_reads_and_input_is_escaped() {
+        let mut codec = TelnetCodec::new("xterm", 80, 24);
+        let (data, _) = receive(&mut codec, b"a\r\0b\xff");
+        assert_eq!(data, b"a\rb");
+        let (data, replies) = receive(&mut codec, &[IAC, b'c', IAC, 241, b'\r', b'\n']);
+        assert_eq!(data, [IAC, b'c', b'\r', b'\n']);
+        assert!(replies.is_empty());
+
+        let mut out = Vec::new();
+        codec.encode(b"ls\r", &mut out);
+        codec.encode(&[IAC, b'\r', b'\n'], &mut out);
+        assert_eq!(out, [b'l', b's', b'\r', 0, IAC, IAC, b'\r', b'\n']);
+    }
+
+    #[test]
+    fn raw_sessions_pass_bytes_through_to_the_screen() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            use std::io::{Read, Write};
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"hello \xff raw\r\n").unwrap();
+            let mut received = [0u8; 5];
+            stream.read_exact(&mut received).unwrap();
+            received
+        });
+
+        let (ui_tx, ui_rx) = std::sync::mpsc::channel();
+        let (worker_tx, worker_rx) = std::sync::mpsc::channel();
+        let settings = ConnectionSettings {
+            protocol: ConnectionProtocol::Raw,
+            host: "127.0.0.1".to_string(),
+            port,
+            ..ConnectionSettings::default()
+        };
+        let handle = start_telnet(
+            1,
+            settings,
+            100,
+            ui_tx,
+            worker_rx,
+            std::env::temp_dir()
+                .join("rusty-telnet-test.log")
+                .display()
+                .to_string(),
+        );
+        worker_tx
+            .send(WorkerMessage::Input {
+                client_id: 1,
+                data: b"ab\xffc\r".to_vec(),
+            })
+            .unwrap();
+        assert_eq!(&server.join().unwrap(), b"ab\xffc\r");
+        handle.join().unwrap();
+
+        let mut saw_hello = false;
+        let mut statuses = Vec::new();
+        let mut connected = Vec::new();
+        for message in ui_rx.try_iter() {
+            match message {
+                UiMessage::Screen(screen) => saw_hello |= screen.contents().contains("hello"),
+                UiMessage::Status(status) => statuses.push(status.message),
+                UiMessage::Connected(ok) => connected.push(ok),
+                _ => {}
+            }
+        }
+        assert!(saw_hello);
+        assert_eq!(connected, [true, false]);
+        assert!(statuses.iter().any(|s| s.contains("not encrypted")));
+        assert_eq!(
+            statuses.last().map(String::as_str),
+            Some(format!("Connection closed by 127.0.0.1:{port}.").as_str())
+        );
+    }
+}
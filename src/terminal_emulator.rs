@@ -273,6 +273,22 @@ impl Screen {
         (self.cursor_row, self.cursor_col)
     }
 
+    /// Text left of the cursor on its row (typically a shell prompt); `None` while scrolled back.
+    pub fn text_before_cursor(&self) -> Option<String> {
+        if self.scrollback != 0 {
+            return None;
+        }
+        let mut line = String::new();
+        for col in 0..self.cursor_col {
+            match self.cell(self.cursor_row, col) {
+                Some(cell) if cell.is_wide_continuation() => {}
+                Some(cell) if cell.has_contents() => line.push_str(&cell.contents()),
+                _ => line.push(' '),
+            }
+        }
+        Some(line)
+    }
+
     pub fn contents(&self) -> String {
         let mut out = String::new();
         for row in 0..self.rows {